@@ -0,0 +1,46 @@
+//! Compares applying a deposit for every client ID with and without pre-allocating the clients and history maps. The
+//! rows are parsed up front, so that CSV parsing does not drown out the cost of growing the maps.
+//!
+//! Run with `cargo bench --bench client_capacity`
+
+use std::time::{Duration, Instant};
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+const CLIENTS: usize = u16::MAX as usize;
+const ITERATIONS: u32 = 20;
+
+/// One deposit per client ID, each with its own tx
+fn full_client_range_rows() -> Vec<InputRow> {
+    (0..CLIENTS)
+        .map(|client| InputRow::new("deposit", client as u16, client as u32, Some(Decimal::ONE)))
+        .collect()
+}
+
+/// Average time to build an engine with the given capacities and apply the rows
+fn time_processing(client_capacity: usize, history_capacity: usize) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let rows = full_client_range_rows();
+        let started = Instant::now();
+        TransactionEngine::builder()
+            .with_client_capacity(client_capacity)
+            .with_history_capacity(history_capacity)
+            .build_sharded(1)
+            .process_rows(rows)
+            .unwrap();
+        elapsed += started.elapsed();
+    }
+
+    elapsed / ITERATIONS
+}
+
+fn main() {
+    // warms up the allocator and caches so that the first measurement is not penalized
+    time_processing(0, 0);
+
+    let grown = time_processing(0, 0);
+    let preallocated = time_processing(CLIENTS, CLIENTS);
+    println!("{} clients, average of {} runs", CLIENTS, ITERATIONS);
+    println!("  grown from empty maps: {:?}", grown);
+    println!("  pre-allocated maps:    {:?}", preallocated);
+}
@@ -0,0 +1,92 @@
+//! Compares the heap held by an engine whose disputes were all resolved, with and without compacting the history. A
+//! counting allocator tracks the bytes that are still allocated once the rows were applied.
+//!
+//! Run with `cargo bench --bench history_compaction`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+const TRANSACTIONS: u32 = 200_000;
+const COMPACT_INTERVAL: u64 = 10_000;
+
+/// Counts the bytes currently allocated through the system allocator
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATED.fetch_add(new_size, Ordering::Relaxed);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// A deposit per tx that is disputed and resolved right away
+fn resolved_dispute_rows() -> Vec<InputRow> {
+    (0..TRANSACTIONS)
+        .flat_map(|tx| {
+            let client = (tx % u32::from(u16::MAX)) as u16;
+            [
+                InputRow::new("deposit", client, tx, Some(Decimal::ONE)),
+                InputRow::new("dispute", client, tx, None),
+                InputRow::new("resolve", client, tx, None),
+            ]
+        })
+        .collect()
+}
+
+/// Applies the rows and reports the bytes still allocated by the engine, which consumed them, and how long applying
+/// them took
+fn measure(auto_compact: bool) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let rows = resolved_dispute_rows();
+    let builder = TransactionEngine::builder();
+    let builder = if auto_compact {
+        builder.with_auto_compact(COMPACT_INTERVAL)
+    } else {
+        builder
+    };
+
+    let started = Instant::now();
+    let mut engine = builder.build_sharded(1);
+    engine.process_rows(rows).unwrap();
+    let elapsed = started.elapsed();
+    let retained = ALLOCATED.load(Ordering::Relaxed).saturating_sub(before);
+
+    println!(
+        "  {:<22} {:>6} KiB retained, {:?}",
+        if auto_compact {
+            "compacted history:"
+        } else {
+            "uncompacted history:"
+        },
+        retained / 1024,
+        elapsed
+    );
+    drop(engine);
+}
+
+fn main() {
+    println!(
+        "{} resolved disputes, compacting every {} rows",
+        TRANSACTIONS, COMPACT_INTERVAL
+    );
+    measure(false);
+    measure(true);
+}
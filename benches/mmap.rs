@@ -0,0 +1,50 @@
+//! Compares processing a large input file through a memory mapping and through tokio's file reads.
+//!
+//! Run with `cargo bench --bench mmap --features mmap`
+
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use transactions_demo::tx_engine::TransactionEngine;
+
+const ROWS: u32 = 1_000_000;
+const ITERATIONS: u32 = 5;
+
+/// One deposit per row, spread over every client ID
+fn deposits() -> String {
+    let mut input = String::from("type,client,tx,amount\n");
+    for tx in 0..ROWS {
+        writeln!(input, "deposit,{},{},1.5", tx % u32::from(u16::MAX), tx).unwrap();
+    }
+    input
+}
+
+/// Average time to process the file at `path`, discarding the output
+async fn time_processing(path: &Path, use_mmap: bool) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut engine = TransactionEngine::builder().with_mmap(use_mmap).build();
+        let started = Instant::now();
+        engine.process_file(path, tokio::io::sink()).await.unwrap();
+        elapsed += started.elapsed();
+    }
+
+    elapsed / ITERATIONS
+}
+
+#[tokio::main]
+async fn main() {
+    let path = std::env::temp_dir().join(format!("mmap_bench_{}.csv", std::process::id()));
+    tokio::fs::write(&path, deposits()).await.unwrap();
+
+    // warms up the page cache so that neither reader pays for the first read from disk
+    time_processing(&path, false).await;
+
+    let read = time_processing(&path, false).await;
+    let mapped = time_processing(&path, true).await;
+    println!("{} rows, average of {} runs", ROWS, ITERATIONS);
+    println!("  tokio file reads: {:?}", read);
+    println!("  memory-mapped:    {:?}", mapped);
+
+    let _ = std::fs::remove_file(&path);
+}
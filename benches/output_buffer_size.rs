@@ -0,0 +1,53 @@
+//! Compares writing every client's balances to a file with different output buffer sizes. The rows are applied before
+//! the timer starts, so that only serializing and writing the output is measured.
+//!
+//! Run with `cargo bench --bench output_buffer_size`
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+const CLIENTS: usize = u16::MAX as usize;
+const ITERATIONS: u32 = 20;
+const BUFFER_SIZES: [usize; 4] = [512, 8 * 1024, 64 * 1024, 1024 * 1024];
+
+/// One deposit per client ID, each with its own tx
+fn full_client_range_rows() -> Vec<InputRow> {
+    (0..CLIENTS)
+        .map(|client| InputRow::new("deposit", client as u16, client as u32, Some(Decimal::ONE)))
+        .collect()
+}
+
+/// Average time to write every client's balances to `path` through a buffer of `bytes`
+async fn time_writing(bytes: usize, path: &Path) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut engine = TransactionEngine::builder()
+            .with_output_buffer_size(bytes)
+            .build_sharded(1);
+        engine.process_rows(full_client_range_rows()).unwrap();
+        let output = tokio::fs::File::create(path).await.unwrap();
+
+        let started = Instant::now();
+        engine.write_output(output).await.unwrap();
+        elapsed += started.elapsed();
+    }
+
+    elapsed / ITERATIONS
+}
+
+#[tokio::main]
+async fn main() {
+    let path = std::env::temp_dir().join(format!("output_buffer_size_{}.csv", std::process::id()));
+
+    // warms up the allocator and caches so that the first measurement is not penalized
+    time_writing(BUFFER_SIZES[0], &path).await;
+
+    println!("{} clients, average of {} runs", CLIENTS, ITERATIONS);
+    for &bytes in BUFFER_SIZES.iter() {
+        let average = time_writing(bytes, &path).await;
+        println!("  {:>8} byte buffer: {:?}", bytes, average);
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,70 @@
+//! Compares processing the same CSV input with different numbers of parse workers. The input is built in memory up
+//! front, so that reading, parsing and applying the rows is measured, but not disk reads. Only deserializing is spread
+//! over the workers, so the speedup is bounded by the share of time spent deserializing rows.
+//!
+//! Run with `cargo bench --bench parallel_parse`
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+use transactions_demo::tx_engine::TransactionEngine;
+
+const TRANSACTIONS: u32 = 500_000;
+const ITERATIONS: u32 = 5;
+const WORKER_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+/// A deposit per tx spread over every client ID, with every tenth deposit disputed and resolved
+fn input() -> String {
+    let mut input = String::from("type,client,tx,amount\n");
+    for tx in 0..TRANSACTIONS {
+        let client = tx % u32::from(u16::MAX);
+        writeln!(input, "deposit,{},{},1.2345", client, tx).unwrap();
+        if tx % 10 == 0 {
+            writeln!(input, "dispute,{},{},", client, tx).unwrap();
+            writeln!(input, "resolve,{},{},", client, tx).unwrap();
+        }
+    }
+    input
+}
+
+/// Average time to process `input` with `workers` parse workers
+async fn time_processing(input: &str, workers: usize) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let mut engine = TransactionEngine::builder()
+            .with_parse_workers(workers)
+            .build();
+        let started = Instant::now();
+        engine
+            .process(input.as_bytes(), tokio::io::sink())
+            .await
+            .unwrap();
+        elapsed += started.elapsed();
+    }
+
+    elapsed / ITERATIONS
+}
+
+#[tokio::main]
+async fn main() {
+    let input = input();
+
+    // warms up the allocator and caches so that the first measurement is not penalized
+    time_processing(&input, 1).await;
+
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    println!(
+        "{} transactions, {} cores, average of {} runs",
+        TRANSACTIONS, cores, ITERATIONS
+    );
+    let serial = time_processing(&input, WORKER_COUNTS[0]).await;
+    println!("  {} worker:  {:?}", WORKER_COUNTS[0], serial);
+    for &workers in WORKER_COUNTS[1..].iter() {
+        let elapsed = time_processing(&input, workers).await;
+        println!(
+            "  {} workers: {:?} ({:.2}x)",
+            workers,
+            elapsed,
+            serial.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
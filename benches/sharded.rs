@@ -0,0 +1,63 @@
+//! Compares applying the same rows with different numbers of shards, each applied on its own thread. The rows are
+//! built up front, so that only partitioning and applying them is measured. The speedup is bounded by the number of
+//! available cores, which is printed along with the results.
+//!
+//! Run with `cargo bench --bench sharded`
+
+use std::thread;
+use std::time::{Duration, Instant};
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+const TRANSACTIONS: u32 = 500_000;
+const ITERATIONS: u32 = 5;
+const SHARD_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+/// A deposit per tx spread over every client ID, with every tenth deposit disputed and resolved
+fn rows() -> Vec<InputRow> {
+    let mut rows = Vec::new();
+    for tx in 0..TRANSACTIONS {
+        let client = (tx % u32::from(u16::MAX)) as u16;
+        rows.push(InputRow::new("deposit", client, tx, Some(Decimal::ONE)));
+        if tx % 10 == 0 {
+            rows.push(InputRow::new("dispute", client, tx, None));
+            rows.push(InputRow::new("resolve", client, tx, None));
+        }
+    }
+    rows
+}
+
+/// Average time to apply the rows across `shards` shards
+fn time_processing(shards: usize) -> Duration {
+    let mut elapsed = Duration::ZERO;
+    for _ in 0..ITERATIONS {
+        let rows = rows();
+        let mut engine = TransactionEngine::builder().build_sharded(shards);
+        let started = Instant::now();
+        engine.process_rows(rows).unwrap();
+        elapsed += started.elapsed();
+    }
+
+    elapsed / ITERATIONS
+}
+
+fn main() {
+    // warms up the allocator and caches so that the first measurement is not penalized
+    time_processing(1);
+
+    let cores = thread::available_parallelism().map_or(1, |cores| cores.get());
+    println!(
+        "{} transactions, {} cores, average of {} runs",
+        TRANSACTIONS, cores, ITERATIONS
+    );
+    let serial = time_processing(SHARD_COUNTS[0]);
+    println!("  {} shard:  {:?}", SHARD_COUNTS[0], serial);
+    for &shards in SHARD_COUNTS[1..].iter() {
+        let elapsed = time_processing(shards);
+        println!(
+            "  {} shards: {:?} ({:.2}x)",
+            shards,
+            elapsed,
+            serial.as_secs_f64() / elapsed.as_secs_f64()
+        );
+    }
+}
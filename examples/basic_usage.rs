@@ -0,0 +1,34 @@
+//! Processes a small CSV held in memory, checks the resulting balances and prints them as CSV
+//!
+//! Run with `cargo run --example basic_usage`
+
+use std::error::Error;
+use transactions_demo::tx_engine::{Decimal, TransactionEngine};
+
+const TRANSACTIONS: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.5
+withdrawal,1,3,2.5
+dispute,2,2,
+chargeback,2,2,
+";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(TRANSACTIONS).await?;
+
+    let first = engine.query_client(1).ok_or("client 1 has no account")?;
+    assert_eq!(first.available(), Decimal::new(75, 1));
+    assert!(!first.locked());
+
+    // the chargeback withdrew the disputed deposit and locked the account
+    let second = engine.query_client(2).ok_or("client 2 has no account")?;
+    assert_eq!(second.total(), Decimal::ZERO);
+    assert!(second.locked());
+
+    // accounts are sorted by client ID
+    print!("{}", engine.to_csv_string().await?);
+    Ok(())
+}
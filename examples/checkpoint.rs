@@ -0,0 +1,46 @@
+//! Saves the engine's state between two batches of transactions and carries on from it in a new engine, as a service
+//! restarting between batches would
+//!
+//! Run with `cargo run --example checkpoint`
+
+use std::error::Error;
+use std::fs::File;
+use transactions_demo::tx_engine::{Decimal, TransactionEngine};
+
+const FIRST_BATCH: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,4.0
+dispute,1,1,
+";
+
+// resolves the dispute opened by the first batch
+const SECOND_BATCH: &str = "\
+type,client,tx,amount
+resolve,1,1,
+withdrawal,1,3,3.0
+";
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let path = std::env::temp_dir().join(format!("checkpoint-{}.json", std::process::id()));
+
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(FIRST_BATCH).await?;
+    engine.save_checkpoint(File::create(&path)?)?;
+
+    let mut restarted = TransactionEngine::builder().build();
+    restarted.load_checkpoint(File::open(&path)?)?;
+    std::fs::remove_file(&path)?;
+
+    // unlike `process_str`, feeding rows carries on from the current state
+    restarted.feed(SECOND_BATCH.as_bytes()).await?;
+    restarted.finish().await?;
+
+    let client = restarted.query_client(1).ok_or("client 1 has no account")?;
+    assert_eq!(client.available(), Decimal::from(7));
+    assert_eq!(client.held(), Decimal::ZERO);
+
+    print!("{}", restarted.to_csv_string().await?);
+    Ok(())
+}
@@ -0,0 +1,31 @@
+//! Feeds rows to the engine through a channel from a separate task, as a service receiving transactions over the
+//! network would
+//!
+//! Run with `cargo run --example streaming`
+
+use std::error::Error;
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let (rows_tx, rows_rx) = tokio::sync::mpsc::channel(16);
+
+    let producer = tokio::spawn(async move {
+        for tx in 1..=10 {
+            let row = InputRow::new("deposit", (tx % 2) as u16, tx, Some(Decimal::from(tx)));
+            if rows_tx.send(row).await.is_err() {
+                break;
+            }
+        }
+
+        // dropping the sender ends processing once the engine has drained the channel
+    });
+
+    let mut engine = TransactionEngine::builder().build();
+    let stats = engine.process_channel(rows_rx, tokio::io::stdout()).await?;
+    producer.await?;
+
+    assert_eq!(stats.transactions_processed, 10);
+    assert_eq!(stats.clients, 2);
+    Ok(())
+}
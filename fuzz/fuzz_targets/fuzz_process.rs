@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use transactions_demo::tx_engine::{HistoryEvictionPolicy, TransactionEngine};
+
+// bounds the history so that inputs with many distinct txs cannot exhaust memory. Clients are bounded by their u16 IDs
+const MAX_HISTORY_ENTRIES: usize = 10_000;
+
+fuzz_target!(|data: &[u8]| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(MAX_HISTORY_ENTRIES, HistoryEvictionPolicy::EvictLru)
+        .build();
+
+    // errors are fine as long as they are reported as a `TransactionError` rather than a panic
+    if runtime.block_on(engine.process_bytes(data)).is_err() {
+        return;
+    }
+
+    if let Err(violations) = engine.verify_invariants() {
+        panic!("invariants violated: {:?}", violations);
+    }
+});
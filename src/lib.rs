@@ -0,0 +1 @@
+pub mod tx_engine;
@@ -1,20 +1,179 @@
-use crate::tx_engine::TransactionEngine;
+use clap::{Parser, ValueEnum};
 use std::error::Error;
+use transactions_demo::tx_engine::{SortField, TransactionEngine};
 
-mod tx_engine;
+/// Processes a CSV of transactions and outputs the resulting client balances as CSV to stdout
+#[derive(Parser)]
+#[command(name = "tx-engine", version)]
+struct Args {
+    /// The input CSV file
+    #[arg(required_unless_present = "template")]
+    input_file: Option<String>,
+    /// Prints a CSV template with the expected headers instead of processing an input
+    #[arg(long, conflicts_with = "input_file")]
+    template: bool,
+    /// Treats the input as a log of transactions and checks that replaying it reproduces the balances in this CSV,
+    /// output by a previous run, instead of outputting them. Diverging accounts are printed as deltas from the
+    /// expected balances
+    #[arg(long, value_name = "EXPECTED_FILE", conflicts_with = "template")]
+    verify: Option<String>,
+    /// Processes this earlier input independently and outputs how each client's balances changed from it to the input
+    #[arg(long, value_name = "EARLIER_FILE", conflicts_with_all = ["template", "verify"])]
+    delta_from: Option<String>,
+    /// Outputs a report instead of the balances
+    #[arg(long, value_enum, conflicts_with_all = ["template", "verify", "delta_from"])]
+    report: Option<ReportKind>,
+    /// Outputs a table of the accounts sorted by total balance instead of CSV
+    #[arg(long, conflicts_with_all = ["template", "verify", "report", "delta_from"])]
+    human: bool,
+    /// Only reads the first N rows of the input, e.g. to sanity check a sample of a huge file
+    #[arg(long, value_name = "N")]
+    max_rows: Option<u64>,
+    /// Skips the first N rows after the header, e.g. to restart after the rows a failed run already applied.
+    /// Disputes of transactions in the skipped rows are rejected
+    #[arg(long, value_name = "N")]
+    skip_rows: Option<u64>,
+    /// Skips rows until the first row with a client ID of N or higher. Disputes of transactions in the skipped rows
+    /// are rejected
+    #[arg(long, value_name = "N")]
+    start_at_client: Option<u16>,
+    /// Prints how many disputes were opened, resolved and charged back, and the amounts involved, to stderr after
+    /// outputting the balances
+    #[arg(long, conflicts_with_all = ["template", "verify", "report", "human", "delta_from"])]
+    dispute_summary: bool,
+    /// Skips input lines starting with `#`, such as the comments of the template printed by `--template`
+    #[arg(long, conflicts_with = "template")]
+    skip_comment_lines: bool,
+    /// Order of the accounts in the output
+    #[arg(long, value_enum, default_value = "client_id")]
+    sort_by: SortBy,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+#[value(rename_all = "snake_case")]
+enum SortBy {
+    ClientId,
+    /// Highest total first
+    TotalBalance,
+    /// Highest available funds first
+    AvailableBalance,
+    /// Order of each client's first transaction
+    InsertionOrder,
+}
+
+impl From<SortBy> for SortField {
+    fn from(sort_by: SortBy) -> Self {
+        match sort_by {
+            SortBy::ClientId => SortField::ClientId,
+            SortBy::TotalBalance => SortField::TotalBalance,
+            SortBy::AvailableBalance => SortField::AvailableBalance,
+            SortBy::InsertionOrder => SortField::InsertionOrder,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportKind {
+    /// The number of rejected transactions and the most recent rejection of each client
+    RejectionsPerClient,
+}
 
 /// Will output to stdout the CSV as desired. For performance in case of large inputs, or from TCP streams, this program uses asynchronous processing of CSVs
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let mut args: Vec<String> = std::env::args().collect();
+    let args = Args::parse();
 
-    // There should be two arguments, the first being the binary name (automatically passed) and the second being the input file (manually passed)
-    if args.len() != 2 {
-        panic!("Invalid number of arguments. Expected an input file with no additional arguments");
-    }
+    let input_file = match args.input_file {
+        Some(input_file) if !args.template => input_file,
+        _ => {
+            TransactionEngine::write_csv_template(tokio::io::stdout()).await?;
+            return Ok(());
+        }
+    };
 
-    let input_file = args.remove(1);
     let output = tokio::io::stdout();
+    let mut builder = TransactionEngine::builder()
+        .with_track_rejections(args.report.is_some())
+        .with_skip_comment_lines(args.skip_comment_lines)
+        .with_sort_output_by(args.sort_by.into());
+    if let Some(max_rows) = args.max_rows {
+        builder = builder.with_max_rows(max_rows);
+    }
+    if let Some(skip_rows) = args.skip_rows {
+        builder = builder.with_skip_rows(skip_rows);
+    }
+    if let Some(client) = args.start_at_client {
+        builder = builder.with_start_at_client(client);
+    }
+    #[cfg(all(unix, feature = "signal-handling"))]
+    let builder = builder.with_shutdown(shutdown_on_signals()?);
+    let mut engine = builder.try_build()?;
+
+    if let Some(ReportKind::RejectionsPerClient) = args.report {
+        engine.process_file(input_file, tokio::io::sink()).await?;
+        return Ok(engine.write_rejections_per_client(output).await?);
+    }
+
+    if args.human {
+        engine.process_file(input_file, tokio::io::sink()).await?;
+        engine.report().print_to_stdout();
+        return Ok(());
+    }
+
+    if let Some(earlier_file) = args.delta_from {
+        let earlier = tokio::fs::File::open(earlier_file).await?;
+        let later = tokio::fs::File::open(input_file).await?;
+        engine.process_delta(earlier, later, output).await?;
+        return Ok(());
+    }
+
+    let expected_file = match args.verify {
+        Some(expected_file) => expected_file,
+        None => {
+            engine.process_file(input_file, output).await?;
+            if args.dispute_summary {
+                eprintln!("{}", engine.dispute_summary());
+            }
+            return Ok(());
+        }
+    };
+
+    let expected =
+        TransactionEngine::from_output_csv_str(&tokio::fs::read_to_string(expected_file).await?)
+            .await?;
+    engine.process_file(input_file, tokio::io::sink()).await?;
+
+    let diverging = engine.diverging_accounts(&expected);
+    if diverging.is_empty() {
+        eprintln!("Replayed balances match the expected balances");
+        return Ok(());
+    }
+
+    engine.write_delta_output(&expected, output).await?;
+    Err(format!(
+        "{} account(s) diverge from the expected balances",
+        diverging.len()
+    )
+    .into())
+}
+
+/// Requests a graceful shutdown on SIGTERM (e.g. `docker stop`) or SIGINT. The balances accumulated so far are still
+/// written, and the process exits with an error to indicate the input was not fully processed
+#[cfg(all(unix, feature = "signal-handling"))]
+fn shutdown_on_signals() -> std::io::Result<tokio::sync::watch::Receiver<bool>> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+    tokio::spawn(async move {
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = interrupt.recv() => {}
+        }
+
+        let _ = shutdown_tx.send(true);
+    });
 
-    TransactionEngine::process_file(input_file, output).await
+    Ok(shutdown_rx)
 }
@@ -1,24 +1,239 @@
+pub use crate::tx_engine::account_events::{AccountEvent, Balances};
+#[cfg(feature = "checksum")]
+pub use crate::tx_engine::checksum::HashingReader;
+#[cfg(feature = "async")]
+pub use crate::tx_engine::comments::CommentFilter;
+#[cfg(feature = "compression")]
+pub use crate::tx_engine::compression::{decompress_detected, Compression};
+pub use crate::tx_engine::config::{
+    AmountRounding, AmountTransform, ClientFilter, CounterOverflow, EventHandler,
+    HistoryEvictionPolicy, LineTerminator, LockedDisputePolicy, LockedFormat, LowBalanceHandler,
+    SortField, TotalFloorPolicy, TransactionEngineBuilder, TransactionEngineConfig, VetoHandler,
+};
+pub use crate::tx_engine::config_validation::ConfigError;
+pub use crate::tx_engine::correction::BalanceCorrection;
+use crate::tx_engine::counters::increment;
+pub use crate::tx_engine::delta::{BalanceChangeRow, DeltaRow};
+#[cfg(feature = "encoding")]
+pub use crate::tx_engine::encoding::{InputEncoding, Transcoder};
+pub use crate::tx_engine::error::TransactionError;
+#[cfg(feature = "async")]
+pub use crate::tx_engine::events::DomainEvent;
+pub use crate::tx_engine::fraud::{
+    FraudRule, FraudScore, NewAccountLargeDepositRule, RoundAmountRule, VelocityRule,
+    MAX_FRAUD_SCORE,
+};
+pub use crate::tx_engine::invariants::InvariantViolation;
+#[cfg(feature = "kafka")]
+pub use crate::tx_engine::kafka::KafkaPayload;
+#[cfg(feature = "async")]
+pub use crate::tx_engine::ledger::LedgerRow;
+pub use crate::tx_engine::multi_channel::SelectBias;
+pub use crate::tx_engine::normalize::InputNormalizer;
+pub use crate::tx_engine::output_validation::OutputValidationError;
+use crate::tx_engine::parse::RawInputRow;
 use crate::tx_engine::processors::{
-    process_chargeback, process_deposit, process_dispute, process_resolve, process_withdrawal,
+    get_disputable_amount, process_chargeback, process_deposit, process_dispute, process_resolve,
+    process_settle, process_transfer, process_withdrawal,
 };
+#[cfg(feature = "async")]
+use crate::tx_engine::progress::Progress;
+#[cfg(feature = "async")]
+pub use crate::tx_engine::progress::ProgressUpdate;
+pub use crate::tx_engine::rejection_sink::RejectionSink;
+use crate::tx_engine::report::DEFAULT_TERMINAL_WIDTH;
+pub use crate::tx_engine::report::{
+    DisputeSummary, OpenDispute, RejectionsPerClientRow, Report, TransactionSummary,
+};
+#[cfg(feature = "io-retry")]
+pub use crate::tx_engine::retry::{IoRetryConfig, RetryingReader};
+#[cfg(feature = "async")]
+use crate::tx_engine::schema::validate_headers;
+pub use crate::tx_engine::schema::SchemaError;
+pub use crate::tx_engine::sharded::{ShardFn, ShardedTransactionEngine};
+#[cfg(feature = "async")]
+use crate::tx_engine::trailer::TrailingNewline;
+use hashlink::LinkedHashSet;
+pub use rust_decimal::Decimal;
+#[cfg(any(feature = "async", feature = "sync"))]
+use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::error::Error;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+#[cfg(feature = "async")]
 use std::path::Path;
-use tokio::io::{AsyncRead, AsyncWrite};
-use tokio_stream::StreamExt;
+use std::sync::Arc;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufWriter};
+#[cfg(feature = "async")]
+use tokio::task::JoinSet;
+#[cfg(feature = "async")]
+use tokio_stream::{Stream, StreamExt};
+
+mod account_events;
+#[cfg(feature = "arrow")]
+mod arrow_export;
+mod checkpoint;
+#[cfg(feature = "checksum")]
+mod checksum;
+#[cfg(feature = "s3")]
+mod cloud;
+#[cfg(feature = "async")]
+mod comments;
+#[cfg(feature = "compression")]
+mod compression;
+mod config;
+mod config_validation;
+mod correction;
+mod counters;
+mod dedup;
+mod delta;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod error;
+#[cfg(feature = "async")]
+mod events;
+mod eviction;
+#[cfg(feature = "async")]
+mod feed;
+mod fraud;
+mod interest;
+mod invariants;
+#[cfg(feature = "kafka")]
+mod kafka;
+#[cfg(feature = "async")]
+mod ledger;
+#[cfg(feature = "async")]
+mod merge;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod multi_channel;
+mod normalize;
+mod output_validation;
+#[cfg(feature = "async")]
+mod parallel_parse;
+mod parse;
+mod processors;
+#[cfg(feature = "async")]
+mod progress;
+#[cfg(feature = "async")]
+mod quarantine;
+mod rejection_sink;
+mod report;
+#[cfg(feature = "io-retry")]
+mod retry;
+mod schema;
+#[cfg(feature = "async")]
+mod server;
+mod sharded;
+#[cfg(all(unix, feature = "unix-socket"))]
+mod socket;
+#[cfg(feature = "sse")]
+mod sse;
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(all(test, feature = "async"))]
+mod tests;
+#[cfg(feature = "async")]
+mod trailer;
+
+/// Number of decimal places amounts are rounded to before they are applied
+pub(crate) const AMOUNT_DECIMAL_PLACES: u32 = 4;
 
 #[derive(Deserialize)]
+#[serde(try_from = "RawInputRow")]
 /// Rows parsed from an input CSV
 pub struct InputRow {
     r#type: String,
     client: u16,
     tx: u32,
     // Requires up to 4 sig figs. Uses optional field since disputes, resolves, and chargebacks may have an empty "amount" field
-    amount: Option<f32>,
+    amount: Option<Decimal>,
+    // Optional feed-specific category (e.g. "fee" or "interest") used to filter heterogeneous inputs
+    #[serde(default)]
+    category: Option<String>,
+    // Optional flag crediting a deposit to the pending balance until it is settled
+    #[serde(default)]
+    pending: Option<bool>,
+    // Optional sub-account of the client, only used when `multi_ledger` is enabled
+    #[serde(default)]
+    ledger: Option<String>,
+    // Optional currency of the amount, only used when `multi_currency` is enabled
+    #[serde(default)]
+    currency: Option<String>,
+    // Optional time of the transaction in seconds since the Unix epoch
+    #[serde(default)]
+    timestamp: Option<u64>,
+    // Client credited by a transfer. Required for transfers, ignored otherwise
+    #[serde(default)]
+    target_client: Option<u16>,
+    // Ledger credited by a transfer, only used when `multi_ledger` is enabled
+    #[serde(default)]
+    target_ledger: Option<String>,
 }
 
 impl InputRow {
+    /// Creates a row for sources that do not go through CSV, such as channels
+    pub fn new(tx_type: &str, client: u16, tx: u32, amount: Option<Decimal>) -> Self {
+        Self {
+            r#type: tx_type.to_string(),
+            client,
+            tx,
+            amount,
+            category: None,
+            pending: None,
+            ledger: None,
+            currency: None,
+            timestamp: None,
+            target_client: None,
+            target_ledger: None,
+        }
+    }
+
+    /// Creates a transfer of `amount` from `client` to `target_client`
+    pub fn transfer(client: u16, tx: u32, amount: Decimal, target_client: u16) -> Self {
+        Self {
+            target_client: Some(target_client),
+            ..Self::new("transfer", client, tx, Some(amount))
+        }
+    }
+
+    pub fn client(&self) -> u16 {
+        self.client
+    }
+
+    pub fn tx(&self) -> u32 {
+        self.tx
+    }
+
+    pub fn amount(&self) -> Option<Decimal> {
+        self.amount
+    }
+
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn ledger(&self) -> Option<&str> {
+        self.ledger.as_deref()
+    }
+
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    pub fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    pub fn target_client(&self) -> Option<u16> {
+        self.target_client
+    }
+
+    pub fn target_ledger(&self) -> Option<&str> {
+        self.target_ledger.as_deref()
+    }
+
     fn transaction_type(&self) -> Option<TransactionType> {
         match self.r#type.as_str() {
             "deposit" => Some(TransactionType::Deposit),
@@ -26,28 +241,589 @@ impl InputRow {
             "dispute" => Some(TransactionType::Dispute),
             "resolve" => Some(TransactionType::Resolve),
             "chargeback" => Some(TransactionType::Chargeback),
+            "settle" => Some(TransactionType::Settle),
+            "transfer" => Some(TransactionType::Transfer),
+            _ => None,
+        }
+    }
+}
+
+/// A validated transaction. Converted from an [`InputRow`] at the boundary so that processors never see invalid states
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Transaction {
+    Deposit {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        /// Credited to the pending balance until a matching settle
+        pending: bool,
+    },
+    Withdrawal {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+    },
+    Dispute {
+        client: u16,
+        tx: u32,
+        /// The amount the dispute claims the disputed transaction had, if the row included one
+        claimed_amount: Option<Decimal>,
+        /// The funds held for this dispute, set once it is applied
+        #[serde(default)]
+        held: Option<Decimal>,
+    },
+    Resolve {
+        client: u16,
+        tx: u32,
+    },
+    Chargeback {
+        client: u16,
+        tx: u32,
+    },
+    Settle {
+        client: u16,
+        tx: u32,
+    },
+    /// Moves funds from the client's available funds to another account. Not kept in the history, so it cannot be
+    /// disputed
+    Transfer {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        target_client: u16,
+        /// Only used when `multi_ledger` is enabled
+        target_ledger: Option<String>,
+    },
+}
+
+impl Transaction {
+    pub fn client(&self) -> u16 {
+        match *self {
+            Transaction::Deposit { client, .. }
+            | Transaction::Withdrawal { client, .. }
+            | Transaction::Dispute { client, .. }
+            | Transaction::Resolve { client, .. }
+            | Transaction::Chargeback { client, .. }
+            | Transaction::Settle { client, .. }
+            | Transaction::Transfer { client, .. } => client,
+        }
+    }
+
+    pub fn tx(&self) -> u32 {
+        match *self {
+            Transaction::Deposit { tx, .. }
+            | Transaction::Withdrawal { tx, .. }
+            | Transaction::Dispute { tx, .. }
+            | Transaction::Resolve { tx, .. }
+            | Transaction::Chargeback { tx, .. }
+            | Transaction::Settle { tx, .. }
+            | Transaction::Transfer { tx, .. } => tx,
+        }
+    }
+
+    /// Only deposits, withdrawals and transfers carry an amount
+    pub fn amount(&self) -> Option<Decimal> {
+        match *self {
+            Transaction::Deposit { amount, .. }
+            | Transaction::Withdrawal { amount, .. }
+            | Transaction::Transfer { amount, .. } => Some(amount),
             _ => None,
         }
     }
+
+    pub fn transaction_type(&self) -> TransactionType {
+        match self {
+            Transaction::Deposit { .. } => TransactionType::Deposit,
+            Transaction::Withdrawal { .. } => TransactionType::Withdrawal,
+            Transaction::Dispute { .. } => TransactionType::Dispute,
+            Transaction::Resolve { .. } => TransactionType::Resolve,
+            Transaction::Chargeback { .. } => TransactionType::Chargeback,
+            Transaction::Settle { .. } => TransactionType::Settle,
+            Transaction::Transfer { .. } => TransactionType::Transfer,
+        }
+    }
+}
+
+impl TryFrom<&InputRow> for Transaction {
+    type Error = TransactionError;
+
+    fn try_from(row: &InputRow) -> Result<Self, Self::Error> {
+        let tx_type = row
+            .transaction_type()
+            .ok_or_else(|| TransactionError::InvalidTransactionType(row.r#type.clone()))?;
+        let InputRow {
+            client,
+            tx,
+            amount,
+            pending,
+            target_client,
+            ..
+        } = *row;
+
+        let checked_amount = || match amount {
+            Some(amount) if amount.is_sign_negative() => {
+                Err(TransactionError::InvalidAmount { tx, amount })
+            }
+            Some(amount) => Ok(amount),
+            None => Err(TransactionError::MissingAmount { tx }),
+        };
+
+        Ok(match tx_type {
+            TransactionType::Deposit => Transaction::Deposit {
+                client,
+                tx,
+                amount: checked_amount()?,
+                pending: pending.unwrap_or(false),
+            },
+            TransactionType::Withdrawal => Transaction::Withdrawal {
+                client,
+                tx,
+                amount: checked_amount()?,
+            },
+            TransactionType::Dispute => Transaction::Dispute {
+                client,
+                tx,
+                claimed_amount: amount,
+                held: None,
+            },
+            TransactionType::Resolve => Transaction::Resolve { client, tx },
+            TransactionType::Chargeback => Transaction::Chargeback { client, tx },
+            TransactionType::Settle => Transaction::Settle { client, tx },
+            TransactionType::Transfer => Transaction::Transfer {
+                client,
+                tx,
+                amount: checked_amount()?,
+                target_client: target_client
+                    .ok_or(TransactionError::MissingTransferTarget { tx })?,
+                target_ledger: row.target_ledger.clone(),
+            },
+        })
+    }
+}
+
+/// Identifies an account: a client, one of its ledgers and a currency. The ledger is empty unless `multi_ledger` is
+/// enabled, and for rows without a ledger. Likewise, the currency is empty unless `multi_currency` is enabled
+pub type AccountKey = (u16, String, String);
+
+/// Encodings available for reports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Json,
 }
 
 /// The output type
-#[derive(Default, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct OutputRow {
     client: u16,
-    available: f32,
-    held: f32,
-    total: f32,
+    // Only set (and output) when `multi_ledger` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ledger: Option<String>,
+    // Only set (and output) in multi-currency mode. Single-currency output has no currency column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+    available: Decimal,
+    held: Decimal,
+    // Deposits that have not settled yet. Not part of `total`. Only tracked (and output) when `track_pending` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pending: Option<Decimal>,
+    total: Decimal,
+    // Accepts every `LockedFormat`, so that output written with any of them can be read back
+    #[serde(deserialize_with = "deserialize_locked")]
     locked: bool,
+    // Only tracked when `track_rejections` is set. Never part of the balances output
+    #[serde(skip)]
+    last_rejection: Option<RejectionInfo>,
+    #[serde(skip)]
+    rejection_count: u32,
+    // Transactions applied to or rejected for the account
+    #[serde(skip)]
+    transaction_count: u64,
+    // Disputes, resolves and chargebacks applied to the account
+    #[serde(skip)]
+    dispute_activity_count: u32,
+    // Only tracked when `track_chargeback_totals` is set. Never part of the balances output
+    #[serde(skip)]
+    total_chargebacked: Decimal,
+    #[serde(skip)]
+    chargeback_count: u32,
+    // Position of the account among the engine's accounts in order of creation, for `SortField::InsertionOrder`
+    #[serde(skip)]
+    created_seq: u64,
+}
+
+/// Every column of the balances output, in the order they are written unless `output_columns` says otherwise
+pub(crate) const OUTPUT_COLUMNS: [&str; 8] = [
+    "client",
+    "ledger",
+    "currency",
+    "available",
+    "held",
+    "pending",
+    "total",
+    "locked",
+];
+
+/// An output row as written, with `locked` in the configured format and only the configured columns
+#[cfg(any(feature = "async", feature = "sync"))]
+struct FormattedOutputRow<'a> {
+    client: u16,
+    ledger: Option<&'a str>,
+    currency: Option<&'a str>,
+    available: Decimal,
+    held: Decimal,
+    pending: Option<Decimal>,
+    total: Decimal,
+    locked: &'static str,
+    // `output_columns`. When unset, the optional columns are only written if the row has a value for them
+    columns: Option<&'a [String]>,
+}
+
+#[cfg(any(feature = "async", feature = "sync"))]
+impl FormattedOutputRow<'_> {
+    /// The columns to write, in order. Fails with the first name that is not an output column, which
+    /// `TransactionEngineConfig::validate` already reports before anything is written
+    fn columns(&self) -> Result<Vec<&'static str>, &str> {
+        match self.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|name| {
+                    OUTPUT_COLUMNS
+                        .iter()
+                        .copied()
+                        .find(|column| column == name)
+                        .ok_or(name.as_str())
+                })
+                .collect(),
+            None => Ok(OUTPUT_COLUMNS
+                .iter()
+                .copied()
+                .filter(|column| match *column {
+                    "ledger" => self.ledger.is_some(),
+                    "currency" => self.currency.is_some(),
+                    "pending" => self.pending.is_some(),
+                    _ => true,
+                })
+                .collect()),
+        }
+    }
+}
+
+#[cfg(any(feature = "async", feature = "sync"))]
+impl Serialize for FormattedOutputRow<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let columns = self.columns().map_err(|name| {
+            serde::ser::Error::custom(format!("unknown output column {:?}", name))
+        })?;
+        let mut row = serializer.serialize_struct("OutputRow", columns.len())?;
+        for column in columns {
+            match column {
+                "client" => row.serialize_field(column, &self.client)?,
+                "ledger" => row.serialize_field(column, &self.ledger)?,
+                "currency" => row.serialize_field(column, &self.currency)?,
+                "available" => row.serialize_field(column, &self.available)?,
+                "held" => row.serialize_field(column, &self.held)?,
+                "pending" => row.serialize_field(column, &self.pending)?,
+                "total" => row.serialize_field(column, &self.total)?,
+                _ => row.serialize_field(column, self.locked)?,
+            }
+        }
+        row.end()
+    }
+}
+
+fn deserialize_locked<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    LockedFormat::parse(&value)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid locked value {:?}", value)))
+}
+
+/// The most recent transaction refused for an account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RejectionInfo {
+    pub tx: u32,
+    pub reason: RejectionReason,
+    /// The transaction's amount, or the disputed transaction's for disputes. Zero if neither is known
+    pub amount: Decimal,
+}
+
+impl OutputRow {
+    pub fn client(&self) -> u16 {
+        self.client
+    }
+
+    #[cfg(any(feature = "async", feature = "sync"))]
+    fn formatted<'a>(&'a self, config: &'a TransactionEngineConfig) -> FormattedOutputRow<'a> {
+        FormattedOutputRow {
+            client: self.client,
+            ledger: self.ledger.as_deref(),
+            currency: self.currency.as_deref(),
+            available: self.available,
+            held: self.held,
+            pending: self.pending,
+            total: self.total,
+            locked: config.locked_field_format.format(self.locked),
+            columns: config.output_columns.as_deref(),
+        }
+    }
+
+    /// `None` unless the engine runs in multi-ledger mode
+    pub fn ledger(&self) -> Option<&str> {
+        self.ledger.as_deref()
+    }
+
+    /// `None` unless the engine runs in multi-currency mode
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    /// The key of this row's account in the engine
+    pub fn account(&self) -> AccountKey {
+        (
+            self.client,
+            self.ledger.clone().unwrap_or_default(),
+            self.currency.clone().unwrap_or_default(),
+        )
+    }
+
+    pub fn available(&self) -> Decimal {
+        self.available
+    }
+
+    pub fn held(&self) -> Decimal {
+        self.held
+    }
+
+    /// `None` unless pending deposits are tracked
+    pub fn pending(&self) -> Option<Decimal> {
+        self.pending
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.total
+    }
+
+    pub fn locked(&self) -> bool {
+        self.locked
+    }
+
+    /// `None` unless `track_rejections` is enabled
+    pub fn last_rejection(&self) -> Option<RejectionInfo> {
+        self.last_rejection
+    }
+
+    /// Always 0 unless `track_rejections` is enabled
+    pub fn rejection_count(&self) -> u32 {
+        self.rejection_count
+    }
+
+    /// Number of transactions applied to or rejected for the account
+    pub fn transaction_count(&self) -> u64 {
+        self.transaction_count
+    }
+
+    /// Number of disputes, resolves and chargebacks applied to the account
+    pub fn dispute_activity_count(&self) -> u32 {
+        self.dispute_activity_count
+    }
+
+    /// Sum of the amounts charged back. Always 0 unless `track_chargeback_totals` is enabled
+    pub fn total_chargebacked(&self) -> Decimal {
+        self.total_chargebacked
+    }
+
+    /// Always 0 unless `track_chargeback_totals` is enabled
+    pub fn chargeback_count(&self) -> u32 {
+        self.chargeback_count
+    }
+}
+
+/// Passed to the low balance handler when a withdrawal drops a client's available funds below the configured threshold
+#[derive(Debug, Clone)]
+pub struct LowBalanceEvent {
+    pub client: u16,
+    pub tx: u32,
+    pub available_after: Decimal,
+    pub threshold: Decimal,
+}
+
+/// Passed to the dispute handler after a dispute moves funds from available to held
+#[derive(Debug, Clone)]
+pub struct DisputeEvent {
+    pub client: u16,
+    pub tx: u32,
+    pub amount: Decimal,
+    pub held_after: Decimal,
+}
+
+/// Passed to the resolve handler after a dispute is resolved and its funds are released from hold
+#[derive(Debug, Clone)]
+pub struct ResolveEvent {
+    pub client: u16,
+    pub tx: u32,
+    pub amount: Decimal,
+    pub available_after: Decimal,
+}
+
+/// Passed to the chargeback handler after disputed funds are withdrawn and the account is locked
+#[derive(Debug, Clone)]
+pub struct ChargebackEvent {
+    pub client: u16,
+    pub tx: u32,
+    pub amount: Decimal,
+    pub total_after: Decimal,
+}
+
+/// Why a transaction was not applied
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize)]
+pub enum RejectionReason {
+    /// The client does not have enough available funds
+    InsufficientFunds,
+    /// Applying the transaction would drop the client's available funds below the configured floor
+    BelowMinimum,
+    /// The amount is larger than the configured maximum for its transaction type
+    AmountExceedsLimit,
+    /// The deposit's fraud score exceeded the threshold and `reject_suspected_fraud` is enabled
+    FraudSuspected,
+    /// The deposit is pending but `track_pending` is not enabled
+    PendingNotTracked,
+    /// The dispute arrived after the account was locked and `locked_dispute_policy` is `Ignore`
+    AccountLocked,
+    /// Rejected by the configured veto callback, with the reason it gave
+    Vetoed(&'static str),
+    /// The dispute references a transaction that does not exist or was compacted away
+    TransactionNotFound,
+    /// The dispute's amount differs from the disputed transaction's and `match_dispute_amounts` is enabled
+    DisputeAmountMismatch,
+    /// The client is excluded by the configured `client_filter`
+    ClientNotAllowed,
+    /// The resolve references a transaction that is not under dispute
+    NotDisputed,
+    /// The client deposited the same amount within `deposit_dedup_window`
+    DuplicateDeposit,
+    /// The client already has `max_open_disputes_per_client` open disputes
+    TooManyOpenDisputes,
+    /// The deposit or withdrawal has no amount and `validate_row_shape` is enabled
+    MissingAmount,
+    /// The dispute, resolve or chargeback has an amount and `validate_row_shape` is enabled. Disputes may have one
+    /// when `match_dispute_amounts` is enabled
+    UnexpectedAmount,
+    /// The dispute would hold more than the client's total, e.g. for a deposit that was partly withdrawn since, and
+    /// `reject_disputes_exceeding_total` is enabled
+    DisputeExceedsTotal,
+    /// The transfer's target is the account it transfers from
+    TransferToSelf,
+    /// The withdrawal or transfer would drive the client's available funds beyond the configured `overdraft_limit`
+    OverdraftLimitExceeded,
+    /// The dispute references a transaction that is already under dispute
+    AlreadyDisputed,
 }
 
-#[derive(Eq, PartialEq, Hash)]
+/// Returned by the veto callback to decide what happens to a transaction before it is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Applies the transaction as usual
+    Accept,
+    /// Records the transaction as rejected with [`RejectionReason::Vetoed`] instead of applying it
+    Reject(&'static str),
+    /// Stops processing with [`TransactionError::Aborted`]
+    Abort,
+}
+
+/// Why a row was deliberately not processed
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub enum SkipReason {
+    /// The row's category is not in the configured allow-list
+    CategoryNotAllowed(String),
+    /// The row's timestamp is outside the configured time window, or the row has none
+    OutsideTimeWindow(Option<u64>),
+    /// Processing of the row's transaction type is disabled
+    TypeDisabled(TransactionType),
+}
+
+/// A row that was skipped before being applied
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedRow {
+    pub client: u16,
+    pub tx: u32,
+    pub reason: SkipReason,
+}
+
+/// Why a transaction was flagged for review. Flagged transactions are still applied unless configured otherwise
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub enum AuditFlag {
+    /// The deposit's aggregate fraud score exceeded the configured threshold
+    FraudSuspected { score: FraudScore },
+    /// The client's balances were corrected with [`TransactionEngine::apply_correction`]. Not tied to a transaction,
+    /// so the entry's tx is 0
+    Corrected(BalanceCorrection),
+    /// The client's total dropped below `total_floor` after the transaction
+    TotalBelowFloor { total: Decimal },
+    /// With `partial_withdrawal`, the withdrawal exceeded the available funds and only part of it was withdrawn
+    PartialWithdrawal {
+        requested: Decimal,
+        withdrawn: Decimal,
+        shortfall: Decimal,
+    },
+    /// Interest for `period` was credited with [`TransactionEngine::apply_interest`]
+    InterestApplied {
+        period: String,
+        rate: Decimal,
+        amount: Decimal,
+    },
+}
+
+/// An account left with `available + held != total` by a transaction, found with `continuous_reconciliation`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ReconciliationError {
+    pub client: u16,
+    pub tx: u32,
+    pub available: Decimal,
+    pub held: Decimal,
+    pub total: Decimal,
+}
+
+/// A transaction flagged for review
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub client: u16,
+    pub tx: u32,
+    pub flag: AuditFlag,
+}
+
+/// A transaction that was not applied, kept so operators can inspect what was refused
+#[derive(Debug, Clone, Serialize)]
+pub struct Rejection {
+    pub client: u16,
+    pub tx: u32,
+    pub reason: RejectionReason,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum TransactionType {
     Deposit,
     Withdrawal,
     Dispute,
     Resolve,
     Chargeback,
+    Settle,
+    Transfer,
+}
+
+impl TransactionType {
+    /// The type's name in the `type` column
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::Withdrawal => "withdrawal",
+            Self::Dispute => "dispute",
+            Self::Resolve => "resolve",
+            Self::Chargeback => "chargeback",
+            Self::Settle => "settle",
+            Self::Transfer => "transfer",
+        }
+    }
 }
 
 #[derive(Hash, Eq, PartialEq)]
@@ -57,241 +833,1752 @@ pub struct HistoryKey {
     tx_type: TransactionType,
 }
 
+/// Reader the CSV parser reads an input through
+#[cfg(feature = "encoding")]
+type InputReader<R> = CommentFilter<Transcoder<R>>;
+#[cfg(all(feature = "async", not(feature = "encoding")))]
+type InputReader<R> = CommentFilter<R>;
+
+/// CSV serializer for every output written by the engine
+#[cfg(feature = "async")]
+type OutputSerializer<W> = csv_async::AsyncSerializer<BufWriter<TrailingNewline<W>>>;
+
 /// Abstraction used to keep track of a client's state as rows are sequentially processed
 pub struct TransactionEngine {
-    // Each client will be mapped to a singular output row as desired
-    clients: HashMap<u16, OutputRow>,
+    // Each account will be mapped to a singular output row as desired. Without `multi_ledger` and `multi_currency`, a
+    // client has one account
+    clients: HashMap<AccountKey, OutputRow>,
+    history: HashMap<HistoryKey, Transaction>,
+    // Clients that have already been warned for their current drop below the low balance threshold
+    low_balance_warned: HashSet<AccountKey>,
+    // The account each deposit and withdrawal was made in, so disputes reach the right account. Only kept with
+    // `multi_ledger` or `multi_currency`
+    tx_accounts: HashMap<(u16, u32), AccountKey>,
+    rejections: Vec<Rejection>,
+    skipped: Vec<SkippedRow>,
+    audit_log: Vec<AuditEntry>,
+    reconciliation_errors: Vec<ReconciliationError>,
+    // Events of each client's accounts in the order they were applied. Only kept with `track_account_events`
+    account_events: HashMap<u16, Vec<AccountEvent>>,
+    // The deposits and withdrawals in the history that may be evicted, least recently referenced first. Disputed
+    // transactions are left out until their dispute concludes. Only maintained for the LRU eviction policy
+    history_lru: LinkedHashSet<HistoryKey>,
+    // Transactions whose dispute was resolved or charged back since the last compaction
+    concluded_disputes: Vec<(u16, u32)>,
+    // Number of transactions applied so far. Used to age open disputes
+    rows_processed: u64,
+    // (client, tx) of each open dispute mapped to the row at which it was opened. Only tracked when disputes expire
+    dispute_opened_at: HashMap<(u16, u32), u64>,
+    // Open disputes in the order they were opened, so expired ones can be found without scanning every dispute
+    dispute_expiry_queue: VecDeque<(u64, u16, u32)>,
+    // Number of open disputes of each client. Clients without open disputes are removed
+    open_dispute_counts: HashMap<u16, usize>,
+    // Amounts and timestamps of each client's recent deposits, oldest first. Only kept with `deposit_dedup_window`
+    recent_deposits: HashMap<u16, VecDeque<(u64, Decimal)>>,
+    // Disputes opened, resolved and charged back so far. The open ones are derived from the history when queried
+    dispute_outcomes: DisputeSummary,
+    // Number of calls to `apply_interest` so far, which determines the tx of the next one
+    interest_applications: u32,
+    // Receives a domain event per transaction while `process_with_events` runs
+    #[cfg(feature = "async")]
+    event_tx: Option<tokio::sync::mpsc::Sender<DomainEvent>>,
+    events_dropped: u64,
+    // Input passed to `feed` that does not form a complete row yet, how much of it is known to be valid UTF-8, the
+    // offset of its first byte in the input, the header row it started with, and the number of rows fed since
+    #[cfg(feature = "async")]
+    feed_buffer: Vec<u8>,
+    #[cfg(feature = "async")]
+    feed_validated: usize,
+    #[cfg(feature = "async")]
+    feed_offset: u64,
+    #[cfg(feature = "async")]
+    feed_headers: Option<Vec<u8>>,
+    #[cfg(feature = "async")]
+    feed_rows_read: u64,
+    // Set while `process_with_progress_stream` runs
+    #[cfg(feature = "async")]
+    progress: Option<Progress>,
+    // Rows of the transactions applied by the current input row while `process_ledger` runs
+    #[cfg(feature = "async")]
+    ledger_rows: Option<Vec<LedgerRow>>,
+    // Rows dropped so far for `skip_rows`, and whether the restart point of `skip_rows` and `start_at_client` was
+    // reached, after which every row is processed
+    restart_rows_skipped: u64,
+    restart_point_reached: bool,
+    // Problems found by `TransactionEngineConfig::validate` when the engine was built, returned before any row is
+    // applied or any output is written
+    config_errors: Vec<ConfigError>,
+    // Shared so that sub-engines spawned for concurrent processing use the same settings and handlers
+    config: Arc<TransactionEngineConfig>,
 }
 
 impl TransactionEngine {
-    fn new() -> Self {
+    fn new(config: Arc<TransactionEngineConfig>) -> Self {
         Self {
-            clients: HashMap::new(),
+            clients: HashMap::with_capacity(config.client_capacity),
+            history: HashMap::with_capacity(config.history_capacity),
+            low_balance_warned: HashSet::new(),
+            tx_accounts: HashMap::new(),
+            rejections: Vec::new(),
+            skipped: Vec::new(),
+            audit_log: Vec::new(),
+            reconciliation_errors: Vec::new(),
+            account_events: HashMap::new(),
+            history_lru: LinkedHashSet::new(),
+            concluded_disputes: Vec::new(),
+            rows_processed: 0,
+            dispute_opened_at: HashMap::new(),
+            dispute_expiry_queue: VecDeque::new(),
+            open_dispute_counts: HashMap::new(),
+            recent_deposits: HashMap::new(),
+            dispute_outcomes: DisputeSummary::default(),
+            interest_applications: 0,
+            #[cfg(feature = "async")]
+            event_tx: None,
+            events_dropped: 0,
+            #[cfg(feature = "async")]
+            feed_buffer: Vec::new(),
+            #[cfg(feature = "async")]
+            feed_validated: 0,
+            #[cfg(feature = "async")]
+            feed_offset: 0,
+            #[cfg(feature = "async")]
+            feed_headers: None,
+            #[cfg(feature = "async")]
+            feed_rows_read: 0,
+            #[cfg(feature = "async")]
+            progress: None,
+            #[cfg(feature = "async")]
+            ledger_rows: None,
+            restart_rows_skipped: 0,
+            restart_point_reached: false,
+            config_errors: config.validate().err().unwrap_or_default(),
+            config,
         }
     }
 
-    /// Fully processes the input file, outputting the contents to the desired output
-    pub async fn process_file<P: AsRef<Path>, W: AsyncWrite + Unpin>(
-        file: P,
-        output: W,
-    ) -> Result<(), Box<dyn Error>> {
-        let source = tokio::fs::File::open(file).await?;
-        Self::process(source, output).await
+    /// Returns a builder for configuring a new engine
+    pub fn builder() -> TransactionEngineBuilder {
+        TransactionEngineBuilder::new()
     }
 
-    /// Fully processes the input source, outputting the contents to the desired output
-    pub async fn process<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
-        input: R,
-        output: W,
-    ) -> Result<(), Box<dyn Error>> {
-        let mut this = Self::new();
-
-        // use "flexible" to allow empty input fields for disputes, resolves, and chargebacks
-        let input = csv_async::AsyncReaderBuilder::new()
-            .flexible(true)
-            .create_deserializer(input);
-        let mut rows = input.into_deserialize::<InputRow>();
-        let mut history = HashMap::new();
-
-        // Assume every row is chronologically sequential as specified
-        while let Some(result) = rows.next().await {
-            let row = result?;
-            this.process_single_transaction(row, &mut history)?;
+    /// Drops all client and history state. The maps are re-allocated to their configured capacity rather than to 0
+    pub fn clear(&mut self) {
+        // maps that are still empty already have their configured capacity
+        if !self.clients.is_empty() {
+            self.clients = HashMap::with_capacity(self.config.client_capacity);
         }
-
-        // output to desired output stream
-        let mut output = csv_async::AsyncSerializer::from_writer(output);
-        for (_, row) in this.clients {
-            output.serialize(row).await?;
+        if !self.history.is_empty() {
+            self.history = HashMap::with_capacity(self.config.history_capacity);
         }
+        self.low_balance_warned.clear();
+        self.tx_accounts.clear();
+        self.rejections.clear();
+        self.skipped.clear();
+        self.audit_log.clear();
+        self.reconciliation_errors.clear();
+        self.account_events.clear();
+        self.history_lru.clear();
+        self.concluded_disputes.clear();
+        self.rows_processed = 0;
+        self.dispute_opened_at.clear();
+        self.dispute_expiry_queue.clear();
+        self.open_dispute_counts.clear();
+        self.recent_deposits.clear();
+        self.dispute_outcomes = DisputeSummary::default();
+        self.interest_applications = 0;
+        self.events_dropped = 0;
+        #[cfg(feature = "async")]
+        {
+            self.feed_buffer.clear();
+            self.feed_validated = 0;
+            self.feed_offset = 0;
+            self.feed_headers = None;
+            self.feed_rows_read = 0;
+        }
+        self.restart_rows_skipped = 0;
+        self.restart_point_reached = false;
+    }
 
-        Ok(output.flush().await?)
+    /// Returns every transaction that was rejected, in the order they were processed
+    pub fn rejections(&self) -> &[Rejection] {
+        &self.rejections
     }
 
-    fn process_single_transaction(
-        &mut self,
-        input_row: InputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) -> Result<(), Box<dyn Error>> {
-        let tx_type = input_row.transaction_type().ok_or_else(|| {
-            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid transaction type")
-        })?;
-        self.create_client_if_non_exists(input_row.client);
-        let client_row = self.clients.get_mut(&input_row.client).unwrap();
+    /// Returns every row that was skipped before being applied, in the order they were read
+    pub fn skipped(&self) -> &[SkippedRow] {
+        &self.skipped
+    }
 
-        match tx_type {
-            TransactionType::Deposit => process_deposit(input_row, client_row, history),
+    /// Returns every transaction that was flagged for review, in the order they were processed
+    pub fn audit_log(&self) -> &[AuditEntry] {
+        &self.audit_log
+    }
 
-            TransactionType::Withdrawal => process_withdrawal(input_row, client_row, history),
+    /// Returns every invariant violation found with `continuous_reconciliation`, in the order they were processed
+    pub fn reconciliation_errors(&self) -> &[ReconciliationError] {
+        &self.reconciliation_errors
+    }
 
-            TransactionType::Dispute => process_dispute(input_row, client_row, history),
+    /// Drops the history of transactions whose dispute has been resolved or charged back, since they no longer need to
+    /// be looked up. Disputing such a transaction again is rejected with [`RejectionReason::TransactionNotFound`]
+    pub fn compact_history(&mut self) {
+        for (client, tx) in self.concluded_disputes.drain(..) {
+            let key = |tx_type| HistoryKey {
+                client,
+                tx,
+                tx_type,
+            };
 
-            TransactionType::Resolve => process_resolve(input_row, client_row, history),
+            // re-disputed since: compacted once that dispute concludes
+            if self.history.contains_key(&key(TransactionType::Dispute)) {
+                continue;
+            }
 
-            TransactionType::Chargeback => process_chargeback(input_row, client_row, history),
+            self.history.remove(&key(TransactionType::Deposit));
+            self.history.remove(&key(TransactionType::Withdrawal));
+            self.tx_accounts.remove(&(client, tx));
         }
-
-        Ok(())
     }
 
-    /// Gets the client from the internal map. If the client does not exist, will create a new entry
-    fn create_client_if_non_exists(&mut self, client: u16) {
-        if !self.clients.contains_key(&client) {
-            let new_row = OutputRow {
-                client,
-                ..Default::default()
-            };
+    /// Returns every dispute that is currently open, sorted by (client, tx)
+    pub fn open_disputes(&self) -> Vec<OpenDispute> {
+        let mut disputes = self
+            .history
+            .keys()
+            .filter(|key| key.tx_type == TransactionType::Dispute)
+            .filter_map(|key| {
+                let amount = get_disputable_amount(key.client, key.tx, &self.history)?;
+                let (client, ledger, currency) = self.account_of_tx(key.client, key.tx);
+                Some(OpenDispute {
+                    client,
+                    ledger: self.config.multi_ledger.then(|| ledger),
+                    currency: self.config.multi_currency.then(|| currency),
+                    tx: key.tx,
+                    amount,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        disputes.sort_by_key(|dispute| (dispute.client, dispute.tx));
+        disputes
+    }
 
-            assert!(self.clients.insert(client, new_row).is_none());
+    /// Returns how many disputes were opened, resolved and charged back so far and the amounts involved, along with
+    /// the disputes still open
+    pub fn dispute_summary(&self) -> DisputeSummary {
+        let open_disputes = self.open_disputes();
+        DisputeSummary {
+            open: open_disputes.len() as u64,
+            open_amount: open_disputes.iter().map(|dispute| dispute.amount).sum(),
+            ..self.dispute_outcomes.clone()
         }
     }
-}
 
-mod processors {
-    use crate::tx_engine::{HistoryKey, InputRow, OutputRow, TransactionType};
-    use std::collections::HashMap;
+    /// Aggregates the accounts, open disputes, rejected, skipped and flagged rows and the transaction summary into a
+    /// single report
+    pub fn full_report(&self) -> Report {
+        let mut accounts = self.clients.values().cloned().collect::<Vec<_>>();
+        accounts.sort_by(|a, b| {
+            (a.client, &a.ledger, &a.currency).cmp(&(b.client, &b.ledger, &b.currency))
+        });
 
-    pub fn process_deposit(
-        input_row: InputRow,
-        client_row: &mut OutputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) {
-        // we can safely unwrap below since the "amount" field is asserted to exist for "deposit" types
-        let amount = input_row.amount.clone().unwrap();
-        client_row.available += amount;
-        client_row.total += amount;
+        Report {
+            accounts,
+            open_disputes: self.open_disputes(),
+            rejections: self.rejections.clone(),
+            skipped: self.skipped.clone(),
+            audit_log: self.audit_log.clone(),
+            summary: self.summary(),
+            terminal_width: DEFAULT_TERMINAL_WIDTH,
+        }
+    }
 
-        history.insert(
-            HistoryKey {
-                client: input_row.client,
-                tx: input_row.tx,
-                tx_type: TransactionType::Deposit,
-            },
-            input_row,
-        );
+    /// Returns a report of the accounts for operators, which displays as a table sorted by total balance descending.
+    /// The same as [`TransactionEngine::full_report`]
+    pub fn report(&self) -> Report {
+        self.full_report()
     }
 
-    pub fn process_withdrawal(
-        input_row: InputRow,
-        client_row: &mut OutputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) {
-        // we can safely unwrap below since the "amount" field is asserted to exist for "withdrawal" types
-        let amount = input_row.amount.clone().unwrap();
-        if amount > client_row.available || amount > client_row.total {
-            return;
+    /// Returns the aggregate counters for everything processed so far
+    pub fn summary(&self) -> TransactionSummary {
+        TransactionSummary {
+            transactions_processed: self.rows_processed,
+            clients: self.clients.len(),
+            locked_clients: self.clients.values().filter(|row| row.locked).count(),
+            open_disputes: self.open_disputes().len(),
+            rejections: self.rejections.len(),
+            skipped: self.skipped.len(),
+            flagged: self.audit_log.len(),
+            events_dropped: self.events_dropped,
+            total_chargebacked: self
+                .clients
+                .values()
+                .map(|row| row.total_chargebacked)
+                .sum(),
+            total_chargeback_count: self.clients.values().fold(0u64, |total, row| {
+                total.saturating_add(u64::from(row.chargeback_count))
+            }),
         }
+    }
 
-        client_row.available -= amount;
-        client_row.total -= amount;
+    /// Returns the account of the client in the default ledger and currency, if it has one
+    pub fn query_client(&self, client: u16) -> Option<&OutputRow> {
+        self.clients.get(&(client, String::new(), String::new()))
+    }
 
-        history.insert(
-            HistoryKey {
-                client: input_row.client,
-                tx: input_row.tx,
-                tx_type: TransactionType::Withdrawal,
-            },
-            input_row,
-        );
+    /// Returns the IDs of every client with an account, sorted
+    pub fn client_ids(&self) -> Vec<u16> {
+        let mut clients = self
+            .clients
+            .keys()
+            .map(|(client, ..)| *client)
+            .collect::<Vec<_>>();
+        // a client has an account per ledger in multi-ledger mode
+        clients.sort_unstable();
+        clients.dedup();
+        clients
     }
 
-    pub fn process_dispute(
-        input_row: InputRow,
-        client_row: &mut OutputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) {
-        let ref expected_key_deposit = HistoryKey {
-            client: input_row.client,
-            tx: input_row.tx,
-            tx_type: TransactionType::Deposit,
-        };
+    /// Returns the IDs of the clients with at least one chargeback, sorted. Always empty unless
+    /// `track_chargeback_totals` is enabled
+    pub fn clients_with_chargebacks(&self) -> Vec<u16> {
+        let mut clients = self
+            .clients
+            .values()
+            .filter(|row| row.chargeback_count > 0)
+            .map(|row| row.client)
+            .collect::<Vec<_>>();
+        // a client has an account per ledger in multi-ledger mode
+        clients.sort_unstable();
+        clients.dedup();
+        clients
+    }
 
-        let ref expected_key_withdrawal = HistoryKey {
-            client: input_row.client,
-            tx: input_row.tx,
-            tx_type: TransactionType::Withdrawal,
-        };
+    /// Writes a CSV template with the expected headers and an example deposit and withdrawal. The template starts
+    /// with `#` comment lines, which are skipped when `skip_comment_lines` is enabled
+    #[cfg(feature = "async")]
+    pub async fn write_csv_template<W: AsyncWrite + Unpin>(
+        mut output: W,
+    ) -> Result<(), TransactionError> {
+        const TEMPLATE: &str = "\
+# type: deposit, withdrawal, dispute, resolve, chargeback or settle
+# client: u16 client ID. tx: u32 globally unique transaction ID
+# amount: up to four decimal places. Leave empty for disputes, resolves and chargebacks
+type,client,tx,amount
+deposit,1,1,10.0
+withdrawal,1,2,2.5
+";
+        output.write_all(TEMPLATE.as_bytes()).await?;
+        Ok(output.flush().await?)
+    }
 
-        // ***Note to reviewer: differential handling of disputes w.r.t deposits or withdrawals was unclear in the assignment text. I assume equivalent treatment for both types***
-        let dispute_amount = if let Some(deposit_row) = history.get(expected_key_deposit) {
-            deposit_row.amount.clone().unwrap()
-        } else {
-            if let Some(withdrawal_row) = history.get(expected_key_withdrawal) {
-                withdrawal_row.amount.clone().unwrap()
-            } else {
-                return;
-            }
-        };
+    /// Fully processes the input file, outputting the contents to the desired output. Like [`Self::process`], any state
+    /// left by a previous call is dropped first
+    #[cfg(feature = "async")]
+    pub async fn process_file<P: AsRef<Path>, W: AsyncWrite + Unpin>(
+        &mut self,
+        file: P,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        let source = Self::open_file(&self.config, file).await?;
+        self.process(source, output).await
+    }
 
-        client_row.available -= dispute_amount;
-        client_row.held += dispute_amount;
+    /// Creates (or truncates) an output file at `path`. A missing parent directory is created when
+    /// `create_output_dirs` is set and is otherwise reported as [`TransactionError::MissingOutputDirectory`]
+    #[cfg(feature = "async")]
+    pub async fn create_output_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<tokio::fs::File, TransactionError> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            if !tokio::fs::try_exists(dir).await? {
+                if !self.config.create_output_dirs {
+                    return Err(TransactionError::MissingOutputDirectory(dir.to_path_buf()));
+                }
 
-        history.insert(
-            HistoryKey {
-                client: input_row.client,
-                tx: input_row.tx,
-                tx_type: TransactionType::Dispute,
-            },
-            input_row,
-        );
+                tokio::fs::create_dir_all(dir).await?;
+            }
+        }
+
+        Ok(tokio::fs::File::create(path).await?)
     }
 
-    pub fn process_resolve(
-        input_row: InputRow,
-        client_row: &mut OutputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) {
-        if let Some(dispute_amount) = get_dispute_amount(&input_row, history) {
-            client_row.held -= dispute_amount;
-            client_row.available += dispute_amount;
+    /// Fully processes the input source, outputting the contents to the desired output.
+    ///
+    /// If reading or applying the input fails, nothing is written unless `flush_partial_output_on_error` is set, in
+    /// which case the balances accumulated up to the failing row are written before the error is returned. Errors
+    /// while writing the output always flush the rows that were already serialized. When processing is stopped through
+    /// the configured `shutdown` signal, the balances accumulated so far are always written.
+    ///
+    /// Every call starts from an empty state as if [`Self::clear`] was called, so the output only reflects this input.
+    /// Use [`Self::feed`] to apply several inputs on top of each other
+    #[cfg(feature = "async")]
+    pub async fn process<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        if self.config.strict_mode {
+            return self.process_with_schema_validation(input, output).await;
         }
+
+        self.process_rows_then_output(input, output, false).await
     }
 
-    pub fn process_chargeback(
-        input_row: InputRow,
-        client_row: &mut OutputRow,
-        history: &mut HashMap<HistoryKey, InputRow>,
-    ) {
-        if let Some(dispute_amount) = get_dispute_amount(&input_row, history) {
-            client_row.held -= dispute_amount;
-            client_row.total -= dispute_amount;
-            client_row.locked = true;
-        }
+    /// Applies every row of an in-memory input without writing the balances, e.g. to fuzz the engine or to check its
+    /// state with [`Self::verify_invariants`] afterwards. Starts from an empty state like [`Self::process`]
+    #[cfg(feature = "async")]
+    pub async fn process_bytes(
+        &mut self,
+        input: &[u8],
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.clear();
+        self.process_rows(input, self.config.strict_mode).await?;
+        Ok(self.summary())
     }
 
-    fn get_dispute_amount(
-        input_row: &InputRow,
-        history: &HashMap<HistoryKey, InputRow>,
-    ) -> Option<f32> {
-        let ref expected_key_deposit = HistoryKey {
-            client: input_row.client,
-            tx: input_row.tx,
-            tx_type: TransactionType::Deposit,
-        };
+    /// Like [`Self::process_bytes`], for input held in a string
+    #[cfg(feature = "async")]
+    pub async fn process_str(
+        &mut self,
+        input: &str,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.process_bytes(input.as_bytes()).await
+    }
 
-        let ref expected_key_withdrawal = HistoryKey {
-            client: input_row.client,
-            tx: input_row.tx,
-            tx_type: TransactionType::Withdrawal,
-        };
+    /// Returns the balances output for the current state, as [`Self::process`] would write it
+    #[cfg(feature = "async")]
+    pub async fn to_csv_string(&self) -> Result<String, TransactionError> {
+        let mut output = Vec::new();
+        self.write_output(&mut output).await?;
+        // the serializer only writes the UTF-8 of the balances and the configured separators
+        Ok(String::from_utf8(output).expect("the balances output is valid UTF-8"))
+    }
 
-        let ref expected_key_dispute = HistoryKey {
-            client: input_row.client,
-            tx: input_row.tx,
-            tx_type: TransactionType::Dispute,
-        };
+    #[cfg(feature = "async")]
+    async fn process_rows_then_output<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        output: W,
+        validate_schema: bool,
+    ) -> Result<(), TransactionError> {
+        if let Err(err) = self.process_rows(input, validate_schema).await {
+            #[cfg(feature = "signal-handling")]
+            if matches!(err, TransactionError::Interrupted) {
+                self.write_output(output).await?;
+                return Err(err);
+            }
 
-        // at most 2 O(1) lookups in the hashmap are cheap
-        if history.contains_key(expected_key_dispute) {
-            if let Some(deposit_row) = history.get(expected_key_deposit) {
-                return Some(deposit_row.amount.clone().unwrap());
-            } else {
-                if let Some(withdrawal_row) = history.get(expected_key_withdrawal) {
-                    return Some(withdrawal_row.amount.clone().unwrap());
-                }
+            if self.config.flush_partial_output_on_error {
+                // the processing error takes precedence over any error while writing the partial output
+                let _ = self.write_output(output).await;
             }
+
+            return Err(err);
+        }
+
+        self.write_output(output).await
+    }
+
+    /// Processes files that are each already grouped by client and chronologically ordered for that client. Since
+    /// clients are independent, each file is processed in parallel on its own engine and the resulting accounts are
+    /// merged afterwards. A client appearing in more than one file is an error. Like [`Self::process`], any state left
+    /// by a previous call is dropped first
+    #[cfg(feature = "async")]
+    pub async fn process_per_client_files<P: AsRef<Path>, W: AsyncWrite + Unpin>(
+        &mut self,
+        files: impl IntoIterator<Item = P>,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        let mut tasks = JoinSet::new();
+        for (index, file) in files.into_iter().enumerate() {
+            let file = file.as_ref().to_path_buf();
+            let mut engine = Self::new(self.config.clone());
+            tasks.spawn(async move {
+                let source = Self::open_file(&engine.config, file).await?;
+                let validate_schema = engine.config.strict_mode;
+                engine.process_rows(source, validate_schema).await?;
+                Ok::<_, TransactionError>((index, engine))
+            });
         }
 
-        None
+        // the first failure stops the files still being processed
+        let mut engines = Vec::with_capacity(tasks.len());
+        while let Some(task) = tasks.join_next().await {
+            match task
+                .map_err(TransactionError::from)
+                .and_then(|result| result)
+            {
+                Ok(engine) => engines.push(engine),
+                Err(err) => {
+                    tasks.abort_all();
+                    return Err(err);
+                }
+            }
+        }
+
+        // merged in the order of the files, so that the accounts' insertion order does not depend on timing
+        engines.sort_unstable_by_key(|(index, _)| *index);
+        for (_, engine) in engines {
+            self.merge(engine)?;
+        }
+
+        self.write_output(output).await
+    }
+
+    /// Opens a local file, wrapping it in a retrying reader and decompressor if configured. With the `s3` feature,
+    /// `s3://` URIs (and `gs://`/`az://` with `cloud-storage`) are streamed from object storage instead. With the
+    /// `unix-socket` feature, a path to a Unix domain socket is connected to and read until the peer closes it
+    #[cfg(feature = "async")]
+    async fn open_file<P: AsRef<Path>>(
+        config: &TransactionEngineConfig,
+        file: P,
+    ) -> Result<Box<dyn AsyncRead + Unpin + Send + Sync>, TransactionError> {
+        #[cfg(feature = "s3")]
+        if let Some(uri) = file
+            .as_ref()
+            .to_str()
+            .filter(|uri| cloud::is_object_uri(uri))
+        {
+            let source = cloud::open_object(uri).await?;
+            #[cfg(feature = "compression")]
+            if config.detect_compression {
+                return Ok(decompress_detected(source).await?.1);
+            }
+
+            return Ok(source);
+        }
+
+        // opening a socket as a file fails, so this must be checked first
+        #[cfg(all(unix, feature = "unix-socket"))]
+        if socket::is_socket(file.as_ref()).await {
+            let source = socket::connect(file.as_ref(), config.connect_timeout).await?;
+            #[cfg(feature = "compression")]
+            if config.detect_compression {
+                return Ok(decompress_detected(source).await?.1);
+            }
+
+            return Ok(source);
+        }
+
+        #[cfg(feature = "mmap")]
+        if config.use_mmap {
+            let source = Box::new(mmap::MmapReader::open(file.as_ref())?);
+            #[cfg(feature = "compression")]
+            if config.detect_compression {
+                return Ok(decompress_detected(source).await?.1);
+            }
+
+            return Ok(source);
+        }
+
+        let source = tokio::fs::File::open(file).await?;
+
+        #[cfg(feature = "io-retry")]
+        let source: Box<dyn AsyncRead + Unpin + Send + Sync> = match config.io_retry.clone() {
+            Some(retry) => Box::new(RetryingReader::new(source, retry)),
+            None => Box::new(source),
+        };
+
+        #[cfg(feature = "compression")]
+        if config.detect_compression {
+            return Ok(decompress_detected(source).await?.1);
+        }
+
+        #[cfg(not(any(
+            feature = "io-retry",
+            feature = "compression",
+            feature = "mmap",
+            all(unix, feature = "unix-socket")
+        )))]
+        let _ = config;
+
+        Ok(Box::new(source))
+    }
+
+    /// Applies every row of the input source without writing any output
+    #[cfg(feature = "async")]
+    async fn process_rows<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        input: R,
+        validate_schema: bool,
+    ) -> Result<(), TransactionError> {
+        if self.config.parse_workers > 1 {
+            return self
+                .process_rows_parallel_parse(input, validate_schema)
+                .await;
+        }
+
+        let mut rows = Self::deserialize_rows(&self.config, input, validate_schema).await?;
+        #[cfg(feature = "signal-handling")]
+        let mut shutdown = self.config.shutdown.clone();
+
+        // Assume every row is chronologically sequential as specified
+        for rows_read in 0.. {
+            if Self::row_limit_reached(&self.config, rows_read) {
+                return Ok(());
+            }
+
+            #[cfg(feature = "signal-handling")]
+            let next = match shutdown.as_mut() {
+                Some(shutdown) => tokio::select! {
+                    // checked first, so that no further row is applied once shutdown was requested
+                    biased;
+                    Ok(_) = shutdown.wait_for(|requested| *requested) => {
+                        return Err(TransactionError::Interrupted)
+                    }
+                    next = Self::next_row(&self.config, &mut rows) => next,
+                },
+                None => Self::next_row(&self.config, &mut rows).await,
+            };
+            #[cfg(not(feature = "signal-handling"))]
+            let next = Self::next_row(&self.config, &mut rows).await;
+
+            match next {
+                Some(result) => self.process_input_row(result?)?,
+                None => return Ok(()),
+            };
+        }
+
+        Ok(())
+    }
+
+    /// Writes the current state of every client to the output
+    #[cfg(feature = "async")]
+    async fn write_output<W: AsyncWrite + Unpin>(
+        &self,
+        mut output: W,
+    ) -> Result<(), TransactionError> {
+        if !self.config.validate_output {
+            return self.write_balances(output).await;
+        }
+
+        // read back before anything reaches the output, so that invalid output is never written
+        let mut written = Vec::new();
+        self.write_balances(&mut written).await?;
+        self.validate_output(&String::from_utf8_lossy(&written))
+            .await
+            .map_err(|errors| TransactionError::OutputValidationFailed { errors })?;
+        output.write_all(&written).await?;
+        Ok(output.flush().await?)
+    }
+
+    /// Orders the accounts of the balances output by `sort_output_by`. Ties are broken by account
+    #[cfg(any(feature = "async", feature = "sync"))]
+    fn sort_output(&self, rows: &mut [(&AccountKey, &OutputRow)]) {
+        match self.config.sort_output_by {
+            SortField::ClientId => rows.sort_unstable_by_key(|(account, _)| *account),
+            SortField::TotalBalance => rows.sort_unstable_by(|(a, a_row), (b, b_row)| {
+                b_row.total.cmp(&a_row.total).then_with(|| a.cmp(b))
+            }),
+            SortField::AvailableBalance => rows.sort_unstable_by(|(a, a_row), (b, b_row)| {
+                b_row.available.cmp(&a_row.available).then_with(|| a.cmp(b))
+            }),
+            SortField::InsertionOrder => rows.sort_unstable_by_key(|(_, row)| row.created_seq),
+        }
+    }
+
+    /// Writes every client's balances to the output
+    #[cfg(feature = "async")]
+    async fn write_balances<W: AsyncWrite + Unpin>(
+        &self,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.check_config()?;
+        let mut rows = self
+            .clients
+            .iter()
+            .filter(|(_, row)| self.is_output(row))
+            .collect::<Vec<_>>();
+        self.sort_output(&mut rows);
+
+        let mut output = Self::serializer(&self.config, output);
+        let mut result = Ok(());
+        for (_, row) in rows {
+            let row = row.formatted(&self.config);
+            if let Err(err) = output.serialize(row).await {
+                result = Err(err);
+                break;
+            }
+        }
+
+        Self::finalize(&self.config, output, result).await
+    }
+
+    /// Whether the account is written to the output. With `disputed_clients_only`, only accounts with dispute activity
+    /// are
+    #[cfg(any(feature = "async", feature = "sync", feature = "arrow"))]
+    fn is_output(&self, row: &OutputRow) -> bool {
+        !self.config.disputed_clients_only || row.dispute_activity_count > 0
+    }
+
+    /// Wraps the output in a buffer of the configured size, writing the configured CSV dialect. The buffer is flushed
+    /// by [`Self::finalize`]
+    #[cfg(feature = "async")]
+    fn serializer<W: AsyncWrite + Unpin>(
+        config: &TransactionEngineConfig,
+        output: W,
+    ) -> OutputSerializer<W> {
+        let terminator = match config.output_line_terminator {
+            LineTerminator::Lf => csv_async::Terminator::Any(b'\n'),
+            LineTerminator::CrLf => csv_async::Terminator::CRLF,
+        };
+        let quote_style = match (config.output_always_quote, config.quote_numeric_output) {
+            (true, true) => csv_async::QuoteStyle::Always,
+            (true, false) => csv_async::QuoteStyle::NonNumeric,
+            (false, _) => csv_async::QuoteStyle::Necessary,
+        };
+
+        csv_async::AsyncWriterBuilder::new()
+            .terminator(terminator)
+            .quote_style(quote_style)
+            .create_serializer(BufWriter::with_capacity(
+                config.output_buffer_size,
+                TrailingNewline::new(output, config.output_line_terminator.as_bytes()),
+            ))
+    }
+
+    /// Flushes the serializer regardless of whether serialization succeeded, so rows already written are not lost in
+    /// its buffer. An earlier serialization error takes precedence over a flush error. On success, the output is
+    /// ended as configured by `output_trailing_newline` and `output_trailing_blank_line`
+    #[cfg(feature = "async")]
+    async fn finalize<W: AsyncWrite + Unpin>(
+        config: &TransactionEngineConfig,
+        mut output: OutputSerializer<W>,
+        result: Result<(), csv_async::Error>,
+    ) -> Result<(), TransactionError> {
+        let flushed = output.flush().await;
+        result?;
+        flushed?;
+
+        let trailer = output
+            .into_inner()
+            .await
+            .map_err(|err| err.into_error())?
+            .into_inner();
+        Ok(trailer
+            .finish(
+                config.output_trailing_newline,
+                config.output_trailing_blank_line,
+            )
+            .await?)
+    }
+
+    /// Moves the clients and history of a disjoint engine into this one
+    fn merge(&mut self, other: TransactionEngine) -> Result<(), TransactionError> {
+        // with `multi_ledger`, the engines must not share a client even in different ledgers
+        let clients = self
+            .clients
+            .keys()
+            .map(|(client, ..)| *client)
+            .collect::<HashSet<_>>();
+        if let Some((client, ..)) = other
+            .clients
+            .keys()
+            .find(|(client, ..)| clients.contains(client))
+        {
+            return Err(TransactionError::DuplicateClient(*client));
+        }
+
+        // the other engine's accounts count as created after this engine's
+        let offset = self.clients.len() as u64;
+        self.clients
+            .extend(other.clients.into_iter().map(|(account, mut row)| {
+                row.created_seq += offset;
+                (account, row)
+            }));
+        self.history.extend(other.history);
+        self.low_balance_warned.extend(other.low_balance_warned);
+        self.tx_accounts.extend(other.tx_accounts);
+        self.rejections.extend(other.rejections);
+        self.skipped.extend(other.skipped);
+        self.audit_log.extend(other.audit_log);
+        self.reconciliation_errors
+            .extend(other.reconciliation_errors);
+        self.account_events.extend(other.account_events);
+        self.history_lru.extend(other.history_lru);
+        self.concluded_disputes.extend(other.concluded_disputes);
+        self.open_dispute_counts.extend(other.open_dispute_counts);
+        self.recent_deposits.extend(other.recent_deposits);
+        self.merge_dispute_ages(
+            other.rows_processed,
+            other.dispute_opened_at,
+            other.dispute_expiry_queue,
+        );
+        self.rows_processed += other.rows_processed;
+
+        let outcomes = other.dispute_outcomes;
+        self.dispute_outcomes.opened += outcomes.opened;
+        self.dispute_outcomes.opened_amount += outcomes.opened_amount;
+        self.dispute_outcomes.resolved += outcomes.resolved;
+        self.dispute_outcomes.resolved_amount += outcomes.resolved_amount;
+        self.dispute_outcomes.charged_back += outcomes.charged_back;
+        self.dispute_outcomes.charged_back_amount += outcomes.charged_back_amount;
+
+        self.interest_applications = self.interest_applications.max(other.interest_applications);
+        self.events_dropped += other.events_dropped;
+        Ok(())
+    }
+
+    /// Adds the open disputes of another engine that processed `other_rows` rows. Since both engines' rows count
+    /// towards the merged `rows_processed`, the rows each dispute was opened at are shifted by the other engine's rows,
+    /// so that every dispute keeps its age
+    fn merge_dispute_ages(
+        &mut self,
+        other_rows: u64,
+        other_opened_at: HashMap<(u16, u32), u64>,
+        other_queue: VecDeque<(u64, u16, u32)>,
+    ) {
+        let own_rows = self.rows_processed;
+        for opened_at in self.dispute_opened_at.values_mut() {
+            *opened_at += other_rows;
+        }
+        self.dispute_opened_at.extend(
+            other_opened_at
+                .into_iter()
+                .map(|(dispute, opened_at)| (dispute, opened_at + own_rows)),
+        );
+
+        let mut queue = std::mem::take(&mut self.dispute_expiry_queue)
+            .into_iter()
+            .map(|(opened_at, client, tx)| (opened_at + other_rows, client, tx))
+            .chain(
+                other_queue
+                    .into_iter()
+                    .map(|(opened_at, client, tx)| (opened_at + own_rows, client, tx)),
+            )
+            .collect::<Vec<_>>();
+        // the oldest dispute must stay at the front
+        queue.sort_unstable();
+        self.dispute_expiry_queue = queue.into();
+    }
+
+    /// Processes the input source, writing the updated state of a client to the output after every transaction.
+    /// Reading is throttled by the output: at most `max_in_flight_rows` updates may be buffered before the
+    /// input stops being polled, keeping memory bounded when the consumer is slower than the producer.
+    /// Updates emitted before an error are always flushed. Like [`Self::process`], any state left by a previous call is
+    /// dropped first
+    #[cfg(feature = "async")]
+    pub async fn process_streaming<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        self.check_config()?;
+        let (updates_tx, mut updates_rx) =
+            tokio::sync::mpsc::channel::<OutputRow>(self.config.max_in_flight_rows.max(1));
+
+        let config = self.config.clone();
+        let reader = self.send_updates(input, updates_tx);
+
+        let writer = async move {
+            let mut output = Self::serializer(&config, output);
+            let mut result = Ok(());
+            while let Some(row) = updates_rx.recv().await {
+                let row = row.formatted(&config);
+                if let Err(err) = output.serialize(row).await {
+                    result = Err(err);
+                    break;
+                }
+            }
+
+            Self::finalize(&config, output, result).await
+        };
+
+        // not try_join: if the reader fails, the writer must still drain and flush the updates already emitted
+        let (read, written) = tokio::join!(reader, writer);
+        read.and(written)
+    }
+
+    /// Applies every input row, sending the updated state of the row's client to `updates` after each transaction.
+    /// Stops reading once the receiver is dropped
+    #[cfg(feature = "async")]
+    pub(crate) async fn send_updates<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        input: R,
+        updates: tokio::sync::mpsc::Sender<OutputRow>,
+    ) -> Result<(), TransactionError> {
+        let mut rows = Self::deserialize_rows(&self.config, input, self.config.strict_mode).await?;
+        let mut rows_read = 0;
+        while !Self::row_limit_reached(&self.config, rows_read) {
+            let result = match Self::next_row(&self.config, &mut rows).await {
+                Some(result) => result,
+                None => break,
+            };
+            rows_read += 1;
+            let row = result?;
+            let account = match self.process_input_row(row)? {
+                Some(account) => account,
+                // skipped rows change nothing
+                None => continue,
+            };
+
+            if !self.is_output(&self.clients[&account]) {
+                continue;
+            }
+
+            // waits here while the writer is behind
+            if updates.send(self.clients[&account].clone()).await.is_err() {
+                // the writer exited early. Its error is returned by the caller
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether `max_rows` input rows have been read
+    #[cfg(any(feature = "async", feature = "sync"))]
+    fn row_limit_reached(config: &TransactionEngineConfig, rows_read: u64) -> bool {
+        config
+            .max_rows
+            .is_some_and(|max_rows| rows_read >= max_rows)
+    }
+
+    /// Returns the next input row. With `ignore_partial_final_row`, a row that fails to parse is treated as the end of
+    /// the input if no rows follow it
+    #[cfg(feature = "async")]
+    async fn next_row<S: Stream<Item = Result<RawInputRow, csv_async::Error>> + Unpin>(
+        config: &TransactionEngineConfig,
+        rows: &mut S,
+    ) -> Option<Result<InputRow, TransactionError>> {
+        let result = rows.next().await?;
+        match result
+            .map_err(TransactionError::from)
+            .and_then(InputRow::try_from)
+        {
+            // only a row followed by more rows is a genuine error
+            Err(err) if config.ignore_partial_final_row => rows.next().await.map(|_| Err(err)),
+            result => Some(result),
+        }
+    }
+
+    /// Reads and validates the header row, returning a stream of the remaining rows
+    #[cfg(feature = "async")]
+    async fn deserialize_rows<'r, R: AsyncRead + Unpin + Send + Sync + 'r>(
+        config: &TransactionEngineConfig,
+        input: R,
+        validate_schema: bool,
+    ) -> Result<
+        csv_async::DeserializeRecordsIntoStream<'r, InputReader<R>, RawInputRow>,
+        TransactionError,
+    > {
+        Ok(Self::open_reader(config, input, validate_schema)
+            .await?
+            .into_deserialize::<RawInputRow>())
+    }
+
+    /// Creates the CSV reader for an input and validates its header row, against the full schema if `validate_schema` is
+    /// set
+    #[cfg(feature = "async")]
+    async fn open_reader<R: AsyncRead + Unpin + Send + Sync>(
+        config: &TransactionEngineConfig,
+        input: R,
+        validate_schema: bool,
+    ) -> Result<csv_async::AsyncDeserializer<InputReader<R>>, TransactionError> {
+        // whitespace around numeric fields would otherwise fail to parse before the normalizer sees the row
+        let trim = if config.normalize_input {
+            csv_async::Trim::All
+        } else {
+            csv_async::Trim::None
+        };
+
+        // use "flexible" to allow empty input fields for disputes, resolves, and chargebacks
+        let mut input = csv_async::AsyncReaderBuilder::new()
+            .flexible(true)
+            .trim(trim)
+            .create_deserializer(CommentFilter::new(
+                #[cfg(feature = "encoding")]
+                Transcoder::new(input, config.input_encoding),
+                #[cfg(not(feature = "encoding"))]
+                input,
+                config.skip_comment_lines,
+            ));
+
+        if validate_schema {
+            let headers = validate_headers(input.headers().await?)?;
+            input.set_headers(headers.iter().collect());
+            return Ok(input);
+        }
+
+        // serde would silently pick one of the duplicated columns
+        let mut seen = HashSet::new();
+        if let Some(name) = input
+            .headers()
+            .await?
+            .iter()
+            .find(|name| !seen.insert(*name))
+        {
+            return Err(TransactionError::DuplicateHeader {
+                name: name.to_string(),
+            });
+        }
+
+        Ok(input)
+    }
+
+    /// Normalizes and validates a parsed row before applying it. Returns the account the row was applied to, or
+    /// `None` if it was skipped or its client is not allowed to transact
+    fn process_input_row(
+        &mut self,
+        input_row: InputRow,
+    ) -> Result<Option<AccountKey>, TransactionError> {
+        self.check_config()?;
+        let result = if self.before_restart_point(&input_row) {
+            Ok(None)
+        } else {
+            self.apply_input_row(input_row)
+        };
+        #[cfg(feature = "async")]
+        self.tick_progress();
+        result
+    }
+
+    /// Fails with [`TransactionError::InvalidConfig`] if the configuration did not pass
+    /// [`TransactionEngineConfig::validate`], which engines created with
+    /// [`TransactionEngineBuilder::build`] are not checked against until they process or output anything
+    fn check_config(&self) -> Result<(), TransactionError> {
+        if self.config_errors.is_empty() {
+            Ok(())
+        } else {
+            Err(TransactionError::InvalidConfig {
+                errors: self.config_errors.clone(),
+            })
+        }
+    }
+
+    /// Whether the row comes before the point set by `skip_rows` and `start_at_client`. Such rows are dropped without
+    /// being recorded as skipped
+    fn before_restart_point(&mut self, input_row: &InputRow) -> bool {
+        if self.restart_point_reached {
+            return false;
+        }
+
+        if self.restart_rows_skipped < self.config.skip_rows {
+            self.restart_rows_skipped += 1;
+            return true;
+        }
+
+        if self
+            .config
+            .start_at_client
+            .is_some_and(|client| input_row.client < client)
+        {
+            return true;
+        }
+
+        self.restart_point_reached = true;
+        false
+    }
+
+    fn apply_input_row(
+        &mut self,
+        input_row: InputRow,
+    ) -> Result<Option<AccountKey>, TransactionError> {
+        let normalizer = InputNormalizer::new(&self.config.type_aliases);
+        let input_row = if self.config.normalize_input {
+            normalizer.normalize(input_row)
+        } else {
+            normalizer.resolve_alias(input_row)
+        };
+
+        if let (Some(allowed), Some(category)) = (
+            self.config.allowed_categories.as_ref(),
+            input_row.category.as_ref(),
+        ) {
+            if !allowed.contains(category) {
+                self.skipped.push(SkippedRow {
+                    client: input_row.client,
+                    tx: input_row.tx,
+                    reason: SkipReason::CategoryNotAllowed(category.clone()),
+                });
+                return Ok(None);
+            }
+        }
+
+        if let Some(tx_type) = input_row
+            .transaction_type()
+            .filter(|tx_type| self.config.disabled_types.contains(tx_type))
+        {
+            self.skipped.push(SkippedRow {
+                client: input_row.client,
+                tx: input_row.tx,
+                reason: SkipReason::TypeDisabled(tx_type),
+            });
+            return Ok(None);
+        }
+
+        if let Some(window) = self.config.time_window.as_ref() {
+            if !input_row.timestamp.is_some_and(|ts| window.contains(&ts)) {
+                self.skipped.push(SkippedRow {
+                    client: input_row.client,
+                    tx: input_row.tx,
+                    reason: SkipReason::OutsideTimeWindow(input_row.timestamp),
+                });
+                return Ok(None);
+            }
+        }
+
+        if let Some(reason) = self.shape_violation(&input_row) {
+            self.count_row()?;
+            self.reject(input_row.client, input_row.tx, reason);
+            return Ok(None);
+        }
+
+        let input_row = self.transform_amount(input_row)?;
+        let account = self.account_of(&input_row);
+        self.process_single_transaction(Transaction::try_from(&input_row)?, &account, &input_row)?;
+        // rejected clients never get an account
+        Ok(self.clients.contains_key(&account).then_some(account))
+    }
+
+    /// With `validate_row_shape`, returns why a row's fields do not fit its transaction type
+    fn shape_violation(&self, input_row: &InputRow) -> Option<RejectionReason> {
+        if !self.config.validate_row_shape {
+            return None;
+        }
+
+        let has_amount = input_row.amount.is_some();
+        match input_row.transaction_type()? {
+            TransactionType::Deposit | TransactionType::Withdrawal | TransactionType::Transfer
+                if !has_amount =>
+            {
+                Some(RejectionReason::MissingAmount)
+            }
+            // the amount is checked against the disputed transaction's instead
+            TransactionType::Dispute if has_amount && !self.config.match_dispute_amounts => {
+                Some(RejectionReason::UnexpectedAmount)
+            }
+            TransactionType::Resolve | TransactionType::Chargeback if has_amount => {
+                Some(RejectionReason::UnexpectedAmount)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the account a row applies to. Disputes, resolves, chargebacks and settles apply to the account of the
+    /// transaction they reference, regardless of their own ledger and currency columns
+    fn account_of(&mut self, input_row: &InputRow) -> AccountKey {
+        let own_account = || {
+            (
+                input_row.client,
+                self.ledger_of(input_row.ledger.as_deref()),
+                self.currency_of(input_row.currency.as_deref()),
+            )
+        };
+
+        if !self.config.multi_ledger && !self.config.multi_currency {
+            return own_account();
+        }
+
+        let key = (input_row.client, input_row.tx);
+        match input_row.transaction_type() {
+            Some(TransactionType::Deposit) | Some(TransactionType::Withdrawal) => {
+                let account = own_account();
+                self.tx_accounts.insert(key, account.clone());
+                account
+            }
+
+            // never disputed, so there is no need to remember the account
+            Some(TransactionType::Transfer) => own_account(),
+
+            _ => self
+                .tx_accounts
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(own_account),
+        }
+    }
+
+    /// The ledger part of an account key, which is empty unless `multi_ledger` is enabled
+    fn ledger_of(&self, ledger: Option<&str>) -> String {
+        match self.config.multi_ledger {
+            true => ledger.unwrap_or_default().to_string(),
+            false => String::new(),
+        }
+    }
+
+    /// The currency part of an account key, which is empty unless `multi_currency` is enabled
+    fn currency_of(&self, currency: Option<&str>) -> String {
+        match self.config.multi_currency {
+            true => currency.unwrap_or_default().to_string(),
+            false => String::new(),
+        }
+    }
+
+    /// The account credited by a transfer taken from `account`. Transfers never convert between currencies
+    fn transfer_target(
+        &self,
+        account: &AccountKey,
+        target_client: u16,
+        target_ledger: Option<&str>,
+    ) -> AccountKey {
+        (
+            target_client,
+            self.ledger_of(target_ledger),
+            account.2.clone(),
+        )
+    }
+
+    /// The account holding the deposit or withdrawal with the given tx
+    fn account_of_tx(&self, client: u16, tx: u32) -> AccountKey {
+        self.tx_accounts.get(&(client, tx)).cloned().unwrap_or((
+            client,
+            String::new(),
+            String::new(),
+        ))
+    }
+
+    /// Aggregates the score of every configured fraud rule for a deposit about to be applied to `client_row`. Rules
+    /// may keep state such as [`VelocityRule`]'s window, so deposits rejected for any other reason are never scored
+    fn score_fraud(
+        config: &TransactionEngineConfig,
+        input_row: &InputRow,
+        client_row: &OutputRow,
+    ) -> FraudScore {
+        config
+            .fraud_rules
+            .iter()
+            .fold(0, |total: FraudScore, rule| {
+                total.saturating_add(rule.score(input_row, client_row))
+            })
+            .min(MAX_FRAUD_SCORE)
+    }
+
+    fn transform_amount(&self, mut input_row: InputRow) -> Result<InputRow, TransactionError> {
+        if let (Some(transform), Some(amount)) =
+            (self.config.amount_transform.as_ref(), input_row.amount)
+        {
+            input_row.amount = Some(transform(amount).ok_or(TransactionError::InvalidAmount {
+                tx: input_row.tx,
+                amount,
+            })?);
+        }
+
+        input_row.amount = input_row.amount.map(|amount| {
+            amount.round_dp_with_strategy(
+                AMOUNT_DECIMAL_PLACES,
+                self.config.amount_rounding.strategy(),
+            )
+        });
+
+        Ok(input_row)
+    }
+
+    fn process_single_transaction(
+        &mut self,
+        transaction: Transaction,
+        account: &AccountKey,
+        input_row: &InputRow,
+    ) -> Result<(), TransactionError> {
+        let timestamp = input_row.timestamp;
+        let tx_type = transaction.transaction_type();
+        let amount = transaction.amount();
+        let (client, tx) = (transaction.client(), transaction.tx());
+        if !self.config.client_filter.allows(client) {
+            self.count_row()?;
+            self.reject(client, tx, RejectionReason::ClientNotAllowed);
+            return Ok(());
+        }
+
+        let vetoed = match self.config.veto.as_ref().map(|veto| veto(&transaction)) {
+            Some(Decision::Abort) => return Err(TransactionError::Aborted { tx }),
+            Some(Decision::Reject(reason)) => Some(reason),
+            Some(Decision::Accept) | None => None,
+        };
+
+        // kept to undo an applied deposit or withdrawal that does not fit in the history
+        let recorded = matches!(
+            tx_type,
+            TransactionType::Deposit | TransactionType::Withdrawal
+        );
+        let previous_row = (recorded && self.config.max_history_entries.is_some())
+            .then(|| self.clients.get(account).cloned());
+        // restored rather than decremented on undo, since a saturated counter was not incremented
+        let previous_rows_processed = self.rows_processed;
+
+        self.count_row()?;
+        let duplicate = match transaction {
+            Transaction::Deposit { amount, .. } => {
+                self.is_duplicate_deposit(client, amount, timestamp)
+            }
+            _ => false,
+        };
+
+        let target = match &transaction {
+            Transaction::Transfer {
+                target_client,
+                target_ledger,
+                ..
+            } => Some(self.transfer_target(account, *target_client, target_ledger.as_deref())),
+            _ => None,
+        };
+        let invalid_target = target.as_ref().and_then(|target| {
+            if target == account {
+                Some(RejectionReason::TransferToSelf)
+            } else if !self.config.client_filter.allows(target.0) {
+                Some(RejectionReason::ClientNotAllowed)
+            } else {
+                None
+            }
+        });
+
+        let too_many_disputes = tx_type == TransactionType::Dispute
+            && self.config.max_open_disputes_per_client.is_some_and(|max| {
+                self.open_dispute_counts.get(&client).copied().unwrap_or(0) >= max
+            });
+
+        self.create_client_if_non_exists(account);
+        let client_row = self.clients.get_mut(account).unwrap();
+        increment(
+            &mut client_row.transaction_count,
+            "transaction_count",
+            self.config.counter_overflow,
+        )?;
+        let history = &mut self.history;
+        let config = &*self.config;
+        // only set once every other check passed
+        let mut fraud_score = None;
+
+        let result = if let Some(reason) = vetoed {
+            Err(RejectionReason::Vetoed(reason))
+        } else if duplicate {
+            Err(RejectionReason::DuplicateDeposit)
+        } else if too_many_disputes {
+            Err(RejectionReason::TooManyOpenDisputes)
+        } else if let Some(reason) = invalid_target {
+            Err(reason)
+        } else {
+            match transaction {
+                Transaction::Deposit { amount, .. } => process_deposit(
+                    transaction,
+                    amount,
+                    |client_row| {
+                        *fraud_score.insert(Self::score_fraud(config, input_row, client_row))
+                    },
+                    client_row,
+                    history,
+                    config,
+                ),
+
+                Transaction::Withdrawal { amount, .. } => {
+                    process_withdrawal(transaction, amount, client_row, history, config)
+                }
+
+                Transaction::Dispute { .. } => {
+                    process_dispute(transaction, client_row, history, config)
+                }
+
+                Transaction::Resolve { .. } => process_resolve(transaction, client_row, history),
+
+                Transaction::Chargeback { .. } => {
+                    process_chargeback(transaction, client_row, history, config)?
+                }
+
+                Transaction::Settle { .. } => process_settle(transaction, client_row, history),
+
+                Transaction::Transfer { amount, .. } => {
+                    process_transfer(amount, client_row, config)
+                }
+            }
+        };
+
+        // only transactions that were applied take up room in the history, so rejected rows never evict anything
+        if let (Ok(Some(_)), Some(previous_row)) = (result, previous_row) {
+            if let Err(err) = self.fit_history(1) {
+                self.undo_recorded(account, tx_type, tx, previous_row, previous_rows_processed);
+                return Err(err);
+            }
+        }
+
+        if let Some(score) = fraud_score.filter(|score| *score > self.config.fraud_threshold) {
+            self.audit_log.push(AuditEntry {
+                client,
+                tx,
+                flag: AuditFlag::FraudSuspected { score },
+            });
+        }
+
+        match result {
+            Ok(Some(applied)) => {
+                if tx_type == TransactionType::Deposit {
+                    self.remember_deposit(client, applied, timestamp);
+                }
+                let overdrawn = amount.filter(|requested| {
+                    tx_type == TransactionType::Withdrawal && *requested > applied
+                });
+                if let Some(requested) = overdrawn {
+                    self.audit_log.push(AuditEntry {
+                        client,
+                        tx,
+                        flag: AuditFlag::PartialWithdrawal {
+                            requested,
+                            withdrawn: applied,
+                            shortfall: requested - applied,
+                        },
+                    });
+                }
+                self.on_applied(tx_type, account, tx, applied)?;
+                if let Some(target) = target.as_ref() {
+                    self.credit_transfer(target, tx, applied)?;
+                }
+            }
+            Ok(None) => {}
+            Err(reason) => {
+                if self.config.track_rejections {
+                    let amount = amount
+                        .or_else(|| get_disputable_amount(client, tx, &self.history))
+                        .unwrap_or_default();
+                    let client_row = self.clients.get_mut(account).unwrap();
+                    client_row.last_rejection = Some(RejectionInfo { tx, reason, amount });
+                    increment(
+                        &mut client_row.rejection_count,
+                        "rejection_count",
+                        self.config.counter_overflow,
+                    )?;
+                }
+
+                self.reject(client, tx, reason);
+            }
+        }
+
+        self.reconcile_account(account, tx);
+        if let Some(target) = target.filter(|target| self.clients.contains_key(target)) {
+            self.reconcile_account(&target, tx);
+        }
+        self.check_total_floor(account, tx)?;
+        self.touch_history_entry(client, tx);
+        self.expire_disputes()?;
+
+        if self.config.auto_compact
+            && self
+                .rows_processed
+                .is_multiple_of(self.config.compact_interval.max(1))
+        {
+            self.compact_history();
+        }
+
+        // re-arm the warning once the client recovers above the threshold
+        if let Some(threshold) = self.config.low_balance_threshold {
+            if self.clients[account].available >= threshold {
+                self.low_balance_warned.remove(account);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reverts a deposit or withdrawal that was applied to the account but could not be kept in the history, restoring
+    /// the account and `rows_processed` as they were before, or removing the account if the transaction created it
+    fn undo_recorded(
+        &mut self,
+        account: &AccountKey,
+        tx_type: TransactionType,
+        tx: u32,
+        previous_row: Option<OutputRow>,
+        previous_rows_processed: u64,
+    ) {
+        self.history.remove(&HistoryKey {
+            client: account.0,
+            tx,
+            tx_type,
+        });
+        match previous_row {
+            Some(row) => {
+                self.clients.insert(account.clone(), row);
+            }
+            None => {
+                self.clients.remove(account);
+            }
+        }
+        self.rows_processed = previous_rows_processed;
+    }
+
+    /// With `continuous_reconciliation`, records a [`ReconciliationError`] if the account no longer satisfies
+    /// `available + held == total`
+    fn reconcile_account(&mut self, account: &AccountKey, tx: u32) {
+        if !self.config.continuous_reconciliation {
+            return;
+        }
+
+        let row = &self.clients[account];
+        if row.available + row.held != row.total {
+            self.reconciliation_errors.push(ReconciliationError {
+                client: account.0,
+                tx,
+                available: row.available,
+                held: row.held,
+                total: row.total,
+            });
+        }
+    }
+
+    fn check_total_floor(&mut self, account: &AccountKey, tx: u32) -> Result<(), TransactionError> {
+        let floor = match self.config.total_floor {
+            Some(floor) => floor,
+            None => return Ok(()),
+        };
+
+        let total = self.clients[account].total;
+        if total >= floor {
+            return Ok(());
+        }
+
+        let client = account.0;
+        match self.config.total_floor_policy {
+            TotalFloorPolicy::Flag => {
+                self.audit_log.push(AuditEntry {
+                    client,
+                    tx,
+                    flag: AuditFlag::TotalBelowFloor { total },
+                });
+                Ok(())
+            }
+            TotalFloorPolicy::Abort => Err(TransactionError::TotalBelowFloor { client, tx, total }),
+        }
+    }
+
+    /// Runs the follow-up work for a transaction that moved funds. Handlers are invoked synchronously
+    fn on_applied(
+        &mut self,
+        tx_type: TransactionType,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) -> Result<(), TransactionError> {
+        if matches!(
+            tx_type,
+            TransactionType::Resolve | TransactionType::Chargeback
+        ) {
+            self.clamp_held(account);
+        }
+
+        #[cfg(feature = "async")]
+        self.emit_applied(tx_type, account, tx, amount);
+        self.record_account_event(tx_type, account, tx, amount);
+        #[cfg(feature = "async")]
+        self.record_ledger_row(tx_type, account, tx, amount);
+
+        if matches!(
+            tx_type,
+            TransactionType::Dispute | TransactionType::Resolve | TransactionType::Chargeback
+        ) {
+            let client_row = self.clients.get_mut(account).unwrap();
+            increment(
+                &mut client_row.dispute_activity_count,
+                "dispute_activity_count",
+                self.config.counter_overflow,
+            )?;
+        }
+
+        let client = account.0;
+        let client_row = &self.clients[account];
+        match tx_type {
+            TransactionType::Withdrawal | TransactionType::Transfer => {
+                self.check_low_balance(account, tx)
+            }
+
+            TransactionType::Dispute => {
+                self.dispute_outcomes.opened += 1;
+                self.dispute_outcomes.opened_amount += amount;
+                if let Some(handler) = self.config.on_dispute.as_ref() {
+                    handler(DisputeEvent {
+                        client,
+                        tx,
+                        amount,
+                        held_after: client_row.held,
+                    });
+                }
+
+                increment(
+                    self.open_dispute_counts.entry(client).or_default(),
+                    "open_dispute_counts",
+                    self.config.counter_overflow,
+                )?;
+                self.track_dispute_age(client, tx);
+            }
+
+            TransactionType::Resolve => {
+                self.dispute_outcomes.resolved += 1;
+                self.dispute_outcomes.resolved_amount += amount;
+                if let Some(handler) = self.config.on_resolve.as_ref() {
+                    handler(ResolveEvent {
+                        client,
+                        tx,
+                        amount,
+                        available_after: client_row.available,
+                    });
+                }
+
+                self.concluded_disputes.push((client, tx));
+                self.close_open_dispute(client);
+            }
+
+            TransactionType::Chargeback => {
+                self.dispute_outcomes.charged_back += 1;
+                self.dispute_outcomes.charged_back_amount += amount;
+                if let Some(handler) = self.config.on_chargeback.as_ref() {
+                    handler(ChargebackEvent {
+                        client,
+                        tx,
+                        amount,
+                        total_after: client_row.total,
+                    });
+                }
+
+                self.concluded_disputes.push((client, tx));
+                self.close_open_dispute(client);
+            }
+
+            TransactionType::Deposit | TransactionType::Settle => {}
+        }
+
+        Ok(())
+    }
+
+    /// Counts a row towards `rows_processed`
+    fn count_row(&mut self) -> Result<(), TransactionError> {
+        increment(
+            &mut self.rows_processed,
+            "rows_processed",
+            self.config.counter_overflow,
+        )
+    }
+
+    /// Releases a residual held balance within `held_zero_threshold` of zero to the available funds, once the dispute
+    /// just concluded was the client's last open one. Funds held by other disputes are never touched, and neither is
+    /// the total
+    fn clamp_held(&mut self, account: &AccountKey) {
+        // the concluded dispute is still counted
+        let other_disputes_open = self
+            .open_dispute_counts
+            .get(&account.0)
+            .is_some_and(|count| *count > 1);
+        let row = self.clients.get_mut(account).unwrap();
+        if !other_disputes_open
+            && !row.held.is_zero()
+            && row.held.abs() < self.config.held_zero_threshold
+        {
+            row.available += row.held;
+            row.held = Decimal::ZERO;
+        }
+    }
+
+    fn close_open_dispute(&mut self, client: u16) {
+        if let Some(count) = self.open_dispute_counts.get_mut(&client) {
+            *count -= 1;
+            if *count == 0 {
+                self.open_dispute_counts.remove(&client);
+            }
+        }
+    }
+
+    fn track_dispute_age(&mut self, client: u16, tx: u32) {
+        if self.config.dispute_expiry_rows.is_some() {
+            self.dispute_opened_at
+                .insert((client, tx), self.rows_processed);
+            self.dispute_expiry_queue
+                .push_back((self.rows_processed, client, tx));
+        }
+    }
+
+    /// Resolves every dispute that has been open for at least `dispute_expiry_rows` rows, returning its held funds to
+    /// available
+    fn expire_disputes(&mut self) -> Result<(), TransactionError> {
+        let window = match self.config.dispute_expiry_rows {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+
+        while let Some(&(opened_at, client, tx)) = self.dispute_expiry_queue.front() {
+            if self.rows_processed - opened_at < window {
+                break;
+            }
+
+            self.dispute_expiry_queue.pop_front();
+            // a dispute that was resolved (and possibly re-opened) since has a different or no entry
+            if self.dispute_opened_at.get(&(client, tx)) != Some(&opened_at) {
+                continue;
+            }
+
+            self.dispute_opened_at.remove(&(client, tx));
+            let account = self.account_of_tx(client, tx);
+            let client_row = self.clients.get_mut(&account).unwrap();
+            if let Ok(Some(amount)) = process_resolve(
+                Transaction::Resolve { client, tx },
+                client_row,
+                &mut self.history,
+            ) {
+                self.on_applied(TransactionType::Resolve, &account, tx, amount)?;
+            }
+            self.touch_history_entry(client, tx);
+        }
+
+        Ok(())
+    }
+
+    /// Invokes the low balance handler if the client's available funds dropped below the threshold. Fires once per drop
+    fn check_low_balance(&mut self, account: &AccountKey, tx: u32) {
+        if let Some(threshold) = self.config.low_balance_threshold {
+            let available_after = self.clients[account].available;
+            if available_after < threshold && self.low_balance_warned.insert(account.clone()) {
+                if let Some(handler) = self.config.low_balance_handler.as_ref() {
+                    handler(LowBalanceEvent {
+                        client: account.0,
+                        tx,
+                        available_after,
+                        threshold,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Credits an applied transfer to its target account, creating the account if needed
+    fn credit_transfer(
+        &mut self,
+        target: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) -> Result<(), TransactionError> {
+        self.create_client_if_non_exists(target);
+        let target_row = self.clients.get_mut(target).unwrap();
+        increment(
+            &mut target_row.transaction_count,
+            "transaction_count",
+            self.config.counter_overflow,
+        )?;
+        target_row.available += amount;
+        target_row.total += amount;
+
+        #[cfg(feature = "async")]
+        self.emit_transfer_received(target, tx, amount);
+        self.record_transfer_received(target, tx, amount);
+        #[cfg(feature = "async")]
+        self.record_ledger_row(TransactionType::Transfer, target, tx, amount);
+        Ok(())
+    }
+
+    /// Gets the account from the internal map. If the account does not exist, will create a new entry
+    fn create_client_if_non_exists(&mut self, account: &AccountKey) {
+        debug_assert!(self.config.client_filter.allows(account.0));
+        let config = &self.config;
+        let created_seq = self.clients.len() as u64;
+        self.clients
+            .entry(account.clone())
+            .or_insert_with(|| OutputRow {
+                client: account.0,
+                created_seq,
+                ledger: config.multi_ledger.then(|| account.1.clone()),
+                currency: config.multi_currency.then(|| account.2.clone()),
+                pending: config.track_pending.then_some(Decimal::ZERO),
+                ..Default::default()
+            });
     }
 }
@@ -0,0 +1,194 @@
+use crate::tx_engine::{AccountKey, Decimal, OutputRow, TransactionEngine, TransactionType};
+use serde::Serialize;
+
+/// An account's balances right after an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Balances {
+    pub available: Decimal,
+    pub held: Decimal,
+    pub total: Decimal,
+}
+
+/// A change to a client's account, recorded with `track_account_events`. See
+/// [`TransactionEngine::client_event_sequence`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum AccountEvent {
+    Deposited {
+        tx: u32,
+        amount: Decimal,
+        balance_after: Balances,
+    },
+    Withdrew {
+        tx: u32,
+        amount: Decimal,
+        balance_after: Balances,
+    },
+    DisputeOpened {
+        tx: u32,
+        amount: Decimal,
+        held_after: Decimal,
+    },
+    /// Also recorded when a dispute expires
+    DisputeResolved {
+        tx: u32,
+        amount: Decimal,
+        held_after: Decimal,
+    },
+    AccountChargebacked {
+        tx: u32,
+        amount: Decimal,
+    },
+    /// Follows the chargeback that locked the account
+    AccountLocked,
+    /// A pending deposit was credited
+    Settled {
+        tx: u32,
+        amount: Decimal,
+        balance_after: Balances,
+    },
+    TransferSent {
+        tx: u32,
+        amount: Decimal,
+        balance_after: Balances,
+    },
+    TransferReceived {
+        tx: u32,
+        amount: Decimal,
+        balance_after: Balances,
+    },
+}
+
+impl TransactionEngine {
+    /// Returns the events of the client's accounts in the order they were applied. Empty unless
+    /// `track_account_events` is enabled
+    pub fn client_event_sequence(&self, client: u16) -> Vec<AccountEvent> {
+        self.account_events
+            .get(&client)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Rebuilds the client's account from its events, e.g. to check them against the engine's state. Replays the
+    /// basic model, so accounts with pending deposits or clamped held balances may end up differently
+    pub fn replay_events(client: u16, events: Vec<AccountEvent>) -> OutputRow {
+        let mut row = OutputRow {
+            client,
+            ..Default::default()
+        };
+
+        for event in events {
+            match event {
+                AccountEvent::Deposited { amount, .. }
+                | AccountEvent::Settled { amount, .. }
+                | AccountEvent::TransferReceived { amount, .. } => {
+                    row.available += amount;
+                    row.total += amount;
+                }
+                AccountEvent::Withdrew { amount, .. }
+                | AccountEvent::TransferSent { amount, .. } => {
+                    row.available -= amount;
+                    row.total -= amount;
+                }
+                AccountEvent::DisputeOpened { amount, .. } => {
+                    row.available -= amount;
+                    row.held += amount;
+                }
+                AccountEvent::DisputeResolved { amount, .. } => {
+                    row.held -= amount;
+                    row.available += amount;
+                }
+                AccountEvent::AccountChargebacked { amount, .. } => {
+                    row.held -= amount;
+                    row.total -= amount;
+                }
+                AccountEvent::AccountLocked => row.locked = true,
+            }
+        }
+
+        row
+    }
+
+    /// Records the event of a transaction that was just applied to the account
+    pub(crate) fn record_account_event(
+        &mut self,
+        tx_type: TransactionType,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) {
+        if !self.config.track_account_events {
+            return;
+        }
+
+        let row = &self.clients[account];
+        let balance_after = Balances {
+            available: row.available,
+            held: row.held,
+            total: row.total,
+        };
+        let events = self.account_events.entry(account.0).or_default();
+        match tx_type {
+            TransactionType::Deposit => events.push(AccountEvent::Deposited {
+                tx,
+                amount,
+                balance_after,
+            }),
+            TransactionType::Withdrawal => events.push(AccountEvent::Withdrew {
+                tx,
+                amount,
+                balance_after,
+            }),
+            TransactionType::Dispute => events.push(AccountEvent::DisputeOpened {
+                tx,
+                amount,
+                held_after: row.held,
+            }),
+            TransactionType::Resolve => events.push(AccountEvent::DisputeResolved {
+                tx,
+                amount,
+                held_after: row.held,
+            }),
+            TransactionType::Chargeback => {
+                events.push(AccountEvent::AccountChargebacked { tx, amount });
+                events.push(AccountEvent::AccountLocked);
+            }
+            TransactionType::Settle => events.push(AccountEvent::Settled {
+                tx,
+                amount,
+                balance_after,
+            }),
+            TransactionType::Transfer => events.push(AccountEvent::TransferSent {
+                tx,
+                amount,
+                balance_after,
+            }),
+        }
+    }
+
+    /// Records the event of a transfer that was just credited to the account
+    pub(crate) fn record_transfer_received(
+        &mut self,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) {
+        if !self.config.track_account_events {
+            return;
+        }
+
+        let row = &self.clients[account];
+        let balance_after = Balances {
+            available: row.available,
+            held: row.held,
+            total: row.total,
+        };
+        self.account_events
+            .entry(account.0)
+            .or_default()
+            .push(AccountEvent::TransferReceived {
+                tx,
+                amount,
+                balance_after,
+            });
+    }
+}
@@ -0,0 +1,72 @@
+use crate::tx_engine::{Decimal, OutputRow, TransactionEngine, TransactionError};
+use arrow::array::{ArrayRef, BooleanArray, Decimal128Array, UInt16Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use std::sync::Arc;
+
+/// Precision and scale of the balance columns
+const DECIMAL_PRECISION: u8 = 18;
+const DECIMAL_SCALE: i8 = 4;
+
+impl TransactionEngine {
+    /// Returns the balances output as an Arrow record batch, sorted by client, with `client` as `UInt16`, the
+    /// balances as `Decimal128(18, 4)` and `locked` as `Boolean`. Ledger, currency and pending columns are not included
+    pub fn to_record_batch(&self) -> RecordBatch {
+        let mut rows = self
+            .clients
+            .iter()
+            .filter(|(_, row)| self.is_output(row))
+            .collect::<Vec<_>>();
+        rows.sort_unstable_by_key(|(account, _)| *account);
+        let rows = rows.into_iter().map(|(_, row)| row).collect::<Vec<_>>();
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt16Array::from_iter_values(
+                rows.iter().map(|row| row.client),
+            )),
+            decimal_column(&rows, |row| row.available),
+            decimal_column(&rows, |row| row.held),
+            decimal_column(&rows, |row| row.total),
+            Arc::new(BooleanArray::from(
+                rows.iter().map(|row| row.locked).collect::<Vec<_>>(),
+            )),
+        ];
+
+        RecordBatch::try_new(Arc::new(arrow_schema()), columns)
+            .expect("the columns match the schema")
+    }
+
+    /// Writes the balances output in the Arrow IPC file format. See [`Self::to_record_batch`]
+    pub fn write_arrow_ipc<W: std::io::Write>(&self, output: W) -> Result<(), TransactionError> {
+        let batch = self.to_record_batch();
+        let mut writer = FileWriter::try_new(output, &batch.schema())?;
+        writer.write(&batch)?;
+        Ok(writer.finish()?)
+    }
+}
+
+fn arrow_schema() -> Schema {
+    let balance = DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE);
+    Schema::new(vec![
+        Field::new("client", DataType::UInt16, false),
+        Field::new("available", balance.clone(), false),
+        Field::new("held", balance.clone(), false),
+        Field::new("total", balance, false),
+        Field::new("locked", DataType::Boolean, false),
+    ])
+}
+
+fn decimal_column(rows: &[&OutputRow], balance: impl Fn(&OutputRow) -> Decimal) -> ArrayRef {
+    let values = rows.iter().map(|row| {
+        let mut value = balance(row);
+        value.rescale(DECIMAL_SCALE as u32);
+        value.mantissa()
+    });
+
+    Arc::new(
+        Decimal128Array::from_iter_values(values)
+            .with_precision_and_scale(DECIMAL_PRECISION, DECIMAL_SCALE)
+            .expect("the precision and scale are valid"),
+    )
+}
@@ -0,0 +1,142 @@
+use crate::tx_engine::{
+    AccountKey, Decimal, HistoryKey, OutputRow, Transaction, TransactionEngine, TransactionError,
+    TransactionType,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+
+/// The state written by [`TransactionEngine::save_checkpoint`]
+#[derive(Serialize, Deserialize)]
+struct Checkpoint {
+    /// In order of creation
+    accounts: Vec<CheckpointAccount>,
+    /// Deposits, withdrawals and open disputes
+    history: Vec<Transaction>,
+    tx_accounts: Vec<((u16, u32), AccountKey)>,
+    dispute_opened_at: Vec<((u16, u32), u64)>,
+    rows_processed: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CheckpointAccount {
+    client: u16,
+    ledger: Option<String>,
+    currency: Option<String>,
+    available: Decimal,
+    held: Decimal,
+    pending: Option<Decimal>,
+    total: Decimal,
+    locked: bool,
+}
+
+impl TransactionEngine {
+    /// Writes the accounts and the history of transactions that can still be disputed as JSON, so that an engine with
+    /// the same configuration can carry on with [`Self::load_checkpoint`]. Reports, logs and per-account counters are
+    /// not included, nor is input buffered by [`Self::feed`]
+    pub fn save_checkpoint<W: Write>(&self, writer: W) -> Result<(), TransactionError> {
+        let mut accounts = self.clients.values().collect::<Vec<_>>();
+        accounts.sort_unstable_by_key(|row| row.created_seq);
+
+        // sorted so that the same state always yields the same checkpoint
+        let mut history = self.history.iter().collect::<Vec<_>>();
+        history.sort_unstable_by_key(|(key, _)| (key.client, key.tx, key.tx_type.as_str()));
+        let mut tx_accounts = self.tx_accounts.iter().collect::<Vec<_>>();
+        tx_accounts.sort_unstable();
+        let mut dispute_opened_at = self.dispute_opened_at.iter().collect::<Vec<_>>();
+        dispute_opened_at.sort_unstable();
+
+        let checkpoint = Checkpoint {
+            accounts: accounts
+                .into_iter()
+                .map(|row| CheckpointAccount {
+                    client: row.client,
+                    ledger: row.ledger.clone(),
+                    currency: row.currency.clone(),
+                    available: row.available,
+                    held: row.held,
+                    pending: row.pending,
+                    total: row.total,
+                    locked: row.locked,
+                })
+                .collect(),
+            history: history
+                .into_iter()
+                .map(|(_, transaction)| transaction.clone())
+                .collect(),
+            tx_accounts: tx_accounts
+                .into_iter()
+                .map(|(key, account)| (*key, account.clone()))
+                .collect(),
+            dispute_opened_at: dispute_opened_at
+                .into_iter()
+                .map(|(key, opened_at)| (*key, *opened_at))
+                .collect(),
+            rows_processed: self.rows_processed,
+        };
+
+        serde_json::to_writer(writer, &checkpoint).map_err(TransactionError::Checkpoint)
+    }
+
+    /// Replaces the engine's state with a checkpoint written by [`Self::save_checkpoint`]. The state is left as is if
+    /// the checkpoint cannot be read
+    pub fn load_checkpoint<R: Read>(&mut self, reader: R) -> Result<(), TransactionError> {
+        let checkpoint: Checkpoint =
+            serde_json::from_reader(reader).map_err(TransactionError::Checkpoint)?;
+
+        self.clear();
+        for (created_seq, account) in checkpoint.accounts.into_iter().enumerate() {
+            let row = OutputRow {
+                client: account.client,
+                ledger: account.ledger,
+                currency: account.currency,
+                available: account.available,
+                held: account.held,
+                pending: account.pending,
+                total: account.total,
+                locked: account.locked,
+                created_seq: created_seq as u64,
+                ..Default::default()
+            };
+            self.clients.insert(row.account(), row);
+        }
+
+        let mut recorded = Vec::new();
+        for transaction in checkpoint.history {
+            let (client, tx) = (transaction.client(), transaction.tx());
+            let tx_type = transaction.transaction_type();
+            match tx_type {
+                TransactionType::Dispute => {
+                    *self.open_dispute_counts.entry(client).or_default() += 1
+                }
+                _ => recorded.push((client, tx)),
+            }
+
+            self.history.insert(
+                HistoryKey {
+                    client,
+                    tx,
+                    tx_type,
+                },
+                transaction,
+            );
+        }
+
+        // referenced in tx order, as their last references are not known
+        recorded.sort_unstable();
+        for (client, tx) in recorded {
+            self.touch_history_entry(client, tx);
+        }
+
+        self.tx_accounts.extend(checkpoint.tx_accounts);
+        let mut opened = checkpoint
+            .dispute_opened_at
+            .iter()
+            .map(|&((client, tx), opened_at)| (opened_at, client, tx))
+            .collect::<Vec<_>>();
+        opened.sort_unstable();
+        self.dispute_expiry_queue.extend(opened);
+        self.dispute_opened_at.extend(checkpoint.dispute_opened_at);
+        self.rows_processed = checkpoint.rows_processed;
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::tx_engine::TransactionError;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, ObjectStoreExt};
+use tokio::io::AsyncRead;
+use tokio_stream::StreamExt;
+use tokio_util::io::StreamReader;
+
+/// URI schemes that are opened through `object_store` rather than the local filesystem
+#[cfg(not(feature = "cloud-storage"))]
+const SCHEMES: &[&str] = &["s3://"];
+#[cfg(feature = "cloud-storage")]
+const SCHEMES: &[&str] = &["s3://", "gs://", "az://"];
+
+/// Returns true if the input names an object in a supported cloud store rather than a local file
+pub fn is_object_uri(input: &str) -> bool {
+    SCHEMES.iter().any(|scheme| input.starts_with(scheme))
+}
+
+/// Opens the object as a streaming reader. Credentials and region are picked up from the standard environment
+/// variables (e.g. `AWS_ACCESS_KEY_ID`) through each store's default credential chain
+pub async fn open_object(
+    uri: &str,
+) -> Result<Box<dyn AsyncRead + Unpin + Send + Sync>, TransactionError> {
+    let (store, location) = build_store(uri)?;
+    let stream = store
+        .get(&location)
+        .await?
+        .into_stream()
+        .map(|chunk| chunk.map_err(std::io::Error::other));
+
+    Ok(Box::new(StreamReader::new(SyncStream(
+        std::sync::Mutex::new(stream),
+    ))))
+}
+
+fn build_store(uri: &str) -> Result<(Box<dyn ObjectStore>, ObjectPath), TransactionError> {
+    // everything after the bucket/container is the object key
+    let (_, rest) = uri.split_once("://").unwrap_or_default();
+    let key = rest.split_once('/').map(|(_, key)| key).unwrap_or_default();
+    let location = ObjectPath::parse(key)?;
+
+    let store: Box<dyn ObjectStore> = if uri.starts_with("s3://") {
+        Box::new(
+            object_store::aws::AmazonS3Builder::from_env()
+                .with_url(uri)
+                .build()?,
+        )
+    } else {
+        build_other_store(uri)?
+    };
+
+    Ok((store, location))
+}
+
+#[cfg(feature = "cloud-storage")]
+fn build_other_store(uri: &str) -> Result<Box<dyn ObjectStore>, TransactionError> {
+    if uri.starts_with("gs://") {
+        Ok(Box::new(
+            object_store::gcp::GoogleCloudStorageBuilder::from_env()
+                .with_url(uri)
+                .build()?,
+        ))
+    } else {
+        Ok(Box::new(
+            object_store::azure::MicrosoftAzureBuilder::from_env()
+                .with_url(uri)
+                .build()?,
+        ))
+    }
+}
+
+#[cfg(not(feature = "cloud-storage"))]
+fn build_other_store(uri: &str) -> Result<Box<dyn ObjectStore>, TransactionError> {
+    Err(TransactionError::UnsupportedUri(uri.to_string()))
+}
+
+/// The engine's readers are required to be `Sync`, which the object stream is not. The stream is only ever accessed
+/// through `&mut`, so the mutex is never actually locked
+struct SyncStream<S>(std::sync::Mutex<S>);
+
+impl<S: tokio_stream::Stream + Unpin> tokio_stream::Stream for SyncStream<S> {
+    type Item = S::Item;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        let stream = self
+            .0
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        std::pin::Pin::new(stream).poll_next(cx)
+    }
+}
@@ -0,0 +1,102 @@
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Removes lines whose first non-whitespace character is `#` from the underlying reader before they reach the CSV
+/// parser. When disabled, reads are passed straight through
+pub struct CommentFilter<R> {
+    inner: R,
+    enabled: bool,
+    at_line_start: bool,
+    in_comment: bool,
+    // leading whitespace of the current line, held back until we know whether the line is a comment
+    pending_whitespace: Vec<u8>,
+    filtered: Vec<u8>,
+    filtered_pos: usize,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> CommentFilter<R> {
+    pub fn new(inner: R, enabled: bool) -> Self {
+        Self {
+            inner,
+            enabled,
+            at_line_start: true,
+            in_comment: false,
+            pending_whitespace: Vec::new(),
+            filtered: Vec::with_capacity(if enabled { CHUNK_SIZE } else { 0 }),
+            filtered_pos: 0,
+            eof: false,
+        }
+    }
+
+    fn filter(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            if self.in_comment {
+                if byte == b'\n' {
+                    self.in_comment = false;
+                    self.at_line_start = true;
+                }
+            } else if self.at_line_start {
+                match byte {
+                    b' ' | b'\t' => self.pending_whitespace.push(byte),
+                    b'#' => {
+                        self.pending_whitespace.clear();
+                        self.in_comment = true;
+                    }
+                    _ => {
+                        self.filtered.append(&mut self.pending_whitespace);
+                        self.filtered.push(byte);
+                        self.at_line_start = byte == b'\n';
+                    }
+                }
+            } else {
+                self.filtered.push(byte);
+                self.at_line_start = byte == b'\n';
+            }
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CommentFilter<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        if !this.enabled {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        }
+
+        loop {
+            if this.filtered_pos < this.filtered.len() {
+                let remaining = &this.filtered[this.filtered_pos..];
+                let n = remaining.len().min(buf.remaining());
+                buf.put_slice(&remaining[..n]);
+                this.filtered_pos += n;
+                return Poll::Ready(Ok(()));
+            }
+
+            if this.eof {
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut chunk = [0u8; CHUNK_SIZE];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+
+            this.filtered.clear();
+            this.filtered_pos = 0;
+            if chunk_buf.filled().is_empty() {
+                // trailing whitespace on the last line is not a comment
+                this.filtered.append(&mut this.pending_whitespace);
+                this.eof = true;
+            } else {
+                this.filter(chunk_buf.filled());
+            }
+        }
+    }
+}
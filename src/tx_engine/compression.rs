@@ -0,0 +1,65 @@
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+use tokio::io::{AsyncRead, AsyncReadExt, BufReader};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// Compression formats recognized by their leading magic bytes
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    fn detect(prefix: &[u8]) -> Self {
+        if prefix.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if prefix.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Sniffs the first bytes of the input and, if they match a known compression format, wraps the input in the
+/// matching decoder. The sniffed bytes are put back in front of the stream, so uncompressed input passes through
+/// unchanged. This does not depend on file extensions, so renamed files and piped input are handled too
+pub async fn decompress_detected<R: AsyncRead + Unpin + Send + Sync + 'static>(
+    mut input: R,
+) -> std::io::Result<(Compression, Box<dyn AsyncRead + Unpin + Send + Sync>)> {
+    // a single read may return fewer bytes than the longest magic number, so keep reading until we have enough or EOF
+    let mut prefix = Vec::with_capacity(ZSTD_MAGIC.len());
+    while prefix.len() < ZSTD_MAGIC.len() {
+        let mut byte = [0u8; 4];
+        let n = input
+            .read(&mut byte[..ZSTD_MAGIC.len() - prefix.len()])
+            .await?;
+        if n == 0 {
+            break;
+        }
+
+        prefix.extend_from_slice(&byte[..n]);
+    }
+
+    let compression = Compression::detect(&prefix);
+    let input = BufReader::new(std::io::Cursor::new(prefix).chain(input));
+
+    let reader: Box<dyn AsyncRead + Unpin + Send + Sync> = match compression {
+        Compression::None => Box::new(input),
+        Compression::Gzip => {
+            let mut decoder = GzipDecoder::new(input);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+        Compression::Zstd => {
+            let mut decoder = ZstdDecoder::new(input);
+            decoder.multiple_members(true);
+            Box::new(decoder)
+        }
+    };
+
+    Ok((compression, reader))
+}
@@ -0,0 +1,1116 @@
+#[cfg(feature = "encoding")]
+use crate::tx_engine::InputEncoding;
+#[cfg(feature = "io-retry")]
+use crate::tx_engine::IoRetryConfig;
+use crate::tx_engine::{
+    ChargebackEvent, Decimal, Decision, DisputeEvent, FraudRule, FraudScore, LowBalanceEvent,
+    RejectionSink, ResolveEvent, SelectBias, ShardedTransactionEngine, Transaction,
+    TransactionEngine, TransactionError, TransactionType,
+};
+use rust_decimal::RoundingStrategy;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "signal-handling")]
+use tokio::sync::watch;
+
+/// Heuristic number of entries pre-allocated when no capacity hint is provided
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// Default size of the buffer wrapping the output
+pub const DEFAULT_OUTPUT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Default number of transactions between automatic history compactions
+pub const DEFAULT_COMPACT_INTERVAL: u64 = 10_000;
+
+/// Default bound on the number of updates buffered between the reader and writer in streaming mode
+pub const DEFAULT_MAX_IN_FLIGHT_ROWS: usize = 64;
+
+/// Default number of rows in each chunk deserialized by a parse worker
+pub const DEFAULT_PARSE_CHUNK_SIZE: usize = 4096;
+
+/// Invoked synchronously before each transaction is applied to accept, reject or abort it, e.g. to enforce custom
+/// business rules. Use interior mutability for rules that keep state
+pub type VetoHandler = Box<dyn Fn(&Transaction) -> Decision + Send + Sync>;
+
+/// Default largest difference between the amounts of two deposits that are considered duplicates
+pub const DEFAULT_DEPOSIT_DEDUP_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// Default number of recent deposits remembered per client for deduplication
+pub const DEFAULT_DEPOSIT_DEDUP_CAPACITY: usize = 1024;
+
+/// Default aggregate fraud score above which a deposit is flagged
+pub const DEFAULT_FRAUD_THRESHOLD: FraudScore = 70;
+
+/// Callback invoked synchronously from within the engine when an event occurs
+pub type EventHandler<E> = Box<dyn Fn(E) + Send + Sync>;
+
+/// Invoked synchronously when a client's available funds drop below the low balance threshold
+pub type LowBalanceHandler = EventHandler<LowBalanceEvent>;
+
+/// Applied to each parsed amount before it is processed, e.g. to convert cents into whole units. Returning `None`
+/// (such as on overflow) marks the amount as invalid
+pub type AmountTransform = Box<dyn Fn(Decimal) -> Option<Decimal> + Send + Sync>;
+
+/// What happens when a deposit or withdrawal arrives while the history holds `max_history_entries` entries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryEvictionPolicy {
+    /// Fails with [`TransactionError::HistoryFull`](crate::tx_engine::TransactionError::HistoryFull)
+    #[default]
+    Reject,
+    /// Evicts the least recently referenced deposit or withdrawal that is not under dispute. Evicted transactions can
+    /// no longer be disputed
+    EvictLru,
+}
+
+/// What happens when a counter, such as the rows processed or a client's transaction count, is incremented past its
+/// maximum
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CounterOverflow {
+    /// Keeps the counter at its maximum
+    #[default]
+    Saturate,
+    /// Fails with [`TransactionError::CounterOverflow`](crate::tx_engine::TransactionError::CounterOverflow)
+    Error,
+}
+
+/// How disputes are handled once a chargeback has locked the client's account
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockedDisputePolicy {
+    /// Rejects the dispute with [`RejectionReason::AccountLocked`](crate::tx_engine::RejectionReason::AccountLocked),
+    /// leaving the locked account's balances frozen
+    #[default]
+    Ignore,
+    /// Applies the dispute as if the account were not locked, e.g. for a second dispute that was raised before the
+    /// chargeback but arrived after it
+    Process,
+}
+
+/// How amounts with more than four decimal places are rounded to four
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AmountRounding {
+    /// 1.23455 becomes 1.2346
+    #[default]
+    HalfUp,
+    /// Banker's rounding: 1.23455 becomes 1.2346 and 1.23445 becomes 1.2344
+    HalfEven,
+    /// 1.23459 becomes 1.2345
+    Truncate,
+}
+
+impl AmountRounding {
+    pub(crate) fn strategy(self) -> RoundingStrategy {
+        match self {
+            Self::HalfUp => RoundingStrategy::MidpointAwayFromZero,
+            Self::HalfEven => RoundingStrategy::MidpointNearestEven,
+            Self::Truncate => RoundingStrategy::ToZero,
+        }
+    }
+}
+
+/// Order of the accounts in the balances output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortField {
+    #[default]
+    ClientId,
+    /// Highest total first, e.g. to reconcile high-value accounts first
+    TotalBalance,
+    /// Highest available funds first
+    AvailableBalance,
+    /// The order in which accounts were created, i.e. of each client's first transaction
+    InsertionOrder,
+}
+
+/// What happens when a client's total drops below `total_floor`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotalFloorPolicy {
+    /// Records an [`AuditFlag::TotalBelowFloor`](crate::tx_engine::AuditFlag::TotalBelowFloor) entry and continues
+    #[default]
+    Flag,
+    /// Fails with [`TransactionError::TotalBelowFloor`](crate::tx_engine::TransactionError::TotalBelowFloor)
+    Abort,
+}
+
+/// Line terminator written after each output row
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineTerminator {
+    #[default]
+    Lf,
+    /// `\r\n`, as expected by Excel and other Windows tools
+    CrLf,
+}
+
+impl LineTerminator {
+    #[cfg(any(feature = "async", feature = "sync"))]
+    pub(crate) fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::CrLf => b"\r\n",
+        }
+    }
+}
+
+/// How the `locked` column of the balances output is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LockedFormat {
+    #[default]
+    TrueFalse,
+    /// `1`/`0`, for consumers that expect a numeric flag
+    OneZero,
+    YesNo,
+}
+
+impl LockedFormat {
+    #[cfg(any(feature = "async", feature = "sync"))]
+    pub(crate) fn format(self, locked: bool) -> &'static str {
+        match (self, locked) {
+            (Self::TrueFalse, true) => "true",
+            (Self::TrueFalse, false) => "false",
+            (Self::OneZero, true) => "1",
+            (Self::OneZero, false) => "0",
+            (Self::YesNo, true) => "yes",
+            (Self::YesNo, false) => "no",
+        }
+    }
+
+    /// Parses a `locked` value written in any of the formats
+    pub(crate) fn parse(value: &str) -> Option<bool> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Some(true),
+            "false" | "0" | "no" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Which clients may transact. Guards multi-tenant deployments against applying another tenant's transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ClientFilter {
+    #[default]
+    All,
+    /// Rejects transactions of clients outside the set
+    Whitelist(HashSet<u16>),
+    /// Rejects transactions of clients in the set
+    Blacklist(HashSet<u16>),
+}
+
+impl ClientFilter {
+    pub fn allows(&self, client: u16) -> bool {
+        match self {
+            ClientFilter::All => true,
+            ClientFilter::Whitelist(clients) => clients.contains(&client),
+            ClientFilter::Blacklist(clients) => !clients.contains(&client),
+        }
+    }
+}
+
+/// Settings that control how a [`TransactionEngine`] allocates and processes rows
+pub struct TransactionEngineConfig {
+    /// Number of clients pre-allocated in the clients map
+    pub client_capacity: usize,
+    /// Number of entries pre-allocated in the history map
+    pub history_capacity: usize,
+    /// Bounds the number of entries in the history. Deposits, withdrawals and open disputes each take an entry.
+    /// Unbounded by default
+    pub max_history_entries: Option<usize>,
+    /// What happens to a deposit or withdrawal once the history is full
+    pub history_eviction_policy: HistoryEvictionPolicy,
+    /// Size in bytes of the buffer wrapping the output. Larger buffers mean fewer writes for very large outputs
+    pub output_buffer_size: usize,
+    /// Ends the output with a newline after the last row. Enabled by default
+    pub output_trailing_newline: bool,
+    /// Ends the output with an empty line, for consumers that use it as an end-of-data marker. Implies a newline after
+    /// the last row
+    pub output_trailing_blank_line: bool,
+    /// Terminator written after each output row, including the trailing newline and blank line. `\n` by default
+    pub output_line_terminator: LineTerminator,
+    /// Quotes every output field rather than only those that need it
+    pub output_always_quote: bool,
+    /// Whether `output_always_quote` also quotes numeric fields, i.e. the client ID and the amounts. Disabling it suits
+    /// consumers such as SQL `COPY` that reject quoted numbers. Enabled by default; without `output_always_quote`,
+    /// numbers never need quoting either way
+    pub quote_numeric_output: bool,
+    /// Columns of the balances output, in the order they are written, e.g. `["client", "total"]`. Every column in the
+    /// default order when unset. Names that are not output columns are reported by
+    /// [`TransactionEngineConfig::validate`] and otherwise ignored
+    pub output_columns: Option<Vec<String>>,
+    /// Order of the accounts in the balances output. Only affects the final output, not the order in which rows are
+    /// processed. By client ID by default
+    pub sort_output_by: SortField,
+    /// How the `locked` column is written. `true`/`false` by default
+    pub locked_field_format: LockedFormat,
+    /// Reads back the balances output with
+    /// [`TransactionEngine::validate_output`](crate::tx_engine::TransactionEngine::validate_output), or
+    /// [`TransactionEngine::validate_output_sync`](crate::tx_engine::TransactionEngine::validate_output_sync) in
+    /// `process_sync`, before writing it, failing instead of writing invalid output. The output is buffered in memory in
+    /// full
+    pub validate_output: bool,
+    /// Only outputs the accounts of clients with at least one applied dispute, resolve or chargeback, e.g. for a
+    /// dispute-focused report
+    pub disputed_clients_only: bool,
+    /// Creates missing parent directories of output files instead of failing
+    pub create_output_dirs: bool,
+    /// Maximum number of client updates buffered before input reading is paused in streaming mode
+    pub max_in_flight_rows: usize,
+    /// Which channel is preferred when several have rows ready in multi-channel mode
+    pub select_bias: SelectBias,
+    /// When processing fails part-way through the input, write the balances accumulated so far before returning the
+    /// error. Off by default, since such output does not reflect the whole input
+    pub flush_partial_output_on_error: bool,
+    /// Validates the header row of every input against the expected columns before reading any row. See
+    /// [`TransactionEngine::process_with_schema_validation`]
+    pub strict_mode: bool,
+    /// When set, a dispute that is still open this many rows after it was opened is automatically resolved and its
+    /// held funds are returned to available. Note that this can mask disputes that were genuinely never resolved
+    pub dispute_expiry_rows: Option<u64>,
+    /// Calls [`TransactionEngine::compact_history`] every `compact_interval` transactions
+    pub auto_compact: bool,
+    /// Number of transactions between automatic compactions. A value of 0 is treated as 1
+    pub compact_interval: u64,
+    /// When set, a successful withdrawal leaving `available` below this value triggers the low balance handler
+    pub low_balance_threshold: Option<Decimal>,
+    pub low_balance_handler: Option<LowBalanceHandler>,
+    /// Invoked before each transaction is applied and may reject it or abort processing
+    pub veto: Option<VetoHandler>,
+    /// Receives every rejected transaction as it is refused, in addition to the list kept in memory for
+    /// [`TransactionEngine::rejections`](crate::tx_engine::TransactionEngine::rejections)
+    pub rejection_sink: Option<Box<dyn RejectionSink + Send + Sync>>,
+    /// Invoked after each applied dispute, e.g. to notify the customer
+    pub on_dispute: Option<EventHandler<DisputeEvent>>,
+    /// Invoked after each applied resolve, including those resolved automatically by dispute expiry
+    pub on_resolve: Option<EventHandler<ResolveEvent>>,
+    /// Invoked after each applied chargeback
+    pub on_chargeback: Option<EventHandler<ChargebackEvent>>,
+    /// Invoked with the error of each connection to `serve_tcp` or `serve_unix` that failed. The connection is closed
+    /// either way
+    #[cfg(feature = "async")]
+    pub on_connection_error: Option<EventHandler<TransactionError>>,
+    /// Floor for a client's available funds, which may be negative for margin accounts. Withdrawals and disputes
+    /// that would breach it are rejected. When unset, withdrawals are limited to the available funds and disputes
+    /// are unrestricted
+    pub min_available: Option<Decimal>,
+    /// How far withdrawals and transfers may drive a client's available funds into the negative. Zero by default, so
+    /// they are limited to the available funds. Cannot be combined with `min_available`, which is the more general
+    /// floor
+    pub overdraft_limit: Decimal,
+    /// Instead of rejecting a withdrawal exceeding the funds it may draw on, withdraws what is there, recording the
+    /// shortfall as an [`AuditFlag::PartialWithdrawal`](crate::tx_engine::AuditFlag::PartialWithdrawal). Disabled by
+    /// default
+    pub partial_withdrawal: bool,
+    /// Whether [`TransactionEngine::apply_interest`](crate::tx_engine::TransactionEngine::apply_interest) also credits
+    /// locked accounts. Disabled by default
+    pub interest_on_locked: bool,
+    /// When set, every client's total is checked against this floor after each transaction. Unlike `available`, the
+    /// total cannot legitimately go negative in the basic model, so a breach points to a bookkeeping bug
+    pub total_floor: Option<Decimal>,
+    /// Checks `available + held == total` for the client after each transaction, recording violations as
+    /// [`ReconciliationError`](crate::tx_engine::ReconciliationError)s instead of failing. Catches bookkeeping bugs at
+    /// the transaction responsible for them
+    pub continuous_reconciliation: bool,
+    /// Whether a breach of `total_floor` is flagged or aborts processing. Defaults to [`TotalFloorPolicy::Flag`]
+    pub total_floor_policy: TotalFloorPolicy,
+    /// Whether disputes are applied to locked accounts. Defaults to [`LockedDisputePolicy::Ignore`]
+    pub locked_dispute_policy: LockedDisputePolicy,
+    /// Whether counters saturate or fail processing on overflow. Defaults to [`CounterOverflow::Saturate`]
+    pub counter_overflow: CounterOverflow,
+    /// Rejects disputes whose row includes an amount that differs from the disputed transaction's. Disputes without
+    /// an amount are unaffected
+    pub match_dispute_amounts: bool,
+    /// Rejects disputes that would leave the client's held funds above their total with
+    /// [`RejectionReason::DisputeExceedsTotal`](crate::tx_engine::RejectionReason::DisputeExceedsTotal), e.g. a
+    /// dispute of a deposit that was partly withdrawn since. By default such disputes hold the full amount and leave
+    /// `available` negative
+    pub reject_disputes_exceeding_total: bool,
+    /// A held balance closer to zero than this after a resolve or chargeback that leaves the client without open
+    /// disputes is released to the available funds. `Decimal` arithmetic is exact, so this only matters when amounts
+    /// went through `f64` before reaching the engine, e.g. in an amount transform. 0 by default, which disables the
+    /// clamping
+    pub held_zero_threshold: Decimal,
+    /// Rejects deposits and withdrawals without an amount with
+    /// [`RejectionReason::MissingAmount`](crate::tx_engine::RejectionReason::MissingAmount) instead of failing, and
+    /// disputes, resolves and chargebacks with one with
+    /// [`RejectionReason::UnexpectedAmount`](crate::tx_engine::RejectionReason::UnexpectedAmount) instead of ignoring it
+    pub validate_row_shape: bool,
+    /// Deposits larger than this are rejected. Guards against data entry errors such as a wrong decimal separator
+    pub max_deposit_amount: Option<Decimal>,
+    /// Withdrawals larger than this are rejected
+    pub max_withdrawal_amount: Option<Decimal>,
+    /// General per-type limits. When a type also has a dedicated limit above, the lower of the two applies
+    pub max_amount_by_type: HashMap<TransactionType, Decimal>,
+    /// Treats a final input row that fails to parse as the end of the input instead of an error, e.g. when reading a
+    /// file that another process is still appending to. Errors on earlier rows are still returned
+    pub ignore_partial_final_row: bool,
+    /// Number of chunks of rows deserialized concurrently on blocking threads by `process`. Rows are still applied in
+    /// input order. Values of 0 and 1 parse serially, which is the default
+    pub parse_workers: usize,
+    /// Number of rows in each chunk deserialized by a parse worker
+    pub parse_chunk_size: usize,
+    /// Stops reading the input after this many rows, e.g. to sanity check a sample of a huge file. Rows that are
+    /// skipped or rejected count towards the limit. Unlimited by default
+    pub max_rows: Option<u64>,
+    /// Drops the first rows the engine is given without processing them, e.g. to restart a large input that partially
+    /// failed after the rows that were already applied. Rows that fail to parse still fail. Since dropped deposits
+    /// and withdrawals are not in the history, disputes referencing them are rejected. 0 by default
+    pub skip_rows: u64,
+    /// Drops rows, after those dropped by `skip_rows`, until the first row of a client with this ID or higher, for
+    /// restarts by client range. Later rows of lower clients are processed. Has the same caveat for disputes as
+    /// `skip_rows`
+    pub start_at_client: Option<u16>,
+    /// Skips input lines whose first non-whitespace character is `#`. Disabled by default, so that such lines fail to
+    /// parse as before
+    pub skip_comment_lines: bool,
+    /// Trims fields and lowercases the transaction type of every row before it is processed
+    pub normalize_input: bool,
+    /// Maps alternate spellings of a transaction type (e.g. `charge_back`) to its canonical name, whether or not
+    /// `normalize_input` is enabled. Keys are lowercase and matched case-insensitively
+    pub type_aliases: HashMap<String, String>,
+    /// Credits deposits flagged in the `pending` column to a separate pending balance until a `settle` row for the same
+    /// tx. Adds a `pending` column to the output
+    pub track_pending: bool,
+    /// Keeps a separate account per client and `ledger` column value. Disputes, resolves, chargebacks and settles
+    /// apply to the ledger of the transaction they reference. Adds a `ledger` column to the output
+    pub multi_ledger: bool,
+    /// Keeps a separate account per client and `currency` column value, like `multi_ledger` does for ledgers. Adds a
+    /// `currency` column to the output
+    pub multi_currency: bool,
+    /// Applied to every parsed amount before validation, so a transform yielding a negative amount is rejected
+    pub amount_transform: Option<AmountTransform>,
+    /// How amounts are rounded to four decimal places, after the amount transform and before they are applied or
+    /// stored in the history. Half up by default
+    pub amount_rounding: AmountRounding,
+    /// Rules scoring each deposit for fraud risk. Their scores are summed, saturating at 100
+    pub fraud_rules: Vec<Box<dyn FraudRule + Send + Sync>>,
+    /// Deposits whose aggregate fraud score exceeds this are flagged in the audit log
+    pub fraud_threshold: FraudScore,
+    /// Rejects flagged deposits instead of only recording them in the audit log
+    pub reject_suspected_fraud: bool,
+    /// Transparently decompresses gzip or zstd input files, detected by their magic bytes
+    #[cfg(feature = "compression")]
+    pub detect_compression: bool,
+    /// Character encoding of the inputs, transcoded to UTF-8 before parsing. Only matters for text fields such as
+    /// categories and ledgers, since amounts and types are ASCII. UTF-8 by default
+    #[cfg(feature = "encoding")]
+    pub input_encoding: InputEncoding,
+    /// When set, rows whose `category` column is not in this set are skipped. Rows without a category are always
+    /// processed. Compared after normalization, so entries should be lowercase when `normalize_input` is enabled
+    pub allowed_categories: Option<HashSet<String>>,
+    /// Transactions of clients the filter does not allow are rejected with
+    /// [`RejectionReason::ClientNotAllowed`](crate::tx_engine::RejectionReason::ClientNotAllowed) and never create an
+    /// account. Accounts that already exist can still be queried and exported
+    pub client_filter: ClientFilter,
+    /// When set, only rows whose `timestamp` is within this inclusive range of Unix seconds are processed. Other rows,
+    /// including rows without a timestamp, are recorded as skipped. Since deposits outside the window are never
+    /// applied, disputes referencing them are rejected with
+    /// [`RejectionReason::TransactionNotFound`](crate::tx_engine::RejectionReason::TransactionNotFound)
+    pub time_window: Option<RangeInclusive<u64>>,
+    /// Rows of these transaction types are recorded as skipped instead of being processed, e.g. to ignore chargebacks
+    /// in a test environment. Unknown types are still rejected as invalid
+    pub disabled_types: HashSet<TransactionType>,
+    /// Rejects a deposit with [`RejectionReason::DuplicateDeposit`](crate::tx_engine::RejectionReason::DuplicateDeposit)
+    /// when the same client deposited the same amount less than this long before, going by the `timestamp` column.
+    /// Catches retries that were resubmitted under a new tx. Deposits without a timestamp are not deduplicated
+    pub deposit_dedup_window: Option<Duration>,
+    /// How much the amounts of two deposits may differ for them to be considered duplicates
+    pub deposit_dedup_tolerance: Decimal,
+    /// Bounds the deposits remembered per client for deduplication. The oldest are forgotten first
+    pub deposit_dedup_capacity: usize,
+    /// Rejects disputes with
+    /// [`RejectionReason::TooManyOpenDisputes`](crate::tx_engine::RejectionReason::TooManyOpenDisputes) while the
+    /// client already has this many open disputes, bounding the history held per client
+    pub max_open_disputes_per_client: Option<usize>,
+    /// Keeps the number of rejected transactions and the most recent rejection of each account, for
+    /// [`TransactionEngine::write_rejections_per_client`]. They are never part of the balances output
+    pub track_rejections: bool,
+    /// Tracks the sum and number of chargebacks of every account, e.g. to find clients with many chargebacks
+    pub track_chargeback_totals: bool,
+    /// Keeps every applied transaction of each client as an
+    /// [`AccountEvent`](crate::tx_engine::AccountEvent), for
+    /// [`TransactionEngine::client_event_sequence`](crate::tx_engine::TransactionEngine::client_event_sequence). Memory
+    /// grows with the number of transactions
+    pub track_account_events: bool,
+    /// Retries transient errors when reading input files
+    #[cfg(feature = "io-retry")]
+    pub io_retry: Option<IoRetryConfig>,
+    /// Memory-maps local input files instead of reading them through tokio, which can be faster for very large files.
+    /// The files must not be modified while they are processed. Not combined with `io_retry`
+    #[cfg(feature = "mmap")]
+    pub use_mmap: bool,
+    /// How long to wait when connecting to an input that is a Unix domain socket. Waits indefinitely when unset
+    #[cfg(feature = "unix-socket")]
+    pub connect_timeout: Option<Duration>,
+    /// Stops `process` once the value becomes `true`, writing the balances accumulated so far before failing with
+    /// [`TransactionError::Interrupted`](crate::tx_engine::TransactionError::Interrupted)
+    #[cfg(feature = "signal-handling")]
+    pub shutdown: Option<watch::Receiver<bool>>,
+}
+
+impl Default for TransactionEngineConfig {
+    fn default() -> Self {
+        Self {
+            client_capacity: DEFAULT_CAPACITY,
+            history_capacity: DEFAULT_CAPACITY,
+            max_history_entries: None,
+            history_eviction_policy: HistoryEvictionPolicy::Reject,
+            output_buffer_size: DEFAULT_OUTPUT_BUFFER_SIZE,
+            output_trailing_newline: true,
+            output_trailing_blank_line: false,
+            output_line_terminator: LineTerminator::Lf,
+            output_always_quote: false,
+            quote_numeric_output: true,
+            output_columns: None,
+            locked_field_format: LockedFormat::TrueFalse,
+            sort_output_by: SortField::ClientId,
+            validate_output: false,
+            disputed_clients_only: false,
+            create_output_dirs: false,
+            max_in_flight_rows: DEFAULT_MAX_IN_FLIGHT_ROWS,
+            select_bias: SelectBias::Fair,
+            flush_partial_output_on_error: false,
+            strict_mode: false,
+            dispute_expiry_rows: None,
+            auto_compact: false,
+            compact_interval: DEFAULT_COMPACT_INTERVAL,
+            low_balance_threshold: None,
+            low_balance_handler: None,
+            veto: None,
+            rejection_sink: None,
+            on_dispute: None,
+            on_resolve: None,
+            on_chargeback: None,
+            #[cfg(feature = "async")]
+            on_connection_error: None,
+            min_available: None,
+            overdraft_limit: Decimal::ZERO,
+            partial_withdrawal: false,
+            interest_on_locked: false,
+            total_floor: None,
+            continuous_reconciliation: false,
+            total_floor_policy: TotalFloorPolicy::Flag,
+            locked_dispute_policy: LockedDisputePolicy::Ignore,
+            counter_overflow: CounterOverflow::Saturate,
+            match_dispute_amounts: false,
+            reject_disputes_exceeding_total: false,
+            held_zero_threshold: Decimal::ZERO,
+            validate_row_shape: false,
+            max_deposit_amount: None,
+            max_withdrawal_amount: None,
+            max_amount_by_type: HashMap::new(),
+            ignore_partial_final_row: false,
+            max_rows: None,
+            skip_rows: 0,
+            start_at_client: None,
+            parse_workers: 1,
+            parse_chunk_size: DEFAULT_PARSE_CHUNK_SIZE,
+            skip_comment_lines: false,
+            normalize_input: true,
+            type_aliases: HashMap::new(),
+            track_pending: false,
+            multi_ledger: false,
+            multi_currency: false,
+            amount_transform: None,
+            amount_rounding: AmountRounding::HalfUp,
+            fraud_rules: Vec::new(),
+            fraud_threshold: DEFAULT_FRAUD_THRESHOLD,
+            reject_suspected_fraud: false,
+            allowed_categories: None,
+            client_filter: ClientFilter::All,
+            time_window: None,
+            disabled_types: HashSet::new(),
+            deposit_dedup_window: None,
+            deposit_dedup_tolerance: DEFAULT_DEPOSIT_DEDUP_TOLERANCE,
+            deposit_dedup_capacity: DEFAULT_DEPOSIT_DEDUP_CAPACITY,
+            max_open_disputes_per_client: None,
+            track_rejections: false,
+            track_chargeback_totals: false,
+            track_account_events: false,
+            #[cfg(feature = "compression")]
+            detect_compression: true,
+            #[cfg(feature = "encoding")]
+            input_encoding: InputEncoding::Utf8,
+            #[cfg(feature = "io-retry")]
+            io_retry: None,
+            #[cfg(feature = "mmap")]
+            use_mmap: false,
+            #[cfg(feature = "unix-socket")]
+            connect_timeout: None,
+            #[cfg(feature = "signal-handling")]
+            shutdown: None,
+        }
+    }
+}
+
+impl TransactionEngineConfig {
+    /// Returns the effective maximum amount for the given transaction type, if any
+    pub fn max_amount(&self, tx_type: TransactionType) -> Option<Decimal> {
+        let dedicated = match tx_type {
+            TransactionType::Deposit => self.max_deposit_amount,
+            TransactionType::Withdrawal => self.max_withdrawal_amount,
+            _ => None,
+        };
+
+        match (dedicated, self.max_amount_by_type.get(&tx_type).copied()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Used to construct a [`TransactionEngine`] with a non-default configuration
+#[derive(Default)]
+pub struct TransactionEngineBuilder {
+    config: TransactionEngineConfig,
+}
+
+impl TransactionEngineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-allocates the clients map for `n` clients. Useful when the approximate number of clients is known in advance
+    pub fn with_client_capacity(mut self, n: usize) -> Self {
+        self.config.client_capacity = n;
+        self
+    }
+
+    /// Pre-allocates the history map for `n` deposits/withdrawals/disputes
+    pub fn with_history_capacity(mut self, n: usize) -> Self {
+        self.config.history_capacity = n;
+        self
+    }
+
+    /// Bounds the history to `max` entries, applying `policy` once it is full
+    pub fn with_max_history_entries(mut self, max: usize, policy: HistoryEvictionPolicy) -> Self {
+        self.config.max_history_entries = Some(max);
+        self.config.history_eviction_policy = policy;
+        self
+    }
+
+    /// Sets the size in bytes of the buffer wrapping the output
+    pub fn with_output_buffer_size(mut self, bytes: usize) -> Self {
+        self.config.output_buffer_size = bytes;
+        self
+    }
+
+    /// Toggles the newline after the last output row. Enabled by default
+    pub fn with_output_trailing_newline(mut self, newline: bool) -> Self {
+        self.config.output_trailing_newline = newline;
+        self
+    }
+
+    /// Toggles an empty line at the end of the output
+    pub fn with_output_trailing_blank_line(mut self, blank_line: bool) -> Self {
+        self.config.output_trailing_blank_line = blank_line;
+        self
+    }
+
+    /// Sets the terminator written after each output row
+    pub fn with_output_line_terminator(mut self, terminator: LineTerminator) -> Self {
+        self.config.output_line_terminator = terminator;
+        self
+    }
+
+    /// Toggles quoting every output field. Disabled by default
+    pub fn with_output_always_quote(mut self, always_quote: bool) -> Self {
+        self.config.output_always_quote = always_quote;
+        self
+    }
+
+    /// Writes only these columns of the balances output, in this order
+    pub fn with_output_columns<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        columns: I,
+    ) -> Self {
+        self.config.output_columns = Some(
+            columns
+                .into_iter()
+                .map(|column| column.as_ref().to_string())
+                .collect(),
+        );
+        self
+    }
+
+    /// Toggles quoting numeric fields when every output field is quoted. Enabled by default
+    pub fn with_quote_numeric_output(mut self, quote_numeric: bool) -> Self {
+        self.config.quote_numeric_output = quote_numeric;
+        self
+    }
+
+    /// Sets the order of the accounts in the balances output
+    pub fn with_sort_output_by(mut self, field: SortField) -> Self {
+        self.config.sort_output_by = field;
+        self
+    }
+
+    /// Sets how the `locked` column is written
+    pub fn with_locked_field_format(mut self, format: LockedFormat) -> Self {
+        self.config.locked_field_format = format;
+        self
+    }
+
+    /// Toggles reading back the balances output before writing it. Disabled by default
+    pub fn with_validate_output(mut self, validate: bool) -> Self {
+        self.config.validate_output = validate;
+        self
+    }
+
+    /// Toggles only outputting the accounts of clients with dispute activity. Disabled by default
+    pub fn with_disputed_clients_only(mut self, only: bool) -> Self {
+        self.config.disputed_clients_only = only;
+        self
+    }
+
+    /// Toggles creating missing parent directories of output files. Disabled by default
+    pub fn with_create_output_dirs(mut self, create: bool) -> Self {
+        self.config.create_output_dirs = create;
+        self
+    }
+
+    /// Bounds the number of buffered client updates in streaming mode. A value of 0 is treated as 1
+    pub fn with_max_in_flight_rows(mut self, n: usize) -> Self {
+        self.config.max_in_flight_rows = n;
+        self
+    }
+
+    /// Sets which channel is preferred when several have rows ready in multi-channel mode
+    pub fn with_select_bias(mut self, bias: SelectBias) -> Self {
+        self.config.select_bias = bias;
+        self
+    }
+
+    /// Validates the header row of every input before processing it. Disabled by default
+    pub fn with_strict_mode(mut self, strict_mode: bool) -> Self {
+        self.config.strict_mode = strict_mode;
+        self
+    }
+
+    /// Writes the balances accumulated so far when processing fails part-way through the input
+    pub fn with_flush_partial_output_on_error(mut self, flush: bool) -> Self {
+        self.config.flush_partial_output_on_error = flush;
+        self
+    }
+
+    /// Automatically resolves disputes that are still open `rows` rows after being opened
+    pub fn with_dispute_expiry_rows(mut self, rows: u64) -> Self {
+        self.config.dispute_expiry_rows = Some(rows);
+        self
+    }
+
+    /// Compacts the history automatically every `interval` transactions
+    pub fn with_auto_compact(mut self, interval: u64) -> Self {
+        self.config.auto_compact = true;
+        self.config.compact_interval = interval;
+        self
+    }
+
+    /// Sets the available balance below which a withdrawal triggers a low balance warning
+    pub fn with_low_balance_threshold(mut self, threshold: Decimal) -> Self {
+        self.config.low_balance_threshold = Some(threshold);
+        self
+    }
+
+    /// Registers the handler invoked once per client each time their available funds drop below the threshold
+    pub fn on_low_balance(
+        mut self,
+        handler: impl Fn(LowBalanceEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.low_balance_handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers the callback deciding whether each transaction is applied, rejected or aborts processing
+    pub fn with_veto(
+        mut self,
+        veto: impl Fn(&Transaction) -> Decision + Send + Sync + 'static,
+    ) -> Self {
+        self.config.veto = Some(Box::new(veto));
+        self
+    }
+
+    /// Passes every rejected transaction to `sink`, e.g. to log it through the host application's logger
+    pub fn with_rejection_sink(mut self, sink: impl RejectionSink + Send + Sync + 'static) -> Self {
+        self.config.rejection_sink = Some(Box::new(sink));
+        self
+    }
+
+    /// Registers the handler invoked after each applied dispute
+    pub fn on_dispute(mut self, handler: impl Fn(DisputeEvent) + Send + Sync + 'static) -> Self {
+        self.config.on_dispute = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers the handler invoked after each applied resolve
+    pub fn on_resolve(mut self, handler: impl Fn(ResolveEvent) + Send + Sync + 'static) -> Self {
+        self.config.on_resolve = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers the handler invoked after each applied chargeback
+    pub fn on_chargeback(
+        mut self,
+        handler: impl Fn(ChargebackEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.on_chargeback = Some(Box::new(handler));
+        self
+    }
+
+    /// Registers the handler invoked with the error of each failed server connection
+    #[cfg(feature = "async")]
+    pub fn on_connection_error(
+        mut self,
+        handler: impl Fn(TransactionError) + Send + Sync + 'static,
+    ) -> Self {
+        self.config.on_connection_error = Some(Box::new(handler));
+        self
+    }
+
+    /// Rejects withdrawals and disputes that would leave the client's available funds below `floor`. A negative floor
+    /// allows an overdraft, e.g. `-limit` for credit accounts that may go up to `limit` into the negative
+    pub fn with_min_available(mut self, floor: Decimal) -> Self {
+        self.config.min_available = Some(floor);
+        self
+    }
+
+    /// Lets withdrawals and transfers drive the client's available funds down to `-limit`, rejecting them beyond it.
+    /// Unlike `min_available`, which must not be set as well, disputes are not restricted
+    pub fn with_overdraft_limit(mut self, limit: Decimal) -> Self {
+        self.config.overdraft_limit = limit;
+        self
+    }
+
+    /// Lets a withdrawal exceeding the available funds drain them rather than be rejected
+    pub fn with_partial_withdrawal(mut self, partial: bool) -> Self {
+        self.config.partial_withdrawal = partial;
+        self
+    }
+
+    /// Lets interest be credited to locked accounts too
+    pub fn with_interest_on_locked(mut self, enabled: bool) -> Self {
+        self.config.interest_on_locked = enabled;
+        self
+    }
+
+    /// Checks after each transaction that the client's total is at least `floor`, e.g. `Decimal::ZERO`
+    pub fn with_total_floor(mut self, floor: Decimal) -> Self {
+        self.config.total_floor = Some(floor);
+        self
+    }
+
+    /// Toggles checking the client's balances after each transaction. Disabled by default
+    pub fn with_continuous_reconciliation(mut self, reconcile: bool) -> Self {
+        self.config.continuous_reconciliation = reconcile;
+        self
+    }
+
+    /// Sets whether a breach of the total floor is flagged or aborts processing
+    pub fn with_total_floor_policy(mut self, policy: TotalFloorPolicy) -> Self {
+        self.config.total_floor_policy = policy;
+        self
+    }
+
+    /// Sets whether counters saturate or fail processing on overflow
+    pub fn with_counter_overflow(mut self, policy: CounterOverflow) -> Self {
+        self.config.counter_overflow = policy;
+        self
+    }
+
+    /// Sets whether disputes are applied to locked accounts
+    pub fn with_locked_dispute_policy(mut self, policy: LockedDisputePolicy) -> Self {
+        self.config.locked_dispute_policy = policy;
+        self
+    }
+
+    /// Toggles rejecting disputes whose amount does not match the disputed transaction. Disabled by default
+    pub fn with_match_dispute_amounts(mut self, match_amounts: bool) -> Self {
+        self.config.match_dispute_amounts = match_amounts;
+        self
+    }
+
+    /// Toggles rejecting disputes that would hold more than the client's total. Disabled by default
+    pub fn with_reject_disputes_exceeding_total(mut self, reject: bool) -> Self {
+        self.config.reject_disputes_exceeding_total = reject;
+        self
+    }
+
+    /// Sets how close to zero a held balance is released after the client's last open dispute concludes, e.g.
+    /// `Decimal::new(1, 8)`
+    pub fn with_held_zero_threshold(mut self, threshold: Decimal) -> Self {
+        self.config.held_zero_threshold = threshold;
+        self
+    }
+
+    /// Toggles rejecting rows whose amount does not fit their transaction type. Disabled by default
+    pub fn with_validate_row_shape(mut self, validate: bool) -> Self {
+        self.config.validate_row_shape = validate;
+        self
+    }
+
+    /// Rejects deposits with an amount above `limit`
+    pub fn with_max_deposit_amount(mut self, limit: Decimal) -> Self {
+        self.config.max_deposit_amount = Some(limit);
+        self
+    }
+
+    /// Rejects withdrawals with an amount above `limit`
+    pub fn with_max_withdrawal_amount(mut self, limit: Decimal) -> Self {
+        self.config.max_withdrawal_amount = Some(limit);
+        self
+    }
+
+    /// Rejects transactions of the given type with an amount above `limit`
+    pub fn with_max_amount(mut self, tx_type: TransactionType, limit: Decimal) -> Self {
+        self.config.max_amount_by_type.insert(tx_type, limit);
+        self
+    }
+
+    /// Toggles ignoring a final input row that fails to parse. Disabled by default
+    pub fn with_ignore_partial_final_row(mut self, ignore: bool) -> Self {
+        self.config.ignore_partial_final_row = ignore;
+        self
+    }
+
+    /// Sets the number of chunks of rows deserialized concurrently. Useful when parsing, rather than processing,
+    /// bounds the throughput on a large input
+    pub fn with_parse_workers(mut self, workers: usize) -> Self {
+        self.config.parse_workers = workers;
+        self
+    }
+
+    /// Sets the number of rows in each chunk deserialized by a parse worker
+    pub fn with_parse_chunk_size(mut self, rows: usize) -> Self {
+        self.config.parse_chunk_size = rows;
+        self
+    }
+
+    /// Stops reading the input after `rows` rows
+    pub fn with_max_rows(mut self, rows: u64) -> Self {
+        self.config.max_rows = Some(rows);
+        self
+    }
+
+    /// Drops the first `rows` rows without processing them
+    pub fn with_skip_rows(mut self, rows: u64) -> Self {
+        self.config.skip_rows = rows;
+        self
+    }
+
+    /// Drops rows until the first row of a client with ID `client` or higher
+    pub fn with_start_at_client(mut self, client: u16) -> Self {
+        self.config.start_at_client = Some(client);
+        self
+    }
+
+    /// Toggles skipping of `#` comment lines in the input. Disabled by default
+    pub fn with_skip_comment_lines(mut self, skip: bool) -> Self {
+        self.config.skip_comment_lines = skip;
+        self
+    }
+
+    /// Toggles trimming and lowercasing of input rows. Enabled by default
+    pub fn with_normalize_input(mut self, normalize_input: bool) -> Self {
+        self.config.normalize_input = normalize_input;
+        self
+    }
+
+    /// Treats the type `alias` (case-insensitive) as `canonical`, e.g. `charge_back` as `chargeback`
+    pub fn with_type_alias(mut self, alias: &str, canonical: &str) -> Self {
+        self.config
+            .type_aliases
+            .insert(alias.trim().to_lowercase(), canonical.to_string());
+        self
+    }
+
+    /// Treats the type `synonym` (case-insensitive) as `tx_type`, e.g. `credit` as [`TransactionType::Deposit`] for
+    /// feeds using their own vocabulary. The canonical names keep working alongside synonyms
+    pub fn with_type_synonym(self, synonym: &str, tx_type: TransactionType) -> Self {
+        self.with_type_alias(synonym, tx_type.as_str())
+    }
+
+    /// Toggles tracking of pending deposits. Disabled by default, in which case pending deposits are rejected
+    pub fn with_track_pending(mut self, track_pending: bool) -> Self {
+        self.config.track_pending = track_pending;
+        self
+    }
+
+    /// Toggles separate accounts per ledger of a client. Disabled by default, in which case the `ledger` column is
+    /// ignored
+    pub fn with_multi_ledger(mut self, multi_ledger: bool) -> Self {
+        self.config.multi_ledger = multi_ledger;
+        self
+    }
+
+    /// Toggles separate accounts per currency of a client. Disabled by default, in which case the `currency` column is
+    /// ignored
+    pub fn with_multi_currency(mut self, multi_currency: bool) -> Self {
+        self.config.multi_currency = multi_currency;
+        self
+    }
+
+    /// Transforms every parsed amount before it is processed, e.g. `|cents| cents.checked_div(Decimal::from(100))`
+    pub fn with_amount_transform(
+        mut self,
+        transform: impl Fn(Decimal) -> Option<Decimal> + Send + Sync + 'static,
+    ) -> Self {
+        self.config.amount_transform = Some(Box::new(transform));
+        self
+    }
+
+    /// Sets how amounts with more than four decimal places are rounded
+    pub fn with_amount_rounding(mut self, rounding: AmountRounding) -> Self {
+        self.config.amount_rounding = rounding;
+        self
+    }
+
+    /// Adds a rule scoring each deposit for fraud risk
+    pub fn with_fraud_rule(mut self, rule: impl FraudRule + Send + Sync + 'static) -> Self {
+        self.config.fraud_rules.push(Box::new(rule));
+        self
+    }
+
+    /// Sets the aggregate fraud score above which a deposit is flagged
+    pub fn with_fraud_threshold(mut self, threshold: FraudScore) -> Self {
+        self.config.fraud_threshold = threshold;
+        self
+    }
+
+    /// Toggles rejecting flagged deposits. Disabled by default, so they are only recorded in the audit log
+    pub fn with_reject_suspected_fraud(mut self, reject: bool) -> Self {
+        self.config.reject_suspected_fraud = reject;
+        self
+    }
+
+    /// Only processes rows in the given categories (plus rows without a category). Other rows are recorded as skipped
+    pub fn with_allowed_categories<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        categories: I,
+    ) -> Self {
+        self.config.allowed_categories = Some(
+            categories
+                .into_iter()
+                .map(|category| category.as_ref().trim().to_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Only processes rows of the given clients, e.g. to investigate a few accounts in a large input. Shorthand for a
+    /// [`ClientFilter::Whitelist`] of `clients`
+    pub fn with_allowed_clients<I: IntoIterator<Item = u16>>(self, clients: I) -> Self {
+        self.with_client_filter(ClientFilter::Whitelist(clients.into_iter().collect()))
+    }
+
+    /// Sets which clients may transact. Allows every client by default
+    pub fn with_client_filter(mut self, filter: ClientFilter) -> Self {
+        self.config.client_filter = filter;
+        self
+    }
+
+    /// Only processes rows timestamped within `window`, e.g. a single day
+    pub fn with_time_window(mut self, window: RangeInclusive<u64>) -> Self {
+        self.config.time_window = Some(window);
+        self
+    }
+
+    /// Skips every row of the given transaction type
+    pub fn with_disabled_type(mut self, tx_type: TransactionType) -> Self {
+        self.config.disabled_types.insert(tx_type);
+        self
+    }
+
+    /// Rejects repeated deposits of the same amount by a client within `window`, e.g. network retries
+    pub fn with_deposit_dedup_window(mut self, window: Duration) -> Self {
+        self.config.deposit_dedup_window = Some(window);
+        self
+    }
+
+    pub fn with_deposit_dedup_tolerance(mut self, tolerance: Decimal) -> Self {
+        self.config.deposit_dedup_tolerance = tolerance;
+        self
+    }
+
+    pub fn with_deposit_dedup_capacity(mut self, n: usize) -> Self {
+        self.config.deposit_dedup_capacity = n;
+        self
+    }
+
+    pub fn with_max_open_disputes_per_client(mut self, max: usize) -> Self {
+        self.config.max_open_disputes_per_client = Some(max);
+        self
+    }
+
+    pub fn with_track_rejections(mut self, track_rejections: bool) -> Self {
+        self.config.track_rejections = track_rejections;
+        self
+    }
+
+    /// Toggles tracking the sum and number of chargebacks of every account. Disabled by default
+    pub fn with_track_chargeback_totals(mut self, track: bool) -> Self {
+        self.config.track_chargeback_totals = track;
+        self
+    }
+
+    /// Toggles keeping each client's account events. Disabled by default
+    pub fn with_track_account_events(mut self, track: bool) -> Self {
+        self.config.track_account_events = track;
+        self
+    }
+
+    /// Toggles detection of compressed input files. Enabled by default
+    #[cfg(feature = "compression")]
+    pub fn with_detect_compression(mut self, detect_compression: bool) -> Self {
+        self.config.detect_compression = detect_compression;
+        self
+    }
+
+    /// Sets the character encoding of the inputs
+    #[cfg(feature = "encoding")]
+    pub fn with_input_encoding(mut self, encoding: InputEncoding) -> Self {
+        self.config.input_encoding = encoding;
+        self
+    }
+
+    /// Retries transient read errors on input files with exponential backoff
+    #[cfg(feature = "io-retry")]
+    pub fn with_io_retry(mut self, retry: IoRetryConfig) -> Self {
+        self.config.io_retry = Some(retry);
+        self
+    }
+
+    /// Toggles memory-mapping of local input files. Disabled by default
+    #[cfg(feature = "mmap")]
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.config.use_mmap = use_mmap;
+        self
+    }
+
+    /// Gives up connecting to a Unix domain socket input after `timeout`
+    #[cfg(feature = "unix-socket")]
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Stops processing gracefully once `true` is sent, e.g. from a SIGTERM handler
+    #[cfg(feature = "signal-handling")]
+    pub fn with_shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.config.shutdown = Some(shutdown);
+        self
+    }
+
+    pub fn build(self) -> TransactionEngine {
+        TransactionEngine::new(Arc::new(self.config))
+    }
+
+    /// Like [`Self::build`], but first checks the configuration with [`TransactionEngineConfig::validate`]
+    pub fn try_build(self) -> Result<TransactionEngine, TransactionError> {
+        self.config
+            .validate()
+            .map_err(|errors| TransactionError::InvalidConfig { errors })?;
+        Ok(self.build())
+    }
+
+    /// Builds an engine partitioning clients across `shards` sub-engines, which process their rows in parallel
+    pub fn build_sharded(self, shards: usize) -> ShardedTransactionEngine {
+        ShardedTransactionEngine::new(self.build(), shards)
+    }
+}
@@ -0,0 +1,206 @@
+use crate::tx_engine::{
+    Decimal, HistoryEvictionPolicy, TransactionEngineConfig, TransactionType, OUTPUT_COLUMNS,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+
+const TRANSACTION_TYPES: [TransactionType; 7] = [
+    TransactionType::Deposit,
+    TransactionType::Withdrawal,
+    TransactionType::Dispute,
+    TransactionType::Resolve,
+    TransactionType::Chargeback,
+    TransactionType::Settle,
+    TransactionType::Transfer,
+];
+
+/// A setting, or a combination of settings, that cannot work as intended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ConfigError {
+    /// A limit or threshold that must not be negative is
+    NegativeValue {
+        setting: &'static str,
+        value: Decimal,
+    },
+    /// `time_window` starts after it ends, so every row would be skipped
+    EmptyTimeWindow { start: u64, end: u64 },
+    /// `skip_rows` drops every row `max_rows` lets through
+    SkipsEveryRow { skip_rows: u64, max_rows: u64 },
+    /// A type alias maps to a name that is not a transaction type, so its rows would fail to parse
+    UnknownAliasTarget { alias: String, target: String },
+    /// `output_columns` names a column the balances output does not have
+    UnknownOutputColumn { name: String },
+    /// `output_columns` names the same column more than once
+    DuplicateOutputColumn { name: String },
+    /// `output_columns` is set but empty, so the output would have no columns
+    NoOutputColumns,
+    /// The low balance handler is set without `low_balance_threshold`, so it would never be invoked
+    LowBalanceHandlerWithoutThreshold,
+    /// `reject_suspected_fraud` is set without fraud rules, so no deposit would ever be rejected
+    FraudRejectionWithoutRules,
+    /// `overdraft_limit` is set along with `min_available`. Both set the lowest available funds a withdrawal may
+    /// leave, so only one of them may be set
+    OverdraftLimitWithMinAvailable,
+    /// `max_history_entries` is 0, so no deposit or withdrawal could ever be recorded
+    NoHistoryEntries,
+    /// An eviction policy other than rejecting is set without `max_history_entries`, so nothing would ever be evicted
+    EvictionWithoutHistoryLimit,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NegativeValue { setting, value } => {
+                write!(f, "{} must not be negative but is {}", setting, value)
+            }
+            Self::EmptyTimeWindow { start, end } => write!(
+                f,
+                "time_window starts at {} after it ends at {}",
+                start, end
+            ),
+            Self::SkipsEveryRow {
+                skip_rows,
+                max_rows,
+            } => write!(
+                f,
+                "skip_rows {} skips every one of the max_rows {} rows",
+                skip_rows, max_rows
+            ),
+            Self::UnknownAliasTarget { alias, target } => write!(
+                f,
+                "type alias {:?} maps to {:?}, which is not a transaction type",
+                alias, target
+            ),
+            Self::UnknownOutputColumn { name } => write!(
+                f,
+                "output column {:?} is not one of {}",
+                name,
+                OUTPUT_COLUMNS.join(", ")
+            ),
+            Self::DuplicateOutputColumn { name } => {
+                write!(f, "output column {:?} is listed more than once", name)
+            }
+            Self::NoOutputColumns => write!(f, "output_columns is empty"),
+            Self::LowBalanceHandlerWithoutThreshold => {
+                write!(f, "the low balance handler is set without a threshold")
+            }
+            Self::FraudRejectionWithoutRules => {
+                write!(f, "reject_suspected_fraud is set without any fraud rules")
+            }
+            Self::OverdraftLimitWithMinAvailable => {
+                write!(f, "overdraft_limit is set along with min_available")
+            }
+            Self::NoHistoryEntries => write!(f, "max_history_entries is 0"),
+            Self::EvictionWithoutHistoryLimit => write!(
+                f,
+                "history_eviction_policy is set without max_history_entries"
+            ),
+        }
+    }
+}
+
+impl TransactionEngineConfig {
+    /// Checks for settings that are invalid or contradict each other, returning every problem found. Called by
+    /// [`TransactionEngineBuilder::try_build`](crate::tx_engine::TransactionEngineBuilder::try_build), and by the
+    /// engine before it applies any row or writes any output
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut errors = Vec::new();
+
+        let mut non_negative = vec![
+            ("max_deposit_amount", self.max_deposit_amount),
+            ("max_withdrawal_amount", self.max_withdrawal_amount),
+            ("held_zero_threshold", Some(self.held_zero_threshold)),
+            ("overdraft_limit", Some(self.overdraft_limit)),
+            (
+                "deposit_dedup_tolerance",
+                Some(self.deposit_dedup_tolerance),
+            ),
+        ];
+        // in a fixed order, so that errors are reported deterministically
+        non_negative.extend(TRANSACTION_TYPES.iter().map(|tx_type| {
+            (
+                "max_amount_by_type",
+                self.max_amount_by_type.get(tx_type).copied(),
+            )
+        }));
+        for (setting, value) in non_negative {
+            if let Some(value) = value.filter(Decimal::is_sign_negative) {
+                errors.push(ConfigError::NegativeValue { setting, value });
+            }
+        }
+
+        if let Some(window) = self.time_window.as_ref() {
+            if window.start() > window.end() {
+                errors.push(ConfigError::EmptyTimeWindow {
+                    start: *window.start(),
+                    end: *window.end(),
+                });
+            }
+        }
+
+        if let Some(max_rows) = self.max_rows {
+            if self.skip_rows > 0 && self.skip_rows >= max_rows {
+                errors.push(ConfigError::SkipsEveryRow {
+                    skip_rows: self.skip_rows,
+                    max_rows,
+                });
+            }
+        }
+
+        let mut aliases = self.type_aliases.iter().collect::<Vec<_>>();
+        aliases.sort_unstable();
+        for (alias, target) in aliases {
+            if !TRANSACTION_TYPES
+                .iter()
+                .any(|tx_type| tx_type.as_str() == target)
+            {
+                errors.push(ConfigError::UnknownAliasTarget {
+                    alias: alias.clone(),
+                    target: target.clone(),
+                });
+            }
+        }
+
+        if let Some(columns) = self.output_columns.as_ref() {
+            if columns.is_empty() {
+                errors.push(ConfigError::NoOutputColumns);
+            }
+
+            let mut seen = HashSet::new();
+            for name in columns {
+                if !OUTPUT_COLUMNS.contains(&name.as_str()) {
+                    errors.push(ConfigError::UnknownOutputColumn { name: name.clone() });
+                } else if !seen.insert(name) {
+                    errors.push(ConfigError::DuplicateOutputColumn { name: name.clone() });
+                }
+            }
+        }
+
+        if self.low_balance_handler.is_some() && self.low_balance_threshold.is_none() {
+            errors.push(ConfigError::LowBalanceHandlerWithoutThreshold);
+        }
+
+        if self.reject_suspected_fraud && self.fraud_rules.is_empty() {
+            errors.push(ConfigError::FraudRejectionWithoutRules);
+        }
+
+        if !self.overdraft_limit.is_zero() && self.min_available.is_some() {
+            errors.push(ConfigError::OverdraftLimitWithMinAvailable);
+        }
+
+        match self.max_history_entries {
+            Some(0) => errors.push(ConfigError::NoHistoryEntries),
+            None if self.history_eviction_policy != HistoryEvictionPolicy::Reject => {
+                errors.push(ConfigError::EvictionWithoutHistoryLimit)
+            }
+            _ => {}
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
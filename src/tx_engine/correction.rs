@@ -0,0 +1,72 @@
+use crate::tx_engine::{AuditEntry, AuditFlag, Decimal, TransactionEngine, TransactionError};
+use serde::Serialize;
+
+/// An administrative change to a client's balances that bypasses the transaction flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BalanceCorrection {
+    /// Adds to the available and total funds
+    AdjustAvailable(Decimal),
+    /// Adds to the held and total funds
+    AdjustHeld(Decimal),
+    /// Sets the total funds, making up the difference in the available funds
+    SetTotal(Decimal),
+    AdjustAll {
+        available_delta: Decimal,
+        held_delta: Decimal,
+    },
+}
+
+impl TransactionEngine {
+    /// Applies a correction to the client's account in the default ledger and currency and records it in the audit
+    /// log. Fails without changing anything if the client has no account, or if the corrected balances would not
+    /// satisfy `available + held == total`, e.g. because the account was already inconsistent
+    pub fn apply_correction(
+        &mut self,
+        client: u16,
+        correction: BalanceCorrection,
+    ) -> Result<(), TransactionError> {
+        let row = self
+            .clients
+            .get_mut(&(client, String::new(), String::new()))
+            .ok_or(TransactionError::UnknownClient(client))?;
+
+        let (available, held, total) = match correction {
+            BalanceCorrection::AdjustAvailable(delta) => {
+                (row.available + delta, row.held, row.total + delta)
+            }
+            BalanceCorrection::AdjustHeld(delta) => {
+                (row.available, row.held + delta, row.total + delta)
+            }
+            BalanceCorrection::SetTotal(total) => {
+                (row.available + (total - row.total), row.held, total)
+            }
+            BalanceCorrection::AdjustAll {
+                available_delta,
+                held_delta,
+            } => (
+                row.available + available_delta,
+                row.held + held_delta,
+                row.total + available_delta + held_delta,
+            ),
+        };
+
+        if available + held != total {
+            return Err(TransactionError::CorrectionViolatesInvariant {
+                client,
+                available,
+                held,
+                total,
+            });
+        }
+
+        row.available = available;
+        row.held = held;
+        row.total = total;
+        self.audit_log.push(AuditEntry {
+            client,
+            tx: 0,
+            flag: AuditFlag::Corrected(correction),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,41 @@
+use crate::tx_engine::{CounterOverflow, TransactionError};
+
+/// Unsigned integer counter
+pub(crate) trait Counter: Copy {
+    fn checked_increment(self) -> Option<Self>;
+}
+
+impl Counter for u32 {
+    fn checked_increment(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+}
+
+impl Counter for u64 {
+    fn checked_increment(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+}
+
+impl Counter for usize {
+    fn checked_increment(self) -> Option<Self> {
+        self.checked_add(1)
+    }
+}
+
+/// Adds one to `counter`. At its maximum, the counter is left as is or `name` is reported in
+/// [`TransactionError::CounterOverflow`], depending on `policy`
+pub(crate) fn increment<T: Counter>(
+    counter: &mut T,
+    name: &'static str,
+    policy: CounterOverflow,
+) -> Result<(), TransactionError> {
+    match (counter.checked_increment(), policy) {
+        (Some(incremented), _) => {
+            *counter = incremented;
+            Ok(())
+        }
+        (None, CounterOverflow::Saturate) => Ok(()),
+        (None, CounterOverflow::Error) => Err(TransactionError::CounterOverflow { counter: name }),
+    }
+}
@@ -0,0 +1,59 @@
+use crate::tx_engine::{Decimal, TransactionEngine};
+use std::time::Duration;
+
+impl TransactionEngine {
+    /// Whether a deposit by `client` matches one applied within `deposit_dedup_window` of `timestamp`, i.e. has the
+    /// same amount within `deposit_dedup_tolerance`. Deposits without a timestamp are never duplicates
+    pub(crate) fn is_duplicate_deposit(
+        &mut self,
+        client: u16,
+        amount: Decimal,
+        timestamp: Option<u64>,
+    ) -> bool {
+        let (window, timestamp) = match (self.config.deposit_dedup_window, timestamp) {
+            (Some(window), Some(timestamp)) => (window, timestamp),
+            _ => return false,
+        };
+
+        let recent = match self.recent_deposits.get_mut(&client) {
+            Some(recent) => recent,
+            None => return false,
+        };
+
+        // rows are chronological, so deposits that fell out of the window can never match again
+        while recent.front().is_some_and(|(applied_at, _)| {
+            Duration::from_secs(timestamp.saturating_sub(*applied_at)) >= window
+        }) {
+            recent.pop_front();
+        }
+
+        let tolerance = self.config.deposit_dedup_tolerance;
+        recent.iter().any(|(applied_at, recent_amount)| {
+            within(*applied_at, timestamp, window) && (*recent_amount - amount).abs() <= tolerance
+        })
+    }
+
+    /// Remembers an applied deposit for deduplication. At most `deposit_dedup_capacity` deposits are kept per client,
+    /// dropping the oldest
+    pub(crate) fn remember_deposit(
+        &mut self,
+        client: u16,
+        amount: Decimal,
+        timestamp: Option<u64>,
+    ) {
+        let timestamp = match (self.config.deposit_dedup_window, timestamp) {
+            (Some(_), Some(timestamp)) => timestamp,
+            _ => return,
+        };
+
+        let recent = self.recent_deposits.entry(client).or_default();
+        if recent.len() >= self.config.deposit_dedup_capacity.max(1) {
+            recent.pop_front();
+        }
+        recent.push_back((timestamp, amount));
+    }
+}
+
+fn within(applied_at: u64, timestamp: u64, window: Duration) -> bool {
+    Duration::from_secs(applied_at.abs_diff(timestamp)) < window
+}
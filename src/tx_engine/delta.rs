@@ -0,0 +1,246 @@
+use crate::tx_engine::{AccountKey, Decimal, OutputRow, TransactionEngine};
+#[cfg(feature = "async")]
+use crate::tx_engine::{OutputFormat, TransactionError};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+#[cfg(feature = "async")]
+use tokio_stream::StreamExt;
+
+/// The change in a client's balances relative to a baseline
+#[derive(Debug, Clone, Serialize)]
+pub struct DeltaRow {
+    pub client: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub available_delta: Decimal,
+    pub held_delta: Decimal,
+    pub total_delta: Decimal,
+    /// Whether the account was locked in the baseline, so that lock transitions can be told apart
+    pub was_locked: bool,
+    pub locked: bool,
+}
+
+/// How much a client's total changed during a run, for reconciliation
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceChangeRow {
+    pub client: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub starting_total: Decimal,
+    pub ending_total: Decimal,
+    pub change: Decimal,
+    /// `None` when the starting total was 0, since the change cannot be expressed as a percentage of it
+    pub change_pct: Option<Decimal>,
+    pub starting_locked: bool,
+    pub ending_locked: bool,
+}
+
+impl TransactionEngine {
+    /// Parses the CSV written by a previous run into a baseline for [`Self::write_delta_output`]
+    #[cfg(feature = "async")]
+    pub async fn from_output_csv_str(
+        csv: &str,
+    ) -> Result<HashMap<AccountKey, OutputRow>, TransactionError> {
+        let mut rows = csv_async::AsyncDeserializer::from_reader(csv.as_bytes())
+            .into_deserialize::<OutputRow>();
+        let mut baseline = HashMap::new();
+        while let Some(row) = rows.next().await {
+            let row = row?;
+            baseline.insert(row.account(), row);
+        }
+
+        Ok(baseline)
+    }
+
+    /// Consumes the engine, returning the state of every account, e.g. as a baseline for [`Self::write_delta_output`]
+    pub fn into_accounts(self) -> HashMap<AccountKey, OutputRow> {
+        self.clients
+    }
+
+    /// Processes `earlier` and `later` independently, e.g. the transactions up to two end-of-day snapshots, and
+    /// writes how each client's balances changed from the first to the second like [`Self::write_delta_output`]. The
+    /// engine is left with the state of `later`, while `earlier` is processed by a fresh engine with the same
+    /// configuration
+    #[cfg(feature = "async")]
+    pub async fn process_delta<R1, R2, W>(
+        &mut self,
+        earlier: R1,
+        later: R2,
+        output: W,
+    ) -> Result<(), TransactionError>
+    where
+        R1: AsyncRead + Unpin + Send + Sync,
+        R2: AsyncRead + Unpin + Send + Sync,
+        W: AsyncWrite + Unpin,
+    {
+        let mut baseline = TransactionEngine::new(self.config.clone());
+        baseline.process(earlier, tokio::io::sink()).await?;
+        self.process(later, tokio::io::sink()).await?;
+        self.write_delta_output(&baseline.into_accounts(), output)
+            .await
+    }
+
+    /// Writes one row per client whose state differs from the baseline, with deltas instead of absolute balances.
+    /// Clients only present in the baseline are written with negated balances, and new clients with their current
+    /// balances. Rows are sorted by client ID
+    #[cfg(feature = "async")]
+    pub async fn write_delta_output<W: AsyncWrite + Unpin>(
+        &self,
+        baseline: &HashMap<AccountKey, OutputRow>,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        let accounts = self
+            .clients
+            .keys()
+            .chain(baseline.keys())
+            .collect::<BTreeSet<_>>();
+
+        let empty = OutputRow::default();
+        let mut output = Self::serializer(&self.config, output);
+        let mut result = Ok(());
+        for account in accounts {
+            let before = baseline.get(account).unwrap_or(&empty);
+            let after = self.clients.get(account).unwrap_or(&empty);
+            if !balances_differ(before, after) {
+                continue;
+            }
+
+            let delta = DeltaRow {
+                client: account.0,
+                ledger: self.ledger_column(account),
+                currency: self.currency_column(account),
+                available_delta: after.available - before.available,
+                held_delta: after.held - before.held,
+                total_delta: after.total - before.total,
+                was_locked: before.locked,
+                locked: after.locked,
+            };
+
+            if let Err(err) = output.serialize(delta).await {
+                result = Err(err);
+                break;
+            }
+        }
+
+        Self::finalize(&self.config, output, result).await
+    }
+
+    /// Replays a log of transactions, which is an input as accepted by [`Self::process`], and returns the accounts
+    /// whose balances differ from `expected`, e.g. the output of the run that applied the log. An empty result means
+    /// the log faithfully records how the expected balances came about. Like [`Self::process`], starts from an empty
+    /// state
+    #[cfg(feature = "async")]
+    pub async fn verify_replay<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        log: R,
+        expected: &HashMap<AccountKey, OutputRow>,
+    ) -> Result<Vec<AccountKey>, TransactionError> {
+        self.process(log, tokio::io::sink()).await?;
+        Ok(self.diverging_accounts(expected))
+    }
+
+    /// Returns the accounts whose balances differ from `expected`, sorted by client ID and ledger
+    pub fn diverging_accounts(&self, expected: &HashMap<AccountKey, OutputRow>) -> Vec<AccountKey> {
+        let empty = OutputRow::default();
+        self.clients
+            .keys()
+            .chain(expected.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|account| {
+                balances_differ(
+                    expected.get(*account).unwrap_or(&empty),
+                    self.clients.get(*account).unwrap_or(&empty),
+                )
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// The ledger written for an account, which is only output in multi-ledger mode
+    #[cfg(feature = "async")]
+    fn ledger_column(&self, account: &AccountKey) -> Option<String> {
+        self.config.multi_ledger.then(|| account.1.clone())
+    }
+
+    /// The currency written for an account, which is only output in multi-currency mode
+    #[cfg(feature = "async")]
+    fn currency_column(&self, account: &AccountKey) -> Option<String> {
+        self.config.multi_currency.then(|| account.2.clone())
+    }
+
+    /// Writes how much each client's total changed relative to `starting_state`, with the largest absolute changes
+    /// first. Clients missing from either side are treated as having a zero balance there
+    #[cfg(feature = "async")]
+    pub async fn write_balance_change_report<W: AsyncWrite + Unpin>(
+        &self,
+        starting_state: &HashMap<AccountKey, OutputRow>,
+        mut output: W,
+        format: OutputFormat,
+    ) -> Result<(), TransactionError> {
+        let empty = OutputRow::default();
+        let mut rows = self
+            .clients
+            .keys()
+            .chain(starting_state.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|account| {
+                let before = starting_state.get(account).unwrap_or(&empty);
+                let after = self.clients.get(account).unwrap_or(&empty);
+                let change = after.total - before.total;
+                BalanceChangeRow {
+                    client: account.0,
+                    ledger: self.ledger_column(account),
+                    currency: self.currency_column(account),
+                    starting_total: before.total,
+                    ending_total: after.total,
+                    change,
+                    change_pct: change
+                        .checked_div(before.total)
+                        .and_then(|ratio| ratio.checked_mul(Decimal::ONE_HUNDRED))
+                        .map(|pct| pct.normalize()),
+                    starting_locked: before.locked,
+                    ending_locked: after.locked,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // stable, so equal changes stay ordered by client ID and ledger
+        rows.sort_by_key(|row| std::cmp::Reverse(row.change.abs()));
+
+        match format {
+            OutputFormat::Csv => {
+                let mut output = Self::serializer(&self.config, output);
+                let mut result = Ok(());
+                for row in rows {
+                    if let Err(err) = output.serialize(row).await {
+                        result = Err(err);
+                        break;
+                    }
+                }
+
+                Self::finalize(&self.config, output, result).await
+            }
+
+            OutputFormat::Json => {
+                let json = serde_json::to_vec(&rows).map_err(std::io::Error::from)?;
+                output.write_all(&json).await?;
+                Ok(output.flush().await?)
+            }
+        }
+    }
+}
+
+fn balances_differ(before: &OutputRow, after: &OutputRow) -> bool {
+    before.available != after.available
+        || before.held != after.held
+        || before.total != after.total
+        || before.locked != after.locked
+}
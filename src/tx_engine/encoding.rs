@@ -0,0 +1,139 @@
+use serde::Serialize;
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Bytes Windows-1252 leaves undefined
+const WINDOWS_1252_UNDEFINED: [u8; 5] = [0x81, 0x8D, 0x8F, 0x90, 0x9D];
+
+/// Character encoding of the input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum InputEncoding {
+    /// Passed through to the CSV parser as is
+    #[default]
+    Utf8,
+    /// ISO-8859-1. Every byte is valid
+    Latin1,
+    Windows1252,
+}
+
+impl fmt::Display for InputEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf8 => write!(f, "UTF-8"),
+            Self::Latin1 => write!(f, "ISO-8859-1"),
+            Self::Windows1252 => write!(f, "Windows-1252"),
+        }
+    }
+}
+
+/// A byte that is invalid in the input encoding. Surfaces as
+/// [`TransactionError::EncodingError`](crate::tx_engine::TransactionError::EncodingError)
+#[derive(Debug)]
+pub(crate) struct MalformedInput {
+    pub byte_offset: u64,
+    pub encoding: InputEncoding,
+}
+
+impl fmt::Display for MalformedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid {} byte at offset {}",
+            self.encoding, self.byte_offset
+        )
+    }
+}
+
+impl std::error::Error for MalformedInput {}
+
+/// Transcodes the underlying reader from the input encoding to UTF-8 before it reaches the CSV parser. UTF-8 input is
+/// passed straight through
+pub struct Transcoder<R> {
+    inner: R,
+    encoding: InputEncoding,
+    decoded: Vec<u8>,
+    decoded_pos: usize,
+    bytes_read: u64,
+}
+
+impl<R: AsyncRead + Unpin> Transcoder<R> {
+    pub fn new(inner: R, encoding: InputEncoding) -> Self {
+        Self {
+            inner,
+            encoding,
+            decoded: Vec::new(),
+            decoded_pos: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Replaces the decoded buffer with `chunk` converted to UTF-8. Both encodings map each byte to one character, so
+    /// chunks can be decoded independently
+    fn decode(&mut self, chunk: &[u8]) -> io::Result<()> {
+        if self.encoding == InputEncoding::Windows1252 {
+            if let Some(index) = chunk
+                .iter()
+                .position(|byte| WINDOWS_1252_UNDEFINED.contains(byte))
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    MalformedInput {
+                        byte_offset: self.bytes_read + index as u64,
+                        encoding: self.encoding,
+                    },
+                ));
+            }
+        }
+
+        // a byte decodes to at most 3 bytes of UTF-8
+        self.decoded.resize(chunk.len() * 3, 0);
+        let written = match self.encoding {
+            InputEncoding::Windows1252 => {
+                let (_, _, written, _) = encoding_rs::WINDOWS_1252
+                    .new_decoder_without_bom_handling()
+                    .decode_to_utf8(chunk, &mut self.decoded, true);
+                written
+            }
+            _ => encoding_rs::mem::convert_latin1_to_utf8(chunk, &mut self.decoded),
+        };
+        self.decoded.truncate(written);
+        self.decoded_pos = 0;
+        self.bytes_read += chunk.len() as u64;
+        Ok(())
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for Transcoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.encoding == InputEncoding::Utf8 {
+            return Pin::new(&mut this.inner).poll_read(cx, buf);
+        }
+
+        if this.decoded_pos == this.decoded.len() {
+            let mut chunk = [0; CHUNK_SIZE];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            let filled = chunk_buf.filled().len();
+            if filled == 0 {
+                return Poll::Ready(Ok(()));
+            }
+            this.decode(&chunk[..filled])?;
+        }
+
+        let remaining = &this.decoded[this.decoded_pos..];
+        let n = remaining.len().min(buf.remaining());
+        buf.put_slice(&remaining[..n]);
+        this.decoded_pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,442 @@
+use std::fmt::{Display, Formatter};
+
+/// Errors that abort processing of an input
+#[derive(Debug)]
+pub enum TransactionError {
+    Io(std::io::Error),
+    #[cfg(feature = "async")]
+    Csv(csv_async::Error),
+    /// The input names the same column more than once
+    DuplicateHeader {
+        name: String,
+    },
+    /// The `type` column of a row did not name a known transaction type
+    InvalidTransactionType(String),
+    /// A field of a row could not be parsed into its expected type
+    FieldParseError {
+        field: &'static str,
+        value: String,
+        expected_type: &'static str,
+    },
+    /// A row being merged by timestamp did not include one
+    MissingTimestamp {
+        tx: u32,
+    },
+    /// The input's header row does not match the expected schema
+    SchemaValidationFailed {
+        errors: Vec<crate::tx_engine::SchemaError>,
+    },
+    /// A deposit, withdrawal or transfer did not include an amount
+    MissingAmount {
+        tx: u32,
+    },
+    /// A transfer did not include a target client
+    MissingTransferTarget {
+        tx: u32,
+    },
+    /// A deposit, withdrawal or transfer had a negative amount
+    InvalidAmount {
+        tx: u32,
+        amount: rust_decimal::Decimal,
+    },
+    /// The funds held for a chargeback's dispute differ from the disputed amount, or are not all held by the
+    /// client. Indicates a bookkeeping bug
+    ChargebackAmountMismatch {
+        tx: u32,
+        expected: rust_decimal::Decimal,
+        held: rust_decimal::Decimal,
+    },
+    /// A deposit or withdrawal could not be recorded because the history holds `max_history_entries` entries and
+    /// none could be evicted
+    HistoryFull {
+        max: usize,
+    },
+    /// A counter was incremented past its maximum and `counter_overflow` is [`CounterOverflow::Error`]
+    ///
+    /// [`CounterOverflow::Error`]: crate::tx_engine::CounterOverflow::Error
+    CounterOverflow {
+        counter: &'static str,
+    },
+    /// The directory an output file was to be created in does not exist
+    MissingOutputDirectory(std::path::PathBuf),
+    /// The veto callback aborted processing at this transaction
+    Aborted {
+        tx: u32,
+    },
+    /// Processing was stopped through the configured shutdown signal before the input was exhausted
+    #[cfg(feature = "signal-handling")]
+    Interrupted,
+    /// A client appeared in more than one input that was expected to be disjoint
+    DuplicateClient(u16),
+    /// The client has no account
+    UnknownClient(u16),
+    /// A transfer's target client is processed by another shard of a
+    /// [`ShardedTransactionEngine`](crate::tx_engine::ShardedTransactionEngine)
+    CrossShardTransfer {
+        tx: u32,
+    },
+    /// The balances output failed to read back with `validate_output` enabled. Nothing was written
+    OutputValidationFailed {
+        errors: Vec<crate::tx_engine::OutputValidationError>,
+    },
+    /// The configuration failed [`TransactionEngineConfig::validate`](crate::tx_engine::TransactionEngineConfig::validate)
+    InvalidConfig {
+        errors: Vec<crate::tx_engine::ConfigError>,
+    },
+    /// A balance correction would have left the account with `available + held != total`
+    CorrectionViolatesInvariant {
+        client: u16,
+        available: rust_decimal::Decimal,
+        held: rust_decimal::Decimal,
+        total: rust_decimal::Decimal,
+    },
+    /// The client's total dropped below `total_floor` with [`TotalFloorPolicy::Abort`](crate::tx_engine::TotalFloorPolicy)
+    TotalBelowFloor {
+        client: u16,
+        tx: u32,
+        total: rust_decimal::Decimal,
+    },
+    /// The tx reserved for an interest application is already used by one of the client's deposits or withdrawals
+    InterestTxInUse {
+        client: u16,
+        tx: u32,
+    },
+    /// The rate passed to [`TransactionEngine::apply_interest`](crate::tx_engine::TransactionEngine::apply_interest) is
+    /// negative
+    InvalidInterestRate {
+        rate: rust_decimal::Decimal,
+    },
+    /// [`TransactionEngine::apply_interest`](crate::tx_engine::TransactionEngine::apply_interest) was called with
+    /// `multi_ledger` or `multi_currency`, where a client's credits would share one tx and could not all be recorded
+    InterestWithMultipleAccounts,
+    /// A concurrently processed input panicked or was cancelled
+    #[cfg(feature = "async")]
+    TaskFailed(tokio::task::JoinError),
+    /// A checkpoint could not be written, or could not be read back
+    Checkpoint(serde_json::Error),
+    /// Input passed to [`TransactionEngine::feed`](crate::tx_engine::TransactionEngine::feed) is not valid UTF-8, or
+    /// ended inside a multi-byte character
+    InvalidUtf8 {
+        byte_offset: u64,
+    },
+    /// Failed to open or read an object from cloud storage
+    #[cfg(feature = "s3")]
+    ObjectStore(object_store::Error),
+    /// The object URI is malformed
+    #[cfg(feature = "s3")]
+    ObjectPath(object_store::path::Error),
+    /// The URI scheme is not supported by the enabled features
+    #[cfg(feature = "s3")]
+    UnsupportedUri(String),
+    /// A transient read error persisted after every configured retry
+    #[cfg(feature = "io-retry")]
+    IoRetryExhausted {
+        attempts: u32,
+        last_error: std::io::Error,
+    },
+    /// The input has a byte that is invalid in the configured encoding
+    #[cfg(feature = "encoding")]
+    EncodingError {
+        byte_offset: u64,
+        encoding: crate::tx_engine::InputEncoding,
+    },
+    /// Failed to receive or commit a Kafka message
+    #[cfg(feature = "kafka")]
+    Kafka(rdkafka::error::KafkaError),
+    /// Failed to build or write the Arrow output
+    #[cfg(feature = "arrow")]
+    Arrow(arrow::error::ArrowError),
+    /// Failed to read or write CSV through the synchronous API
+    #[cfg(feature = "sync")]
+    SyncCsv(csv::Error),
+}
+
+impl Display for TransactionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            #[cfg(feature = "async")]
+            Self::Csv(err) => write!(f, "CSV error: {}", err),
+            Self::DuplicateHeader { name } => write!(f, "Duplicate header in input: {}", name),
+            Self::InvalidTransactionType(ty) => write!(f, "Invalid transaction type: {}", ty),
+            Self::FieldParseError {
+                field,
+                value,
+                expected_type,
+            } => write!(
+                f,
+                "Invalid value for field {}: {:?} is not a valid {}",
+                field, value, expected_type
+            ),
+            Self::MissingTimestamp { tx } => {
+                write!(f, "Transaction {} is missing a timestamp", tx)
+            }
+            Self::SchemaValidationFailed { errors } => {
+                write!(f, "Invalid input schema: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            Self::MissingAmount { tx } => write!(f, "Transaction {} is missing an amount", tx),
+            Self::MissingTransferTarget { tx } => {
+                write!(f, "Transfer {} is missing a target client", tx)
+            }
+            Self::InvalidAmount { tx, amount } => {
+                write!(f, "Transaction {} has an invalid amount: {}", tx, amount)
+            }
+            Self::ChargebackAmountMismatch { tx, expected, held } => write!(
+                f,
+                "Chargeback of transaction {} expected {} to be held but only {} is",
+                tx, expected, held
+            ),
+            Self::HistoryFull { max } => {
+                write!(f, "Transaction history is full ({} entries)", max)
+            }
+            Self::CounterOverflow { counter } => write!(f, "Counter {} overflowed", counter),
+            Self::MissingOutputDirectory(dir) => {
+                write!(f, "Output directory does not exist: {}", dir.display())
+            }
+            Self::Aborted { tx } => write!(f, "Processing aborted at transaction {}", tx),
+            #[cfg(feature = "signal-handling")]
+            Self::Interrupted => {
+                write!(f, "Processing was interrupted before the end of the input")
+            }
+            Self::DuplicateClient(client) => {
+                write!(f, "Client {} appears in more than one input", client)
+            }
+            Self::UnknownClient(client) => write!(f, "Client {} has no account", client),
+            Self::CrossShardTransfer { tx } => {
+                write!(f, "Transfer {} targets a client of another shard", tx)
+            }
+            Self::OutputValidationFailed { errors } => {
+                write!(f, "Invalid output: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            Self::InvalidConfig { errors } => {
+                write!(f, "Invalid configuration: ")?;
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+                Ok(())
+            }
+            Self::CorrectionViolatesInvariant {
+                client,
+                available,
+                held,
+                total,
+            } => write!(
+                f,
+                "Correction of client {} would leave available {} + held {} != total {}",
+                client, available, held, total
+            ),
+            Self::TotalBelowFloor { client, tx, total } => write!(
+                f,
+                "Transaction {} left client {} with total {} below the floor",
+                tx, client, total
+            ),
+            Self::InterestTxInUse { client, tx } => write!(
+                f,
+                "Transaction {} of client {} is reserved for interest",
+                tx, client
+            ),
+            Self::InvalidInterestRate { rate } => {
+                write!(f, "Interest rate {} is negative", rate)
+            }
+            Self::InterestWithMultipleAccounts => write!(
+                f,
+                "Interest cannot be applied with multiple accounts per client"
+            ),
+            #[cfg(feature = "async")]
+            Self::TaskFailed(err) => write!(f, "Processing task failed: {}", err),
+            Self::Checkpoint(err) => write!(f, "Checkpoint error: {}", err),
+            Self::InvalidUtf8 { byte_offset } => {
+                write!(f, "Invalid UTF-8 input at byte offset {}", byte_offset)
+            }
+            #[cfg(feature = "s3")]
+            Self::ObjectStore(err) => write!(f, "Object store error: {}", err),
+            #[cfg(feature = "s3")]
+            Self::ObjectPath(err) => write!(f, "Invalid object path: {}", err),
+            #[cfg(feature = "s3")]
+            Self::UnsupportedUri(uri) => write!(f, "Unsupported URI: {}", uri),
+            #[cfg(feature = "io-retry")]
+            Self::IoRetryExhausted {
+                attempts,
+                last_error,
+            } => write!(f, "I/O error after {} attempts: {}", attempts, last_error),
+            #[cfg(feature = "encoding")]
+            Self::EncodingError {
+                byte_offset,
+                encoding,
+            } => write!(
+                f,
+                "Invalid {} input at byte offset {}",
+                encoding, byte_offset
+            ),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(err) => write!(f, "Kafka error: {}", err),
+            #[cfg(feature = "arrow")]
+            Self::Arrow(err) => write!(f, "Arrow error: {}", err),
+            #[cfg(feature = "sync")]
+            Self::SyncCsv(err) => write!(f, "CSV error: {}", err),
+        }
+    }
+}
+
+impl TransactionError {
+    /// Whether the error is confined to a single input row that failed to parse or validate, as opposed to the input
+    /// or the engine as a whole
+    pub fn is_row_error(&self) -> bool {
+        match self {
+            #[cfg(feature = "async")]
+            Self::Csv(_) => true,
+            Self::InvalidTransactionType(_)
+            | Self::FieldParseError { .. }
+            | Self::MissingTimestamp { .. }
+            | Self::MissingAmount { .. }
+            | Self::MissingTransferTarget { .. }
+            | Self::InvalidAmount { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            #[cfg(feature = "async")]
+            Self::Csv(err) => Some(err),
+            #[cfg(feature = "async")]
+            Self::TaskFailed(err) => Some(err),
+            Self::Checkpoint(err) => Some(err),
+            #[cfg(feature = "s3")]
+            Self::ObjectStore(err) => Some(err),
+            #[cfg(feature = "s3")]
+            Self::ObjectPath(err) => Some(err),
+            #[cfg(feature = "io-retry")]
+            Self::IoRetryExhausted { last_error, .. } => Some(last_error),
+            #[cfg(feature = "kafka")]
+            Self::Kafka(err) => Some(err),
+            #[cfg(feature = "arrow")]
+            Self::Arrow(err) => Some(err),
+            #[cfg(feature = "sync")]
+            Self::SyncCsv(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for TransactionError {
+    fn from(err: std::io::Error) -> Self {
+        // the retrying reader can only surface an io::Error, so unwrap its payload here
+        #[cfg(feature = "io-retry")]
+        if err
+            .get_ref()
+            .is_some_and(|inner| inner.is::<crate::tx_engine::retry::RetriesExhausted>())
+        {
+            let exhausted = err
+                .into_inner()
+                .unwrap()
+                .downcast::<crate::tx_engine::retry::RetriesExhausted>()
+                .unwrap();
+            return Self::IoRetryExhausted {
+                attempts: exhausted.attempts,
+                last_error: exhausted.last_error,
+            };
+        }
+
+        #[cfg(feature = "encoding")]
+        if err
+            .get_ref()
+            .is_some_and(|inner| inner.is::<crate::tx_engine::encoding::MalformedInput>())
+        {
+            let malformed = err
+                .into_inner()
+                .unwrap()
+                .downcast::<crate::tx_engine::encoding::MalformedInput>()
+                .unwrap();
+            return Self::EncodingError {
+                byte_offset: malformed.byte_offset,
+                encoding: malformed.encoding,
+            };
+        }
+
+        Self::Io(err)
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<csv_async::Error> for TransactionError {
+    fn from(err: csv_async::Error) -> Self {
+        if !err.is_io_error() {
+            return Self::Csv(err);
+        }
+
+        match err.into_kind() {
+            csv_async::ErrorKind::Io(err) => err.into(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<tokio::task::JoinError> for TransactionError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Self::TaskFailed(err)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl From<object_store::Error> for TransactionError {
+    fn from(err: object_store::Error) -> Self {
+        Self::ObjectStore(err)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl From<object_store::path::Error> for TransactionError {
+    fn from(err: object_store::path::Error) -> Self {
+        Self::ObjectPath(err)
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl From<rdkafka::error::KafkaError> for TransactionError {
+    fn from(err: rdkafka::error::KafkaError) -> Self {
+        Self::Kafka(err)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow::error::ArrowError> for TransactionError {
+    fn from(err: arrow::error::ArrowError) -> Self {
+        Self::Arrow(err)
+    }
+}
+
+#[cfg(feature = "sync")]
+impl From<csv::Error> for TransactionError {
+    fn from(err: csv::Error) -> Self {
+        if !err.is_io_error() {
+            return Self::SyncCsv(err);
+        }
+
+        match err.into_kind() {
+            csv::ErrorKind::Io(err) => err.into(),
+            _ => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use crate::tx_engine::{
+    AccountKey, Decimal, RejectionReason, TransactionEngine, TransactionError, TransactionSummary,
+    TransactionType,
+};
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+
+/// Emitted for each processed transaction by [`TransactionEngine::process_with_events`], after the state was updated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum DomainEvent {
+    DepositApplied {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_total: Decimal,
+    },
+    WithdrawalApplied {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_total: Decimal,
+    },
+    DisputeOpened {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_held: Decimal,
+    },
+    /// Also emitted when a dispute expires
+    DisputeResolved {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_held: Decimal,
+    },
+    AccountChargebacked {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+    },
+    DepositSettled {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_total: Decimal,
+    },
+    /// Emitted for the account the transfer is taken from
+    TransferApplied {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_total: Decimal,
+    },
+    /// Emitted for the account the transfer is credited to, right after [`DomainEvent::TransferApplied`]
+    TransferReceived {
+        client: u16,
+        tx: u32,
+        amount: Decimal,
+        new_available: Decimal,
+        new_total: Decimal,
+    },
+    TransactionRejected {
+        client: u16,
+        tx: u32,
+        reason: RejectionReason,
+    },
+}
+
+impl TransactionEngine {
+    /// Like [`Self::process`], but also sends a [`DomainEvent`] to `event_tx` for every applied or rejected
+    /// transaction. Sending never blocks: events that do not fit in the channel are dropped and counted in
+    /// [`TransactionSummary::events_dropped`]
+    pub async fn process_with_events<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        output: W,
+        event_tx: mpsc::Sender<DomainEvent>,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.event_tx = Some(event_tx);
+        let result = self.process(input, output).await;
+        // dropping the sender lets the receiver finish once it has drained the channel
+        self.event_tx = None;
+        result.map(|_| self.summary())
+    }
+
+    /// Sends the event for a transaction that moved `amount`, if events are enabled
+    pub(crate) fn emit_applied(
+        &mut self,
+        tx_type: TransactionType,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) {
+        if self.event_tx.is_none() {
+            return;
+        }
+
+        let client = account.0;
+        let row = &self.clients[account];
+        let event = match tx_type {
+            TransactionType::Deposit => DomainEvent::DepositApplied {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_total: row.total,
+            },
+
+            TransactionType::Withdrawal => DomainEvent::WithdrawalApplied {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_total: row.total,
+            },
+
+            TransactionType::Dispute => DomainEvent::DisputeOpened {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_held: row.held,
+            },
+
+            TransactionType::Resolve => DomainEvent::DisputeResolved {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_held: row.held,
+            },
+
+            TransactionType::Chargeback => DomainEvent::AccountChargebacked { client, tx, amount },
+
+            TransactionType::Settle => DomainEvent::DepositSettled {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_total: row.total,
+            },
+
+            TransactionType::Transfer => DomainEvent::TransferApplied {
+                client,
+                tx,
+                amount,
+                new_available: row.available,
+                new_total: row.total,
+            },
+        };
+
+        self.emit(event);
+    }
+
+    /// Sends the event for a transfer that was credited to the account, if events are enabled
+    pub(crate) fn emit_transfer_received(
+        &mut self,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) {
+        if self.event_tx.is_none() {
+            return;
+        }
+
+        let row = &self.clients[account];
+        let event = DomainEvent::TransferReceived {
+            client: account.0,
+            tx,
+            amount,
+            new_available: row.available,
+            new_total: row.total,
+        };
+        self.emit(event);
+    }
+
+    pub(crate) fn emit(&mut self, event: DomainEvent) {
+        if let Some(event_tx) = self.event_tx.as_ref() {
+            // a closed receiver drops events just like a full channel
+            if event_tx.try_send(event).is_err() {
+                self.events_dropped += 1;
+            }
+        }
+    }
+}
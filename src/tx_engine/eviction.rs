@@ -0,0 +1,106 @@
+use crate::tx_engine::{
+    HistoryEvictionPolicy, HistoryKey, TransactionEngine, TransactionError, TransactionType,
+};
+
+impl TransactionEngine {
+    /// Makes room for a new deposit or withdrawal when `max_history_entries` is reached, either by evicting the least
+    /// recently referenced undisputed transaction or by failing with [`TransactionError::HistoryFull`]
+    pub(crate) fn reserve_history_entry(&mut self) -> Result<(), TransactionError> {
+        self.fit_history(0)
+    }
+
+    /// Like [`Self::reserve_history_entry`], but for `inserted` deposits and withdrawals that are already in the
+    /// history. They are not referenced yet, so they are never the ones evicted
+    pub(crate) fn fit_history(&mut self, inserted: usize) -> Result<(), TransactionError> {
+        let max = match self.config.max_history_entries {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+
+        while self.history.len() >= max + inserted {
+            if self.config.history_eviction_policy == HistoryEvictionPolicy::Reject
+                || !self.evict_lru_history_entry()
+            {
+                return Err(TransactionError::HistoryFull { max });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Makes room for `count` new deposits and withdrawals at once. Fails with [`TransactionError::HistoryFull`]
+    /// without evicting anything if they would not all fit
+    pub(crate) fn reserve_history_entries(&mut self, count: usize) -> Result<(), TransactionError> {
+        let max = match self.config.max_history_entries {
+            Some(max) if count > 0 => max,
+            _ => return Ok(()),
+        };
+
+        let evictable = if self.config.history_eviction_policy == HistoryEvictionPolicy::EvictLru {
+            self.history_lru
+                .iter()
+                .filter(|&key| self.history.contains_key(key))
+                .count()
+        } else {
+            0
+        };
+        if self.history.len() - evictable + count > max {
+            return Err(TransactionError::HistoryFull { max });
+        }
+
+        while self.history.len() + count > max && self.evict_lru_history_entry() {}
+
+        Ok(())
+    }
+
+    /// Marks the deposit or withdrawal with the given tx as the most recently referenced, if it is in the history. A
+    /// disputed one is taken out of the eviction order instead, until a reference after its dispute concluded
+    pub(crate) fn touch_history_entry(&mut self, client: u16, tx: u32) {
+        if self.config.max_history_entries.is_none()
+            || self.config.history_eviction_policy != HistoryEvictionPolicy::EvictLru
+        {
+            return;
+        }
+
+        let key = [TransactionType::Deposit, TransactionType::Withdrawal]
+            .iter()
+            .map(|&tx_type| history_key(client, tx, tx_type))
+            .find(|key| self.history.contains_key(key));
+        let key = match key {
+            Some(key) => key,
+            None => return,
+        };
+
+        if self
+            .history
+            .contains_key(&history_key(client, tx, TransactionType::Dispute))
+        {
+            self.history_lru.remove(&key);
+        } else {
+            // moves an entry that is already queued to the back
+            self.history_lru.insert(key);
+        }
+    }
+
+    /// Removes the least recently referenced deposit or withdrawal that is not under dispute. Returns false if there
+    /// was none
+    fn evict_lru_history_entry(&mut self) -> bool {
+        while let Some(key) = self.history_lru.pop_front() {
+            // may already have been compacted away
+            if self.history.remove(&key).is_some() {
+                self.tx_accounts.remove(&(key.client, key.tx));
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+fn history_key(client: u16, tx: u32, tx_type: TransactionType) -> HistoryKey {
+    HistoryKey {
+        client,
+        tx,
+        tx_type,
+    }
+}
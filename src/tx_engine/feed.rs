@@ -0,0 +1,178 @@
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError};
+use std::convert::TryFrom;
+use tokio_stream::StreamExt;
+
+impl TransactionEngine {
+    /// Buffers a chunk of CSV input, which may end anywhere in a row, and applies every row completed by it. The first
+    /// row fed is the header row. Call [`Self::finish`] after the last chunk to apply a final row without a line ending.
+    ///
+    /// A chunk may also end inside a multi-byte UTF-8 character, whose bytes are held back until the next chunk
+    /// completes it. Bytes that can never form valid UTF-8 fail with [`TransactionError::InvalidUtf8`]
+    pub async fn feed(&mut self, chunk: &[u8]) -> Result<(), TransactionError> {
+        self.feed_buffer.extend_from_slice(chunk);
+        self.validate_fed()?;
+
+        // the unvalidated tail is part of a character, so it never holds a line ending
+        let end = match last_record_end(&self.feed_buffer[..self.feed_validated]) {
+            Some(end) => end,
+            None => return Ok(()),
+        };
+
+        let records = self.feed_buffer.drain(..end).collect::<Vec<_>>();
+        self.feed_validated -= end;
+        self.feed_offset += end as u64;
+        self.apply_fed(records, false).await
+    }
+
+    /// Applies the row left in the buffer by [`Self::feed`], if any, and resets the buffered input so that another
+    /// input, with its own header row, can be fed. Fails with [`TransactionError::InvalidUtf8`] if the input ended
+    /// inside a multi-byte character
+    pub async fn finish(&mut self) -> Result<(), TransactionError> {
+        let dangling = self.feed_validated < self.feed_buffer.len();
+        let byte_offset = self.feed_offset + self.feed_validated as u64;
+        let records = std::mem::take(&mut self.feed_buffer);
+        let result = if dangling {
+            Err(TransactionError::InvalidUtf8 { byte_offset })
+        } else {
+            self.apply_fed(records, true).await
+        };
+        self.feed_validated = 0;
+        self.feed_offset = 0;
+        self.feed_headers = None;
+        self.feed_rows_read = 0;
+        result
+    }
+
+    /// Extends the validated part of the buffer over the bytes fed since, stopping before an incomplete character at
+    /// its end
+    fn validate_fed(&mut self) -> Result<(), TransactionError> {
+        match std::str::from_utf8(&self.feed_buffer[self.feed_validated..]) {
+            Ok(_) => self.feed_validated = self.feed_buffer.len(),
+            Err(err) => {
+                let valid_end = self.feed_validated + err.valid_up_to();
+                if err.error_len().is_some() {
+                    return Err(TransactionError::InvalidUtf8 {
+                        byte_offset: self.feed_offset + valid_end as u64,
+                    });
+                }
+                self.feed_validated = valid_end;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies complete records, prefixed with the header row so that they can be parsed on their own
+    async fn apply_fed(
+        &mut self,
+        mut records: Vec<u8>,
+        last: bool,
+    ) -> Result<(), TransactionError> {
+        let headers = match self.feed_headers.as_ref() {
+            Some(headers) => headers,
+            None => match self.take_fed_headers(&mut records) {
+                Some(headers) => self.feed_headers.insert(headers),
+                // only blank or comment lines so far
+                None => return Ok(()),
+            },
+        };
+
+        if records.is_empty() {
+            return Ok(());
+        }
+        records.splice(..0, headers.iter().copied());
+
+        let config = self.config.clone();
+        let mut rows =
+            Self::deserialize_rows(&config, records.as_slice(), config.strict_mode).await?;
+        while let Some(result) = rows.next().await {
+            if Self::row_limit_reached(&config, self.feed_rows_read) {
+                return Ok(());
+            }
+            self.feed_rows_read += 1;
+
+            match result
+                .map_err(TransactionError::from)
+                .and_then(InputRow::try_from)
+            {
+                Ok(row) => {
+                    self.process_input_row(row)?;
+                }
+
+                // only a row followed by more rows is a genuine error
+                Err(err) if last && config.ignore_partial_final_row => {
+                    return match rows.next().await {
+                        Some(_) => Err(err),
+                        None => Ok(()),
+                    };
+                }
+
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the records up to and including the header row from `records`, returning the header row. Blank lines
+    /// and, with `skip_comment_lines`, comment lines before it are dropped
+    fn take_fed_headers(&self, records: &mut Vec<u8>) -> Option<Vec<u8>> {
+        let mut start = 0;
+        while let Some(len) = first_record_end(&records[start..]) {
+            let record = &records[start..start + len];
+            start += len;
+
+            match record.iter().find(|byte| !byte.is_ascii_whitespace()) {
+                None => continue,
+                Some(b'#') if self.config.skip_comment_lines => continue,
+                Some(_) => {
+                    let mut headers = record.to_vec();
+                    // the records that follow must start on a new line
+                    if !headers.ends_with(b"\n") {
+                        headers.push(b'\n');
+                    }
+                    records.drain(..start);
+                    return Some(headers);
+                }
+            }
+        }
+
+        records.clear();
+        None
+    }
+}
+
+/// Returns the length of the first record, including its line ending. A record ends at a newline outside of quotes.
+/// The rest of the buffer counts as a record if it has no line ending
+fn first_record_end(buffer: &[u8]) -> Option<usize> {
+    if buffer.is_empty() {
+        return None;
+    }
+
+    let mut quoted = false;
+    for (index, &byte) in buffer.iter().enumerate() {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'\n' if !quoted => return Some(index + 1),
+            _ => {}
+        }
+    }
+
+    Some(buffer.len())
+}
+
+/// Returns the end of the last complete record, i.e. the position after its newline. Scanning bytes never splits a
+/// UTF-8 character since `\n` and `"` do not occur inside multi-byte sequences
+fn last_record_end(buffer: &[u8]) -> Option<usize> {
+    let mut quoted = false;
+    let mut end = None;
+    for (index, &byte) in buffer.iter().enumerate() {
+        match byte {
+            b'"' => quoted = !quoted,
+            b'\n' if !quoted => end = Some(index + 1),
+            _ => {}
+        }
+    }
+
+    end
+}
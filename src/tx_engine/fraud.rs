@@ -0,0 +1,102 @@
+use crate::tx_engine::{Decimal, InputRow, OutputRow};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Fraud risk of a deposit, from 0 (safe) to 100 (high risk)
+pub type FraudScore = u8;
+
+/// Highest possible [`FraudScore`]. Aggregate scores saturate at this value
+pub const MAX_FRAUD_SCORE: FraudScore = 100;
+
+/// Scores a deposit before it is applied. `client` is the client's state before the deposit
+pub trait FraudRule {
+    fn score(&self, row: &InputRow, client: &OutputRow) -> FraudScore;
+}
+
+/// Flags suspiciously round amounts such as 1000.00 or 5000.00
+pub struct RoundAmountRule {
+    /// Amounts that are a non-zero multiple of this are flagged
+    pub multiple: Decimal,
+    pub score: FraudScore,
+}
+
+impl Default for RoundAmountRule {
+    fn default() -> Self {
+        Self {
+            multiple: Decimal::from(1000),
+            score: 30,
+        }
+    }
+}
+
+impl FraudRule for RoundAmountRule {
+    fn score(&self, row: &InputRow, _client: &OutputRow) -> FraudScore {
+        match row.amount() {
+            Some(amount)
+                if !amount.is_zero()
+                    && !self.multiple.is_zero()
+                    && (amount % self.multiple).is_zero() =>
+            {
+                self.score
+            }
+            _ => 0,
+        }
+    }
+}
+
+/// Flags a client making more than `max_deposits` of the last `window` scored deposits. The window counts deposits
+/// across all clients, since rows carry no timestamp
+pub struct VelocityRule {
+    pub max_deposits: usize,
+    pub window: usize,
+    pub score: FraudScore,
+    recent: Mutex<VecDeque<u16>>,
+}
+
+impl VelocityRule {
+    pub fn new(max_deposits: usize, window: usize, score: FraudScore) -> Self {
+        Self {
+            max_deposits,
+            window,
+            score,
+            recent: Mutex::new(VecDeque::with_capacity(window)),
+        }
+    }
+}
+
+impl FraudRule for VelocityRule {
+    fn score(&self, row: &InputRow, _client: &OutputRow) -> FraudScore {
+        // a panic in another rule holding the lock does not invalidate the window
+        let mut recent = self.recent.lock().unwrap_or_else(|err| err.into_inner());
+        recent.push_back(row.client());
+        while recent.len() > self.window {
+            recent.pop_front();
+        }
+
+        let deposits = recent
+            .iter()
+            .filter(|client| **client == row.client())
+            .count();
+        if deposits > self.max_deposits {
+            self.score
+        } else {
+            0
+        }
+    }
+}
+
+/// Flags a deposit of at least `threshold` into an account that holds no funds yet
+pub struct NewAccountLargeDepositRule {
+    pub threshold: Decimal,
+    pub score: FraudScore,
+}
+
+impl FraudRule for NewAccountLargeDepositRule {
+    fn score(&self, row: &InputRow, client: &OutputRow) -> FraudScore {
+        let is_new = client.total().is_zero() && !client.locked();
+        match row.amount() {
+            Some(amount) if is_new && amount >= self.threshold => self.score,
+            _ => 0,
+        }
+    }
+}
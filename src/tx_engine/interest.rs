@@ -0,0 +1,100 @@
+use crate::tx_engine::{
+    AccountKey, AuditEntry, AuditFlag, Decimal, HistoryKey, Transaction, TransactionEngine,
+    TransactionError, TransactionSummary, TransactionType, AMOUNT_DECIMAL_PLACES,
+};
+
+impl TransactionEngine {
+    /// Credits every account with positive available funds with `available * rate`, e.g. `0.01` for 1%, rounded with
+    /// `amount_rounding`. Locked accounts are skipped unless `interest_on_locked` is set. Each credit is recorded in the
+    /// audit log with `period_label`, e.g. `"2024-Q1"`, and in the history as a deposit, so it can be disputed.
+    ///
+    /// The Nth call uses tx `u32::MAX - N` (counting from 0) for every client, so input transactions should not use
+    /// the top of the tx range. Fails without crediting anything if `rate` is negative, if a client already has a
+    /// deposit or withdrawal with that tx, or if the credits do not all fit in `max_history_entries`. Not supported
+    /// with `multi_ledger` or `multi_currency`, since a client's accounts would all be credited under the same tx
+    pub fn apply_interest(
+        &mut self,
+        rate: Decimal,
+        period_label: &str,
+    ) -> Result<TransactionSummary, TransactionError> {
+        if rate < Decimal::ZERO {
+            return Err(TransactionError::InvalidInterestRate { rate });
+        }
+        if self.config.multi_ledger || self.config.multi_currency {
+            return Err(TransactionError::InterestWithMultipleAccounts);
+        }
+        let tx = u32::MAX - self.interest_applications;
+        let mut credits = self
+            .clients
+            .iter()
+            .filter(|(_, row)| self.config.interest_on_locked || !row.locked)
+            .filter(|(_, row)| row.available > Decimal::ZERO)
+            .map(|(account, row)| {
+                let interest = (row.available * rate).round_dp_with_strategy(
+                    AMOUNT_DECIMAL_PLACES,
+                    self.config.amount_rounding.strategy(),
+                );
+                (account.clone(), interest)
+            })
+            .filter(|(_, interest)| !interest.is_zero())
+            .collect::<Vec<(AccountKey, Decimal)>>();
+        credits.sort_unstable();
+
+        let tx_in_use = |client: u16| {
+            [TransactionType::Deposit, TransactionType::Withdrawal]
+                .iter()
+                .any(|&tx_type| {
+                    self.history.contains_key(&HistoryKey {
+                        client,
+                        tx,
+                        tx_type,
+                    })
+                })
+        };
+        if let Some(&((client, ..), _)) =
+            credits.iter().find(|((client, ..), _)| tx_in_use(*client))
+        {
+            return Err(TransactionError::InterestTxInUse { client, tx });
+        }
+
+        // every credit must fit before any is applied, so that a full history leaves the accounts and tx untouched
+        self.reserve_history_entries(credits.len())?;
+
+        self.interest_applications += 1;
+        for (account, interest) in credits {
+            let client = account.0;
+
+            let row = self.clients.get_mut(&account).unwrap();
+            row.available += interest;
+            row.total += interest;
+
+            self.history.insert(
+                HistoryKey {
+                    client,
+                    tx,
+                    tx_type: TransactionType::Deposit,
+                },
+                Transaction::Deposit {
+                    client,
+                    tx,
+                    amount: interest,
+                    pending: false,
+                },
+            );
+            self.touch_history_entry(client, tx);
+
+            self.audit_log.push(AuditEntry {
+                client,
+                tx,
+                flag: AuditFlag::InterestApplied {
+                    period: period_label.to_string(),
+                    rate,
+                    amount: interest,
+                },
+            });
+            self.on_applied(TransactionType::Deposit, &account, tx, interest)?;
+        }
+
+        Ok(self.summary())
+    }
+}
@@ -0,0 +1,65 @@
+use crate::tx_engine::{AccountKey, Decimal, TransactionEngine};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// An inconsistency in the engine's state found by [`TransactionEngine::verify_invariants`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum InvariantViolation {
+    /// The account's available and held funds do not add up to its total
+    Unbalanced {
+        account: AccountKey,
+        available: Decimal,
+        held: Decimal,
+        total: Decimal,
+    },
+    /// The account's held funds differ from the sum of its open disputes
+    HeldMismatch {
+        account: AccountKey,
+        held: Decimal,
+        disputed: Decimal,
+    },
+}
+
+impl TransactionEngine {
+    /// Checks that every account satisfies `available + held == total` and holds exactly the amounts of its open
+    /// disputes. Transactions never break either invariant, but corrections adjusting the held funds break the latter
+    /// by design. Violations are sorted by account
+    pub fn verify_invariants(&self) -> Result<(), Vec<InvariantViolation>> {
+        let mut disputed = HashMap::<AccountKey, Decimal>::new();
+        for dispute in self.open_disputes() {
+            *disputed
+                .entry(self.account_of_tx(dispute.client, dispute.tx))
+                .or_default() += dispute.amount;
+        }
+
+        let mut accounts = self.clients.iter().collect::<Vec<_>>();
+        accounts.sort_unstable_by_key(|(account, _)| *account);
+
+        let mut violations = Vec::new();
+        for (account, row) in accounts {
+            if row.available + row.held != row.total {
+                violations.push(InvariantViolation::Unbalanced {
+                    account: account.clone(),
+                    available: row.available,
+                    held: row.held,
+                    total: row.total,
+                });
+            }
+
+            let disputed = disputed.get(account).copied().unwrap_or_default();
+            if row.held != disputed {
+                violations.push(InvariantViolation::HeldMismatch {
+                    account: account.clone(),
+                    held: row.held,
+                    disputed,
+                });
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::tx_engine::parse::RawInputRow;
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError};
+use csv_async::StringRecord;
+use rdkafka::consumer::{CommitMode, Consumer, StreamConsumer};
+use rdkafka::Message;
+use std::convert::TryFrom;
+use std::future::Future;
+use tokio::io::AsyncWrite;
+
+/// Columns of a CSV message, which holds a single row without a header
+const CSV_COLUMNS: [&str; 4] = ["type", "client", "tx", "amount"];
+
+/// Encoding of the transaction in each Kafka message
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KafkaPayload {
+    /// A single `type,client,tx,amount` row without a header
+    #[default]
+    Csv,
+    /// An object with the input columns as keys, e.g. `{"type":"deposit","client":1,"tx":1,"amount":"1.5"}`
+    Json,
+}
+
+impl TransactionEngine {
+    /// Applies the transaction in each message received by `consumer`, which must already be subscribed, until
+    /// `shutdown` completes. The offset of each message is committed once it was applied, so the consumer should be
+    /// created with `enable.auto.commit` set to `false`. On shutdown, the balances so far are written to the output.
+    /// Messages without a payload are skipped. Starts from an empty state like [`Self::process`]
+    pub async fn process_kafka<W: AsyncWrite + Unpin, F: Future<Output = ()>>(
+        &mut self,
+        consumer: &StreamConsumer,
+        payload: KafkaPayload,
+        shutdown: F,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        tokio::pin!(shutdown);
+        loop {
+            let message = tokio::select! {
+                message = consumer.recv() => message?,
+                _ = &mut shutdown => break,
+            };
+
+            if let Some(bytes) = message.payload() {
+                let row = parse_message(bytes, payload).await?;
+                self.process_input_row(row)?;
+            }
+            consumer.commit_message(&message, CommitMode::Async)?;
+        }
+
+        self.write_output(output).await
+    }
+}
+
+async fn parse_message(bytes: &[u8], payload: KafkaPayload) -> Result<InputRow, TransactionError> {
+    let (headers, record) = match payload {
+        KafkaPayload::Csv => {
+            let mut reader = csv_async::AsyncReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .trim(csv_async::Trim::All)
+                .create_reader(bytes);
+            let mut record = StringRecord::new();
+            reader.read_record(&mut record).await?;
+            let headers = CSV_COLUMNS.iter().take(record.len()).copied().collect();
+            (headers, record)
+        }
+
+        KafkaPayload::Json => {
+            let fields =
+                serde_json::from_slice::<serde_json::Map<String, serde_json::Value>>(bytes)
+                    .map_err(std::io::Error::from)?;
+            let mut headers = StringRecord::new();
+            let mut record = StringRecord::new();
+            for (name, value) in fields {
+                headers.push_field(&name);
+                match value {
+                    serde_json::Value::Null => record.push_field(""),
+                    serde_json::Value::String(value) => record.push_field(&value),
+                    value => record.push_field(&value.to_string()),
+                }
+            }
+            (headers, record)
+        }
+    };
+
+    let row = record.deserialize::<RawInputRow>(Some(&headers))?;
+    InputRow::try_from(row)
+}
@@ -0,0 +1,115 @@
+use crate::tx_engine::{AccountKey, Decimal, TransactionEngine, TransactionError, TransactionType};
+use serde::Serialize;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+
+/// An applied transaction with the running balances of its client's account right after it, written by
+/// [`TransactionEngine::process_ledger`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerRow {
+    pub client: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub tx: u32,
+    #[serde(rename = "type")]
+    pub tx_type: &'static str,
+    pub amount: Decimal,
+    pub available: Decimal,
+    pub held: Decimal,
+    pub total: Decimal,
+    pub locked: bool,
+}
+
+impl TransactionEngine {
+    /// Processes the input source, writing a [`LedgerRow`] for every applied transaction instead of the final
+    /// balances. Rejected and skipped transactions have no row. Rows are streamed, so memory stays bounded by
+    /// `max_in_flight_rows` like [`Self::process_streaming`]. Rows written before an error are always flushed. Starts
+    /// from an empty state like [`Self::process`]
+    pub async fn process_ledger<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        let (rows_tx, mut rows_rx) =
+            mpsc::channel::<LedgerRow>(self.config.max_in_flight_rows.max(1));
+
+        let config = self.config.clone();
+        self.ledger_rows = Some(Vec::new());
+        let reader = self.send_ledger_rows(input, rows_tx);
+        let writer = async move {
+            let mut output = Self::serializer(&config, output);
+            let mut result = Ok(());
+            while let Some(row) = rows_rx.recv().await {
+                if let Err(err) = output.serialize(row).await {
+                    result = Err(err);
+                    break;
+                }
+            }
+
+            Self::finalize(&config, output, result).await
+        };
+
+        // not try_join: if the reader fails, the writer must still drain and flush the rows already emitted
+        let (read, written) = tokio::join!(reader, writer);
+        self.ledger_rows = None;
+        read.and(written)
+    }
+
+    async fn send_ledger_rows<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        input: R,
+        rows_tx: mpsc::Sender<LedgerRow>,
+    ) -> Result<(), TransactionError> {
+        let mut rows = Self::deserialize_rows(&self.config, input, self.config.strict_mode).await?;
+        let mut rows_read = 0;
+        while !Self::row_limit_reached(&self.config, rows_read) {
+            let result = match Self::next_row(&self.config, &mut rows).await {
+                Some(result) => result,
+                None => break,
+            };
+            rows_read += 1;
+            self.process_input_row(result?)?;
+
+            // a row can apply more than one transaction, e.g. when it expires disputes
+            let applied = self.ledger_rows.as_mut().map(std::mem::take);
+            for row in applied.into_iter().flatten() {
+                if rows_tx.send(row).await.is_err() {
+                    // the writer exited early. Its error is returned by the caller
+                    return Ok(());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Records the ledger row of a transaction that was just applied, while `process_ledger` runs
+    pub(crate) fn record_ledger_row(
+        &mut self,
+        tx_type: TransactionType,
+        account: &AccountKey,
+        tx: u32,
+        amount: Decimal,
+    ) {
+        let ledger = self.config.multi_ledger.then(|| account.1.clone());
+        let currency = self.config.multi_currency.then(|| account.2.clone());
+        if let Some(rows) = self.ledger_rows.as_mut() {
+            let row = &self.clients[account];
+            rows.push(LedgerRow {
+                client: account.0,
+                ledger,
+                currency,
+                tx,
+                tx_type: tx_type.as_str(),
+                amount,
+                available: row.available,
+                held: row.held,
+                total: row.total,
+                locked: row.locked,
+            });
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError, TransactionSummary};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+impl TransactionEngine {
+    /// Applies the rows of several CSV inputs in chronological order of their `timestamp` column, then writes every
+    /// client's balances to the output. Each input must already be sorted by timestamp. Rows with equal timestamps are
+    /// applied in the order of their inputs. A row without a timestamp fails with
+    /// [`TransactionError::MissingTimestamp`]. `max_rows` limits the rows applied across all inputs. Starts from an empty
+    /// state like [`Self::process`]
+    pub async fn process_merged_by_timestamp<
+        R: AsyncRead + Unpin + Send + Sync,
+        W: AsyncWrite + Unpin,
+    >(
+        &mut self,
+        inputs: Vec<R>,
+        output: W,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.clear();
+        if let Err(err) = self.merge_inputs(inputs).await {
+            if self.config.flush_partial_output_on_error {
+                // the processing error takes precedence over any error while writing the partial output
+                let _ = self.write_output(output).await;
+            }
+
+            return Err(err);
+        }
+
+        self.write_output(output).await?;
+        Ok(self.summary())
+    }
+
+    async fn merge_inputs<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        inputs: Vec<R>,
+    ) -> Result<(), TransactionError> {
+        let config = self.config.clone();
+        let mut sources = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            sources.push(Self::deserialize_rows(&config, input, config.strict_mode).await?);
+        }
+
+        // holds the next row of every input that is not exhausted, keyed by (timestamp, input index)
+        let mut heads = BinaryHeap::with_capacity(sources.len());
+        let mut rows = Vec::with_capacity(sources.len());
+        for (index, source) in sources.iter_mut().enumerate() {
+            let row = Self::next_row(&config, source).await.transpose()?;
+            if let Some(row) = row.as_ref() {
+                heads.push(Reverse((timestamp_of(row)?, index)));
+            }
+            rows.push(row);
+        }
+
+        let mut rows_read = 0;
+        while let Some(Reverse((_, index))) = heads.pop() {
+            if Self::row_limit_reached(&config, rows_read) {
+                break;
+            }
+            rows_read += 1;
+
+            if let Some(row) = rows[index].take() {
+                self.process_input_row(row)?;
+            }
+
+            let next = Self::next_row(&config, &mut sources[index])
+                .await
+                .transpose()?;
+            if let Some(next) = next.as_ref() {
+                heads.push(Reverse((timestamp_of(next)?, index)));
+            }
+            rows[index] = next;
+        }
+
+        Ok(())
+    }
+}
+
+fn timestamp_of(row: &InputRow) -> Result<u64, TransactionError> {
+    row.timestamp
+        .ok_or(TransactionError::MissingTimestamp { tx: row.tx })
+}
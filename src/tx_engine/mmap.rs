@@ -0,0 +1,39 @@
+use memmap2::Mmap;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Reads a memory-mapped local file. Every read is a copy out of the mapping, so no I/O is scheduled on tokio's
+/// blocking pool
+pub struct MmapReader {
+    mmap: Mmap,
+    pos: usize,
+}
+
+impl MmapReader {
+    /// Maps the file at `path`. The file must not be modified or truncated while it is mapped
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the mapping is only read, and the caller opted in on the condition that the file is not modified
+        // while it is being processed
+        let mmap = unsafe { Mmap::map(&file)? };
+        Ok(Self { mmap, pos: 0 })
+    }
+}
+
+impl AsyncRead for MmapReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let remaining = &this.mmap[this.pos..];
+        let n = remaining.len().min(buf.remaining());
+        buf.put_slice(&remaining[..n]);
+        this.pos += n;
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(feature = "async")]
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError, TransactionSummary};
+#[cfg(feature = "async")]
+use std::future::poll_fn;
+#[cfg(feature = "async")]
+use std::task::Poll;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+#[cfg(feature = "async")]
+use tokio::sync::mpsc::Receiver;
+
+/// Which channel is drained first when several have rows ready at the same time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SelectBias {
+    /// Channels take turns, starting after the channel that was served last
+    #[default]
+    Fair,
+    /// Channels are checked in the given order of indices. Channels not listed are checked last, in index order
+    Priority(Vec<usize>),
+}
+
+impl SelectBias {
+    /// Returns the order in which to check `channels` channels, given the index that was served last
+    #[cfg(feature = "async")]
+    fn poll_order(&self, channels: usize, last_served: usize) -> Vec<usize> {
+        match self {
+            Self::Fair => (1..=channels)
+                .map(|offset| (last_served + offset) % channels)
+                .collect(),
+            Self::Priority(priority) => {
+                let mut order = priority
+                    .iter()
+                    .copied()
+                    .filter(|&index| index < channels)
+                    .collect::<Vec<_>>();
+                order.extend((0..channels).filter(|index| !priority.contains(index)));
+                order
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl TransactionEngine {
+    /// Applies rows from several independent sources as they arrive, then writes every client's balances to the
+    /// output. Each channel's own order is preserved but the interleaving across channels is not deterministic.
+    /// Returns once every sender has been dropped. Starts from an empty state like [`Self::process`]
+    pub async fn process_multi_channel<W: AsyncWrite + Unpin>(
+        &mut self,
+        receivers: Vec<Receiver<InputRow>>,
+        output: W,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.clear();
+        if let Err(err) = self.drain_channels(receivers).await {
+            if self.config.flush_partial_output_on_error {
+                // the processing error takes precedence over any error while writing the partial output
+                let _ = self.write_output(output).await;
+            }
+
+            return Err(err);
+        }
+
+        self.write_output(output).await?;
+        Ok(self.summary())
+    }
+
+    /// Like [`Self::process_multi_channel`] for a single channel, so rows are applied in the order they were sent
+    pub async fn process_channel<W: AsyncWrite + Unpin>(
+        &mut self,
+        receiver: Receiver<InputRow>,
+        output: W,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.process_multi_channel(vec![receiver], output).await
+    }
+
+    async fn drain_channels(
+        &mut self,
+        mut receivers: Vec<Receiver<InputRow>>,
+    ) -> Result<(), TransactionError> {
+        let mut open = vec![true; receivers.len()];
+        let mut last_served = receivers.len().saturating_sub(1);
+
+        loop {
+            let order = self
+                .config
+                .select_bias
+                .poll_order(receivers.len(), last_served);
+            let next = poll_fn(|cx| {
+                for &index in &order {
+                    if !open[index] {
+                        continue;
+                    }
+
+                    match receivers[index].poll_recv(cx) {
+                        Poll::Ready(Some(row)) => return Poll::Ready(Some((index, row))),
+                        Poll::Ready(None) => open[index] = false,
+                        Poll::Pending => {}
+                    }
+                }
+
+                if open.contains(&true) {
+                    Poll::Pending
+                } else {
+                    Poll::Ready(None)
+                }
+            })
+            .await;
+
+            match next {
+                Some((index, row)) => {
+                    last_served = index;
+                    self.process_input_row(row)?;
+                }
+
+                None => return Ok(()),
+            }
+        }
+    }
+}
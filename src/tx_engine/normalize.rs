@@ -0,0 +1,41 @@
+use crate::tx_engine::InputRow;
+use std::collections::HashMap;
+
+/// Cleans up common formatting variations in parsed rows before they are processed. Surrounding whitespace is
+/// removed from the `type` and `category` fields, both are lowercased, and the type is mapped through the configured
+/// aliases
+pub struct InputNormalizer<'a> {
+    // keys are lowercase
+    type_aliases: &'a HashMap<String, String>,
+}
+
+impl<'a> InputNormalizer<'a> {
+    pub fn new(type_aliases: &'a HashMap<String, String>) -> Self {
+        Self { type_aliases }
+    }
+
+    pub fn normalize(&self, mut row: InputRow) -> InputRow {
+        row.r#type = row.r#type.trim().to_lowercase();
+        let mut row = self.resolve_alias(row);
+
+        row.category = row
+            .category
+            .map(|category| category.trim().to_lowercase())
+            .filter(|category| !category.is_empty());
+
+        row
+    }
+
+    /// Replaces the type with the canonical name it is an alias of, if any. Aliases are resolved even when the rest of
+    /// the row is not normalized
+    pub fn resolve_alias(&self, mut row: InputRow) -> InputRow {
+        if self.type_aliases.is_empty() {
+            return row;
+        }
+
+        if let Some(canonical) = self.type_aliases.get(&row.r#type.trim().to_lowercase()) {
+            row.r#type = canonical.clone();
+        }
+        row
+    }
+}
@@ -0,0 +1,302 @@
+use crate::tx_engine::Decimal;
+#[cfg(any(feature = "async", feature = "sync"))]
+use crate::tx_engine::{TransactionEngine, OUTPUT_COLUMNS};
+use serde::Serialize;
+use std::fmt;
+#[cfg(any(feature = "async", feature = "sync"))]
+use std::str::FromStr;
+#[cfg(feature = "async")]
+use tokio_stream::StreamExt;
+
+/// Most decimal places an output amount may have
+#[cfg(any(feature = "async", feature = "sync"))]
+const MAX_DECIMAL_PLACES: u32 = 4;
+
+/// A problem found when reading back the balances output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum OutputValidationError {
+    /// The output could not be parsed as CSV
+    Unparseable { line: u64, message: String },
+    /// The header row differs from the columns the engine writes with its configuration
+    UnexpectedHeader {
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    ColumnCount {
+        line: u64,
+        expected: usize,
+        found: usize,
+    },
+    /// An amount is not a decimal number with at most four decimal places
+    InvalidAmount {
+        line: u64,
+        column: String,
+        value: String,
+    },
+    /// The `locked` field is neither `true` nor `false`
+    InvalidLocked { line: u64, value: String },
+    /// `available + held != total`
+    BalanceMismatch {
+        line: u64,
+        available: Decimal,
+        held: Decimal,
+        total: Decimal,
+    },
+}
+
+impl fmt::Display for OutputValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unparseable { line, message } => write!(f, "line {}: {}", line, message),
+            Self::UnexpectedHeader { expected, found } => write!(
+                f,
+                "expected columns {} but found {}",
+                expected.join(","),
+                found.join(",")
+            ),
+            Self::ColumnCount {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} columns but found {}",
+                line, expected, found
+            ),
+            Self::InvalidAmount {
+                line,
+                column,
+                value,
+            } => write!(f, "line {}: invalid {} {:?}", line, column, value),
+            Self::InvalidLocked { line, value } => {
+                write!(f, "line {}: invalid locked {:?}", line, value)
+            }
+            Self::BalanceMismatch {
+                line,
+                available,
+                held,
+                total,
+            } => write!(
+                f,
+                "line {}: available {} + held {} != total {}",
+                line, available, held, total
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl TransactionEngine {
+    /// Reads back balances output written with this engine's configuration, checking its columns, that amounts have
+    /// at most four decimal places, that `locked` is `true` or `false` and that `available + held == total` on every
+    /// row. Called on the output before it is written when `validate_output` is enabled
+    pub async fn validate_output(
+        &self,
+        output_csv: &str,
+    ) -> Result<(), Vec<OutputValidationError>> {
+        let expected = self.output_columns();
+        let mut output = csv_async::AsyncReaderBuilder::new()
+            .flexible(true)
+            .create_reader(output_csv.as_bytes());
+        let mut errors = Vec::new();
+
+        match output.headers().await {
+            Ok(headers) => errors.extend(unexpected_header(
+                &expected,
+                &headers.iter().collect::<Vec<_>>(),
+            )),
+            Err(err) => errors.push(unparseable(&err)),
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut records = output.records();
+        while let Some(record) = records.next().await {
+            match record {
+                Ok(record) => self.check_output_record(
+                    &expected,
+                    record.position().map_or(0, |position| position.line()),
+                    &record.iter().collect::<Vec<_>>(),
+                    &mut errors,
+                ),
+                Err(err) => errors.push(unparseable(&err)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(feature = "sync")]
+impl TransactionEngine {
+    /// Blocking counterpart of [`Self::validate_output`]. Called on the output of [`Self::process_sync`] before it is
+    /// written when `validate_output` is enabled
+    pub fn validate_output_sync(&self, output_csv: &str) -> Result<(), Vec<OutputValidationError>> {
+        let expected = self.output_columns();
+        let mut output = csv::ReaderBuilder::new()
+            .flexible(true)
+            .from_reader(output_csv.as_bytes());
+        let mut errors = Vec::new();
+
+        match output.headers() {
+            Ok(headers) => errors.extend(unexpected_header(
+                &expected,
+                &headers.iter().collect::<Vec<_>>(),
+            )),
+            Err(err) => errors.push(unparseable_sync(&err)),
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        for record in output.records() {
+            match record {
+                Ok(record) => self.check_output_record(
+                    &expected,
+                    record.position().map_or(0, |position| position.line()),
+                    &record.iter().collect::<Vec<_>>(),
+                    &mut errors,
+                ),
+                Err(err) => errors.push(unparseable_sync(&err)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(any(feature = "async", feature = "sync"))]
+impl TransactionEngine {
+    /// Checks one row of the balances output, which has the fields `record` and is on `line`
+    fn check_output_record(
+        &self,
+        expected: &[&'static str],
+        line: u64,
+        record: &[&str],
+        errors: &mut Vec<OutputValidationError>,
+    ) {
+        if record.len() != expected.len() {
+            errors.push(OutputValidationError::ColumnCount {
+                line,
+                expected: expected.len(),
+                found: record.len(),
+            });
+            return;
+        }
+
+        let mut amount = |column: &str| {
+            // a column left out by `output_columns` is not checked
+            let value = expected
+                .iter()
+                .position(|name| *name == column)
+                .map(|index| record[index])?;
+            match Decimal::from_str(value) {
+                Ok(amount) if amount.scale() <= MAX_DECIMAL_PLACES => Some(amount),
+                _ => {
+                    errors.push(OutputValidationError::InvalidAmount {
+                        line,
+                        column: column.to_string(),
+                        value: value.to_string(),
+                    });
+                    None
+                }
+            }
+        };
+
+        let available = amount("available");
+        let held = amount("held");
+        let total = amount("total");
+        if self.config.track_pending {
+            amount("pending");
+        }
+
+        if let (Some(available), Some(held), Some(total)) = (available, held, total) {
+            if available + held != total {
+                errors.push(OutputValidationError::BalanceMismatch {
+                    line,
+                    available,
+                    held,
+                    total,
+                });
+            }
+        }
+
+        let locked = expected
+            .iter()
+            .position(|name| *name == "locked")
+            .map(|index| record[index]);
+        let format = self.config.locked_field_format;
+        if let Some(locked) = locked
+            .filter(|locked| *locked != format.format(true) && *locked != format.format(false))
+        {
+            errors.push(OutputValidationError::InvalidLocked {
+                line,
+                value: locked.to_string(),
+            });
+        }
+    }
+
+    /// The columns of the balances output, in order, for this engine's configuration
+    fn output_columns(&self) -> Vec<&'static str> {
+        if let Some(columns) = self.config.output_columns.as_ref() {
+            return columns
+                .iter()
+                .filter_map(|name| OUTPUT_COLUMNS.iter().copied().find(|column| column == name))
+                .collect();
+        }
+
+        let mut columns = vec!["client"];
+        if self.config.multi_ledger {
+            columns.push("ledger");
+        }
+        if self.config.multi_currency {
+            columns.push("currency");
+        }
+        columns.extend(["available", "held"]);
+        if self.config.track_pending {
+            columns.push("pending");
+        }
+        columns.extend(["total", "locked"]);
+        columns
+    }
+}
+
+/// The header row's mismatch with the `expected` columns, if any. Output without any rows has no header row
+#[cfg(any(feature = "async", feature = "sync"))]
+fn unexpected_header(expected: &[&str], found: &[&str]) -> Option<OutputValidationError> {
+    if found.is_empty() || found == expected {
+        return None;
+    }
+
+    Some(OutputValidationError::UnexpectedHeader {
+        expected: expected.iter().map(|name| name.to_string()).collect(),
+        found: found.iter().map(|name| name.to_string()).collect(),
+    })
+}
+
+#[cfg(feature = "async")]
+fn unparseable(err: &csv_async::Error) -> OutputValidationError {
+    OutputValidationError::Unparseable {
+        line: err.position().map_or(0, |position| position.line()),
+        message: err.to_string(),
+    }
+}
+
+#[cfg(feature = "sync")]
+fn unparseable_sync(err: &csv::Error) -> OutputValidationError {
+    OutputValidationError::Unparseable {
+        line: err.position().map_or(0, |position| position.line()),
+        message: err.to_string(),
+    }
+}
@@ -0,0 +1,120 @@
+use crate::tx_engine::parse::RawInputRow;
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError};
+use csv_async::StringRecord;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use tokio::io::AsyncRead;
+use tokio::task::JoinHandle;
+
+/// Rows of a chunk, parsed on a blocking thread
+type ParsedChunk = JoinHandle<Vec<Result<InputRow, TransactionError>>>;
+
+impl TransactionEngine {
+    /// Applies every row of the input like [`Self::process_rows`], but deserializes chunks of `parse_chunk_size` rows
+    /// on up to `parse_workers` blocking threads at once. Chunks are applied in input order, so the engine still sees
+    /// the rows chronologically
+    pub(crate) async fn process_rows_parallel_parse<R: AsyncRead + Unpin + Send + Sync>(
+        &mut self,
+        input: R,
+        validate_schema: bool,
+    ) -> Result<(), TransactionError> {
+        let config = self.config.clone();
+        let mut reader = Self::open_reader(&config, input, validate_schema).await?;
+        let headers = Arc::new(reader.headers().await?.clone());
+        let chunk_size = config.parse_chunk_size.max(1);
+
+        let mut in_flight: VecDeque<ParsedChunk> = VecDeque::with_capacity(config.parse_workers);
+        let mut rows_read = 0u64;
+        let mut exhausted = false;
+        // whether the last row read is the end of the input, rather than followed by rows that were not read
+        let mut input_ended = false;
+        loop {
+            while !exhausted && in_flight.len() < config.parse_workers {
+                let mut chunk = Vec::with_capacity(chunk_size);
+                while chunk.len() < chunk_size {
+                    if Self::row_limit_reached(&config, rows_read) {
+                        exhausted = true;
+                        break;
+                    }
+
+                    let mut record = StringRecord::new();
+                    match reader.read_record(&mut record).await {
+                        Ok(true) => chunk.push(Ok(record)),
+                        Ok(false) => {
+                            input_ended = true;
+                            exhausted = true;
+                        }
+                        Err(err) => {
+                            input_ended = !reader.read_record(&mut record).await.unwrap_or(true);
+                            chunk.push(Err(err));
+                            exhausted = true;
+                        }
+                    }
+
+                    if exhausted {
+                        break;
+                    }
+                    rows_read += 1;
+                }
+
+                if chunk.is_empty() {
+                    break;
+                }
+
+                let headers = headers.clone();
+                in_flight.push_back(tokio::task::spawn_blocking(move || {
+                    chunk
+                        .into_iter()
+                        .map(|record| parse_record(&headers, record))
+                        .collect()
+                }));
+            }
+
+            let rows = match in_flight.pop_front() {
+                Some(chunk) => chunk.await?,
+                None => return Ok(()),
+            };
+
+            let last_chunk = input_ended && in_flight.is_empty();
+            let row_count = rows.len();
+            for (index, row) in rows.into_iter().enumerate() {
+                #[cfg(feature = "signal-handling")]
+                if config
+                    .shutdown
+                    .as_ref()
+                    .is_some_and(|shutdown| *shutdown.borrow())
+                {
+                    return Err(TransactionError::Interrupted);
+                }
+
+                match row {
+                    Ok(row) => {
+                        self.process_input_row(row)?;
+                    }
+
+                    // only a row followed by more rows is a genuine error
+                    Err(_)
+                        if config.ignore_partial_final_row
+                            && last_chunk
+                            && index + 1 == row_count =>
+                    {
+                        return Ok(())
+                    }
+
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+    }
+}
+
+fn parse_record(
+    headers: &StringRecord,
+    record: Result<StringRecord, csv_async::Error>,
+) -> Result<InputRow, TransactionError> {
+    record
+        .and_then(|record| record.deserialize::<RawInputRow>(Some(headers)))
+        .map_err(TransactionError::from)
+        .and_then(InputRow::try_from)
+}
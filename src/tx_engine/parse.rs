@@ -0,0 +1,152 @@
+use crate::tx_engine::{Decimal, InputRow, TransactionError};
+use serde::de::{self, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+const FIELDS: &[&str] = &[
+    "type",
+    "client",
+    "tx",
+    "amount",
+    "category",
+    "pending",
+    "ledger",
+    "currency",
+    "timestamp",
+    "target_client",
+    "target_ledger",
+];
+
+/// The unparsed fields of an input row. Each field is parsed separately when converting into an [`InputRow`], so that
+/// a failure can name the offending field
+#[derive(Default)]
+pub(crate) struct RawInputRow {
+    r#type: Option<String>,
+    client: Option<String>,
+    tx: Option<String>,
+    amount: Option<String>,
+    category: Option<String>,
+    pending: Option<String>,
+    ledger: Option<String>,
+    currency: Option<String>,
+    timestamp: Option<String>,
+    target_client: Option<String>,
+    target_ledger: Option<String>,
+}
+
+impl<'de> Deserialize<'de> for RawInputRow {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("InputRow", FIELDS, RawInputRowVisitor)
+    }
+}
+
+struct RawInputRowVisitor;
+
+impl<'de> Visitor<'de> for RawInputRowVisitor {
+    type Value = RawInputRow;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a transaction row")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut row = RawInputRow::default();
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                // required, so an empty field is kept to be reported as unparseable rather than missing
+                "type" => row.r#type = Some(map.next_value()?),
+                "client" => row.client = Some(map.next_value()?),
+                "tx" => row.tx = Some(map.next_value()?),
+                "amount" => row.amount = map.next_value()?,
+                "category" => row.category = map.next_value()?,
+                "pending" => row.pending = map.next_value()?,
+                "ledger" => row.ledger = map.next_value()?,
+                "currency" => row.currency = map.next_value()?,
+                "timestamp" => row.timestamp = map.next_value()?,
+                "target_client" => row.target_client = map.next_value()?,
+                "target_ledger" => row.target_ledger = map.next_value()?,
+                // unknown columns are ignored, as with the derived implementation
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        if row.r#type.is_none() {
+            return Err(de::Error::missing_field("type"));
+        }
+
+        if row.client.is_none() {
+            return Err(de::Error::missing_field("client"));
+        }
+
+        if row.tx.is_none() {
+            return Err(de::Error::missing_field("tx"));
+        }
+
+        Ok(row)
+    }
+}
+
+impl TryFrom<RawInputRow> for InputRow {
+    type Error = TransactionError;
+
+    fn try_from(row: RawInputRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            r#type: row.r#type.unwrap_or_default(),
+            client: parse_field("client", "u16", row.client.unwrap_or_default())?,
+            tx: parse_field("tx", "u32", row.tx.unwrap_or_default())?,
+            amount: row
+                .amount
+                .map(|amount| parse_field::<ParsedDecimal>("amount", "decimal", amount))
+                .transpose()?
+                .map(|amount| amount.0),
+            category: row.category,
+            pending: row
+                .pending
+                .map(|pending| parse_field("pending", "bool", pending))
+                .transpose()?,
+            ledger: row.ledger,
+            currency: row.currency,
+            timestamp: row
+                .timestamp
+                .map(|timestamp| parse_field("timestamp", "u64", timestamp))
+                .transpose()?,
+            target_client: row
+                .target_client
+                .map(|target_client| parse_field("target_client", "u16", target_client))
+                .transpose()?,
+            target_ledger: row.target_ledger,
+        })
+    }
+}
+
+fn parse_field<T: FromStr>(
+    field: &'static str,
+    expected_type: &'static str,
+    value: String,
+) -> Result<T, TransactionError> {
+    value
+        .parse()
+        .map_err(|_| TransactionError::FieldParseError {
+            field,
+            value,
+            expected_type,
+        })
+}
+
+/// Accepts scientific notation and drops trailing zeros, like the CSV deserializer did when it went through floats, but
+/// without their loss of precision
+struct ParsedDecimal(Decimal);
+
+impl FromStr for ParsedDecimal {
+    type Err = rust_decimal::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Decimal::from_str(value)
+            .or_else(|_| Decimal::from_scientific(value))
+            .map(|amount| ParsedDecimal(amount.normalize()))
+    }
+}
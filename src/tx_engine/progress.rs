@@ -0,0 +1,127 @@
+use crate::tx_engine::{TransactionEngine, TransactionError, TransactionSummary};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::watch;
+use tokio_stream::wrappers::WatchStream;
+
+/// Rows between progress updates
+const PROGRESS_ROW_INTERVAL: u64 = 1000;
+/// Longest time between progress updates while rows are being read
+const PROGRESS_TIME_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How far processing has come, published by [`TransactionEngine::process_with_progress_stream`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    /// Input rows read so far, including skipped and rejected ones
+    pub rows_processed: u64,
+    pub bytes_read: u64,
+    pub clients_seen: usize,
+    pub elapsed: Duration,
+}
+
+/// Publishes progress while `process_with_progress_stream` runs
+pub(crate) struct Progress {
+    updates: watch::Sender<ProgressUpdate>,
+    bytes_read: Arc<AtomicU64>,
+    rows: u64,
+    started: Instant,
+    last_update: Instant,
+}
+
+impl TransactionEngine {
+    /// Like [`Self::process`], but also returns a stream of [`ProgressUpdate`]s, published every 1000 rows or every
+    /// second while rows are read, whichever comes first, and once more when processing ends. Only the latest update
+    /// is kept, so a slow consumer skips updates rather than holding up processing. The stream may be dropped at any
+    /// time
+    pub fn process_with_progress_stream<'a, R, W>(
+        &'a mut self,
+        input: R,
+        output: W,
+    ) -> (
+        impl Future<Output = Result<TransactionSummary, TransactionError>> + 'a,
+        WatchStream<ProgressUpdate>,
+    )
+    where
+        R: AsyncRead + Unpin + Send + Sync + 'a,
+        W: AsyncWrite + Unpin + 'a,
+    {
+        let (updates, updates_rx) = watch::channel(ProgressUpdate::default());
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let input = CountingReader {
+            inner: input,
+            bytes_read: bytes_read.clone(),
+        };
+
+        let processing = async move {
+            let started = Instant::now();
+            self.progress = Some(Progress {
+                updates,
+                bytes_read,
+                rows: 0,
+                started,
+                last_update: started,
+            });
+
+            let result = self.process(input, output).await;
+            self.publish_progress();
+            self.progress = None;
+            result.map(|_| self.summary())
+        };
+
+        (processing, WatchStream::new(updates_rx))
+    }
+
+    /// Counts a row read, publishing an update if one is due
+    pub(crate) fn tick_progress(&mut self) {
+        let progress = match self.progress.as_mut() {
+            Some(progress) => progress,
+            None => return,
+        };
+
+        progress.rows += 1;
+        if progress.rows.is_multiple_of(PROGRESS_ROW_INTERVAL)
+            || progress.last_update.elapsed() >= PROGRESS_TIME_INTERVAL
+        {
+            self.publish_progress();
+        }
+    }
+
+    fn publish_progress(&mut self) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.last_update = Instant::now();
+            // unlike send, succeeds even once the stream was dropped
+            progress.updates.send_replace(ProgressUpdate {
+                rows_processed: progress.rows,
+                bytes_read: progress.bytes_read.load(Ordering::Relaxed),
+                clients_seen: self.clients.len(),
+                elapsed: progress.started.elapsed(),
+            });
+        }
+    }
+}
+
+/// Counts the bytes read through it for progress updates
+struct CountingReader<R> {
+    inner: R,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for CountingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut this.inner).poll_read(cx, buf))?;
+        let read = (buf.filled().len() - filled) as u64;
+        this.bytes_read.fetch_add(read, Ordering::Relaxed);
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,101 @@
+use crate::tx_engine::parse::RawInputRow;
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError};
+use std::convert::TryFrom;
+use tokio::io::{AsyncRead, AsyncWrite, BufWriter};
+
+impl TransactionEngine {
+    /// Processes the input like [`Self::process`], except that rows which fail to parse or validate are written to
+    /// `quarantine` instead of aborting. The quarantine CSV repeats the input's header followed by `line` and `error`
+    /// columns, so it can be fixed and resubmitted as is. Lines are counted after comment lines are removed.
+    /// Starts from an empty state like [`Self::process`] and returns the number of quarantined rows
+    pub async fn process_with_quarantine<
+        R: AsyncRead + Unpin + Send + Sync,
+        W: AsyncWrite + Unpin,
+        Q: AsyncWrite + Unpin,
+    >(
+        &mut self,
+        input: R,
+        output: W,
+        quarantine: Q,
+    ) -> Result<u64, TransactionError> {
+        self.clear();
+        let quarantined = match self.quarantine_rows(input, quarantine).await {
+            Ok(quarantined) => quarantined,
+            Err(err) => {
+                if self.config.flush_partial_output_on_error {
+                    // the processing error takes precedence over any error while writing the partial output
+                    let _ = self.write_output(output).await;
+                }
+
+                return Err(err);
+            }
+        };
+
+        self.write_output(output).await?;
+        Ok(quarantined)
+    }
+
+    /// Applies every valid row of the input, writing the others to `quarantine`. The quarantine is flushed even if
+    /// processing fails
+    async fn quarantine_rows<R: AsyncRead + Unpin + Send + Sync, Q: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        quarantine: Q,
+    ) -> Result<u64, TransactionError> {
+        let mut input = Self::open_reader(&self.config, input, self.config.strict_mode).await?;
+        let headers = input.headers().await?.clone();
+        // rows may have any number of fields, so the line and error columns cannot line up for every row
+        let mut quarantine = csv_async::AsyncWriterBuilder::new()
+            .flexible(true)
+            .create_writer(BufWriter::with_capacity(
+                self.config.output_buffer_size,
+                quarantine,
+            ));
+
+        let result = async {
+            let mut quarantine_headers = headers.clone();
+            quarantine_headers.push_field("line");
+            quarantine_headers.push_field("error");
+            quarantine.write_record(&quarantine_headers).await?;
+
+            let mut quarantined = 0;
+            let mut record = csv_async::StringRecord::new();
+            let mut rows_read = 0;
+            while !Self::row_limit_reached(&self.config, rows_read)
+                && input.read_record(&mut record).await?
+            {
+                rows_read += 1;
+                let applied = record
+                    .deserialize::<RawInputRow>(Some(&headers))
+                    .map_err(TransactionError::from)
+                    .and_then(InputRow::try_from)
+                    .and_then(|row| self.process_input_row(row).map(|_| ()));
+
+                match applied {
+                    Err(err) if err.is_row_error() => {
+                        let line = record
+                            .position()
+                            .map(|position| position.line().to_string())
+                            .unwrap_or_default();
+
+                        let mut quarantined_record = record.clone();
+                        quarantined_record.push_field(&line);
+                        quarantined_record.push_field(&err.to_string());
+                        quarantine.write_record(&quarantined_record).await?;
+                        quarantined += 1;
+                    }
+
+                    applied => applied?,
+                }
+            }
+
+            Ok::<_, TransactionError>(quarantined)
+        }
+        .await;
+
+        let flushed = quarantine.flush().await;
+        let quarantined = result?;
+        flushed?;
+        Ok(quarantined)
+    }
+}
@@ -0,0 +1,22 @@
+#[cfg(feature = "async")]
+use crate::tx_engine::DomainEvent;
+use crate::tx_engine::{Rejection, RejectionReason, TransactionEngine};
+
+/// Receives every rejected transaction as it is refused, e.g. to forward it to the host application's logging
+pub trait RejectionSink {
+    fn record(&self, rejection: &Rejection);
+}
+
+impl TransactionEngine {
+    /// Records a refused transaction, passing it to the configured rejection sink and emitting its event
+    pub(crate) fn reject(&mut self, client: u16, tx: u32, reason: RejectionReason) {
+        let rejection = Rejection { client, tx, reason };
+        if let Some(sink) = self.config.rejection_sink.as_ref() {
+            sink.record(&rejection);
+        }
+
+        self.rejections.push(rejection);
+        #[cfg(feature = "async")]
+        self.emit(DomainEvent::TransactionRejected { client, tx, reason });
+    }
+}
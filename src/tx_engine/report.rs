@@ -0,0 +1,285 @@
+#[cfg(feature = "async")]
+use crate::tx_engine::TransactionError;
+use crate::tx_engine::{
+    AuditEntry, Decimal, OutputRow, Rejection, RejectionReason, SkippedRow, TransactionEngine,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Width in columns the displayed report fits in unless changed with [`Report::with_terminal_width`]
+pub(crate) const DEFAULT_TERMINAL_WIDTH: u16 = 80;
+/// Headers of the displayed table, with the ledger and currency columns inserted after the client when any account has
+/// one. The amount columns share whatever width is left by the others
+const TABLE_HEADERS: [&str; 7] = [
+    "client",
+    "available",
+    "held",
+    "total",
+    "locked",
+    "disputes",
+    "transactions",
+];
+/// Number of available, held and total columns, which follow the client, ledger and currency
+const AMOUNT_COLUMNS: usize = 3;
+const COLUMN_SEPARATOR: &str = " | ";
+
+/// A dispute that has been opened but not yet resolved or charged back
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenDispute {
+    pub client: u16,
+    /// `None` unless the engine runs in multi-ledger mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+    /// `None` unless the engine runs in multi-currency mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+    pub tx: u32,
+    pub amount: Decimal,
+}
+
+/// Aggregate counters for everything an engine processed, returned by the `process_*` methods that report on a run and
+/// by [`TransactionEngine::summary`](crate::tx_engine::TransactionEngine::summary)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionSummary {
+    /// Transactions applied or rejected. Skipped rows are not included
+    pub transactions_processed: u64,
+    pub clients: usize,
+    pub locked_clients: usize,
+    pub open_disputes: usize,
+    pub rejections: usize,
+    pub skipped: usize,
+    pub flagged: usize,
+    /// Domain events that did not fit in the channel passed to `process_with_events`
+    pub events_dropped: u64,
+    /// Sum of all amounts charged back. Only tracked when `track_chargeback_totals` is enabled
+    pub total_chargebacked: Decimal,
+    pub total_chargeback_count: u64,
+}
+
+/// How the disputes of a run turned out. Disputes that expired count as resolved
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DisputeSummary {
+    pub opened: u64,
+    pub opened_amount: Decimal,
+    pub resolved: u64,
+    pub resolved_amount: Decimal,
+    pub charged_back: u64,
+    pub charged_back_amount: Decimal,
+    /// Disputes neither resolved nor charged back yet
+    pub open: u64,
+    pub open_amount: Decimal,
+}
+
+impl fmt::Display for DisputeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Disputes: {} opened ({}), {} resolved ({}), {} charged back ({}), {} still open ({})",
+            self.opened,
+            self.opened_amount,
+            self.resolved,
+            self.resolved_amount,
+            self.charged_back,
+            self.charged_back_amount,
+            self.open,
+            self.open_amount
+        )
+    }
+}
+
+/// How many transactions were refused for an account, and the most recent one
+#[derive(Debug, Clone, Serialize)]
+pub struct RejectionsPerClientRow {
+    pub client: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger: Option<String>,
+    pub rejection_count: u32,
+    pub last_rejected_tx: Option<u32>,
+    pub last_rejection_reason: Option<RejectionReason>,
+    pub last_rejected_amount: Option<Decimal>,
+}
+
+/// Everything known about a run: the accounts, open disputes, rejected, skipped and flagged rows, and run
+/// statistics. Accounts are sorted by client ID and disputes by (client, tx) so the report is deterministic.
+/// Displays as a table for operators with one row per account, sorted by total balance descending
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub accounts: Vec<OutputRow>,
+    pub open_disputes: Vec<OpenDispute>,
+    pub rejections: Vec<Rejection>,
+    pub skipped: Vec<SkippedRow>,
+    pub audit_log: Vec<AuditEntry>,
+    pub summary: TransactionSummary,
+    #[serde(skip)]
+    pub(crate) terminal_width: u16,
+}
+
+impl Report {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Sets the number of columns the displayed table should fit in. 80 by default. Amounts wider than their column
+    /// still overflow it
+    pub fn with_terminal_width(mut self, width: u16) -> Self {
+        self.terminal_width = width;
+        self
+    }
+
+    pub fn print_to_stdout(&self) {
+        print!("{}", self)
+    }
+
+    pub fn print_to_stderr(&self) {
+        eprint!("{}", self)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut open_disputes = HashMap::<(u16, Option<&str>, Option<&str>), usize>::new();
+        for dispute in &self.open_disputes {
+            let account = (
+                dispute.client,
+                dispute.ledger.as_deref(),
+                dispute.currency.as_deref(),
+            );
+            *open_disputes.entry(account).or_default() += 1;
+        }
+
+        let mut accounts = self.accounts.iter().collect::<Vec<_>>();
+        accounts.sort_by(|a, b| {
+            b.total().cmp(&a.total()).then_with(|| {
+                (a.client(), a.ledger(), a.currency()).cmp(&(b.client(), b.ledger(), b.currency()))
+            })
+        });
+        let with_ledger = accounts.iter().any(|row| row.ledger().is_some());
+        let with_currency = accounts.iter().any(|row| row.currency().is_some());
+
+        let mut headers = vec![TABLE_HEADERS[0]];
+        if with_ledger {
+            headers.push("ledger");
+        }
+        if with_currency {
+            headers.push("currency");
+        }
+        let first_amount = headers.len();
+        headers.extend(&TABLE_HEADERS[1..]);
+        let amount_columns = first_amount..first_amount + AMOUNT_COLUMNS;
+
+        let rows = accounts
+            .into_iter()
+            .map(|row| {
+                let mut cells = vec![row.client().to_string()];
+                if with_ledger {
+                    cells.push(row.ledger().unwrap_or_default().to_string());
+                }
+                if with_currency {
+                    cells.push(row.currency().unwrap_or_default().to_string());
+                }
+                let account = (row.client(), row.ledger(), row.currency());
+                cells.extend([
+                    row.available().to_string(),
+                    row.held().to_string(),
+                    row.total().to_string(),
+                    row.locked().to_string(),
+                    open_disputes
+                        .get(&account)
+                        .copied()
+                        .unwrap_or(0)
+                        .to_string(),
+                    row.transaction_count().to_string(),
+                ]);
+                cells
+            })
+            .collect::<Vec<_>>();
+
+        // every column but the amounts is as wide as its header or widest cell, whichever is wider
+        let mut widths = headers
+            .iter()
+            .map(|header| header.len())
+            .collect::<Vec<_>>();
+        for cells in &rows {
+            for (width, cell) in widths.iter_mut().zip(cells) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let other_width = widths.iter().sum::<usize>()
+            - widths[amount_columns.clone()].iter().sum::<usize>()
+            + COLUMN_SEPARATOR.len() * (widths.len() - 1);
+        let amount_width = ((self.terminal_width as usize).saturating_sub(other_width)
+            / AMOUNT_COLUMNS)
+            .max(TABLE_HEADERS[1].len());
+        widths[amount_columns].fill(amount_width);
+
+        let header = headers.iter().map(|header| header.to_string());
+        write_table_row(f, &widths, header)?;
+        let rule = widths.iter().map(|width| "-".repeat(*width));
+        write_table_row(f, &widths, rule)?;
+
+        for cells in rows {
+            write_table_row(f, &widths, cells.into_iter())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes one line of the displayed table, right-aligning every cell in its column
+fn write_table_row(
+    f: &mut fmt::Formatter<'_>,
+    widths: &[usize],
+    cells: impl Iterator<Item = String>,
+) -> fmt::Result {
+    let line = cells
+        .zip(widths)
+        .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+        .collect::<Vec<_>>()
+        .join(COLUMN_SEPARATOR);
+    writeln!(f, "{}", line)
+}
+
+impl TransactionEngine {
+    /// Writes the number of rejected transactions and the most recent rejection of every account with at least one,
+    /// sorted by client ID and ledger. Requires `track_rejections`, without which nothing is written
+    #[cfg(feature = "async")]
+    pub async fn write_rejections_per_client<W: AsyncWrite + Unpin>(
+        &self,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        let mut accounts = self
+            .clients
+            .values()
+            .filter(|row| row.rejection_count() > 0)
+            .collect::<Vec<_>>();
+        accounts.sort_by(|a, b| (a.client(), a.ledger()).cmp(&(b.client(), b.ledger())));
+
+        let mut output = Self::serializer(&self.config, output);
+        let mut result = Ok(());
+        for row in accounts {
+            let last_rejection = row.last_rejection();
+            let report_row = RejectionsPerClientRow {
+                client: row.client(),
+                ledger: row.ledger().map(str::to_string),
+                rejection_count: row.rejection_count(),
+                last_rejected_tx: last_rejection.map(|rejection| rejection.tx),
+                last_rejection_reason: last_rejection.map(|rejection| rejection.reason),
+                last_rejected_amount: last_rejection.map(|rejection| rejection.amount),
+            };
+
+            if let Err(err) = output.serialize(report_row).await {
+                result = Err(err);
+                break;
+            }
+        }
+
+        Self::finalize(&self.config, output, result).await
+    }
+}
@@ -0,0 +1,121 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, ReadBuf};
+use tokio::time::Sleep;
+
+/// Controls how transient read errors (e.g. from network-mounted filesystems) are retried
+#[derive(Clone, Debug)]
+pub struct IoRetryConfig {
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    /// Each successive delay is the previous one multiplied by this factor
+    pub backoff_factor: f64,
+    /// Error kinds considered transient. `WouldBlock` is always retried
+    pub retryable_kinds: Vec<ErrorKind>,
+}
+
+impl Default for IoRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(50),
+            backoff_factor: 2.0,
+            retryable_kinds: Vec::new(),
+        }
+    }
+}
+
+impl IoRetryConfig {
+    fn is_retryable(&self, kind: ErrorKind) -> bool {
+        kind == ErrorKind::WouldBlock || self.retryable_kinds.contains(&kind)
+    }
+
+    fn delay_for(&self, retry: u32) -> Duration {
+        self.initial_delay
+            .mul_f64(self.backoff_factor.powi(retry as i32))
+    }
+}
+
+/// Payload of the `io::Error` returned once all retries are used up. Converted into
+/// [`TransactionError::IoRetryExhausted`](crate::tx_engine::TransactionError::IoRetryExhausted)
+#[derive(Debug)]
+pub(crate) struct RetriesExhausted {
+    pub attempts: u32,
+    pub last_error: std::io::Error,
+}
+
+impl std::fmt::Display for RetriesExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "I/O error after {} attempts: {}",
+            self.attempts, self.last_error
+        )
+    }
+}
+
+impl std::error::Error for RetriesExhausted {}
+
+/// Wraps a reader, retrying failed reads with exponential backoff when the error is transient
+pub struct RetryingReader<R> {
+    inner: R,
+    config: IoRetryConfig,
+    // retries performed for the current read. Reset after every successful read
+    retries: u32,
+    delay: Option<Pin<Box<Sleep>>>,
+}
+
+impl<R: AsyncRead + Unpin> RetryingReader<R> {
+    pub fn new(inner: R, config: IoRetryConfig) -> Self {
+        Self {
+            inner,
+            config,
+            retries: 0,
+            delay: None,
+        }
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for RetryingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(delay) = this.delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                this.delay = None;
+            }
+
+            match ready!(Pin::new(&mut this.inner).poll_read(cx, buf)) {
+                Ok(()) => {
+                    this.retries = 0;
+                    return Poll::Ready(Ok(()));
+                }
+
+                Err(err) if this.config.is_retryable(err.kind()) => {
+                    if this.retries >= this.config.max_retries {
+                        let kind = err.kind();
+                        let exhausted = RetriesExhausted {
+                            attempts: this.retries + 1,
+                            last_error: err,
+                        };
+                        return Poll::Ready(Err(std::io::Error::new(kind, exhausted)));
+                    }
+
+                    this.delay = Some(Box::pin(tokio::time::sleep(
+                        this.config.delay_for(this.retries),
+                    )));
+                    this.retries += 1;
+                }
+
+                Err(err) => return Poll::Ready(Err(err)),
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(any(feature = "async", feature = "sync"))]
+use crate::tx_engine::TransactionError;
+#[cfg(feature = "async")]
+use crate::tx_engine::{AsyncRead, AsyncWrite, TransactionEngine};
+use serde::Serialize;
+#[cfg(any(feature = "async", feature = "sync"))]
+use std::collections::HashSet;
+use std::fmt;
+
+/// Columns every input must have
+#[cfg(any(feature = "async", feature = "sync"))]
+const REQUIRED_COLUMNS: &[&str] = &["type", "client", "tx", "amount"];
+/// Columns an input may have in addition to the required ones
+#[cfg(any(feature = "async", feature = "sync"))]
+const OPTIONAL_COLUMNS: &[&str] = &["category", "pending", "ledger", "timestamp"];
+
+/// A problem with an input's header row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SchemaError {
+    MissingColumn(String),
+    /// The column appears more than once, ignoring case
+    DuplicateColumn(String),
+    UnknownColumn(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingColumn(name) => write!(f, "missing column {}", name),
+            Self::DuplicateColumn(name) => write!(f, "duplicate column {}", name),
+            Self::UnknownColumn(name) => write!(f, "unknown column {}", name),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl TransactionEngine {
+    /// Processes the input like [`Self::process`], after checking that its header row has every required column, no
+    /// unknown columns and no duplicates. Column names are matched case-insensitively. No row is read unless the
+    /// header is valid, in which case every problem found is returned in
+    /// [`TransactionError::SchemaValidationFailed`]. Called by [`Self::process`] when `strict_mode` is enabled, and
+    /// like it starts from an empty state
+    pub async fn process_with_schema_validation<
+        R: AsyncRead + Unpin + Send + Sync,
+        W: AsyncWrite + Unpin,
+    >(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        self.process_rows_then_output(input, output, true).await
+    }
+}
+
+/// Validates an input's header row, returning it with lowercase column names so that they match the fields of
+/// [`InputRow`](crate::tx_engine::InputRow)
+#[cfg(any(feature = "async", feature = "sync"))]
+pub(crate) fn validate_headers<'a, I: IntoIterator<Item = &'a str>>(
+    headers: I,
+) -> Result<Vec<String>, TransactionError> {
+    let normalized = headers
+        .into_iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect::<Vec<_>>();
+
+    let mut errors = Vec::new();
+    let mut seen = HashSet::new();
+    for name in normalized.iter().map(String::as_str) {
+        if !seen.insert(name) {
+            errors.push(SchemaError::DuplicateColumn(name.to_string()));
+        } else if !REQUIRED_COLUMNS.contains(&name) && !OPTIONAL_COLUMNS.contains(&name) {
+            errors.push(SchemaError::UnknownColumn(name.to_string()));
+        }
+    }
+
+    errors.extend(
+        REQUIRED_COLUMNS
+            .iter()
+            .filter(|name| !seen.contains(*name))
+            .map(|name| SchemaError::MissingColumn(name.to_string())),
+    );
+
+    if errors.is_empty() {
+        Ok(normalized)
+    } else {
+        Err(TransactionError::SchemaValidationFailed { errors })
+    }
+}
@@ -0,0 +1,49 @@
+use crate::tx_engine::{TransactionEngine, TransactionError};
+#[cfg(unix)]
+use std::path::Path;
+use tokio::io::{AsyncRead, AsyncWrite};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+impl TransactionEngine {
+    /// Listens for TCP connections on `addr`. Each connection is processed on its own engine sharing this engine's
+    /// configuration: the client writes its transactions, shuts down its write half, and reads back the balances.
+    /// Only returns if binding or accepting fails
+    pub async fn serve_tcp<A: ToSocketAddrs>(&self, addr: A) -> Result<(), TransactionError> {
+        let listener = TcpListener::bind(addr).await?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let (input, output) = stream.into_split();
+            self.spawn_connection(input, output);
+        }
+    }
+
+    /// Like [`Self::serve_tcp`], listening on a Unix domain socket at `path`
+    #[cfg(unix)]
+    pub async fn serve_unix<P: AsRef<Path>>(&self, path: P) -> Result<(), TransactionError> {
+        let listener = UnixListener::bind(path)?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let (input, output) = stream.into_split();
+            self.spawn_connection(input, output);
+        }
+    }
+
+    /// Processes a single connection on a fresh engine. Shared by every transport so they handle streams identically
+    fn spawn_connection<R, W>(&self, input: R, output: W)
+    where
+        R: AsyncRead + Unpin + Send + Sync + 'static,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut engine = Self::new(self.config.clone());
+        tokio::spawn(async move {
+            // the peer only sees the connection close, so the error is passed to the handler
+            if let Err(err) = engine.process(input, output).await {
+                if let Some(handler) = engine.config.on_connection_error.as_ref() {
+                    handler(err);
+                }
+            }
+        });
+    }
+}
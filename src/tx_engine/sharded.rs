@@ -0,0 +1,131 @@
+use crate::tx_engine::{InputRow, TransactionEngine, TransactionError, TransactionSummary};
+use std::thread;
+#[cfg(feature = "async")]
+use tokio::io::AsyncWrite;
+
+/// Maps a client to the index of the shard processing its transactions. Out of range indices wrap around
+pub type ShardFn = Box<dyn Fn(u16) -> usize + Send + Sync>;
+
+/// Partitions clients across several engines that process their rows in parallel. Clients are independent, so each
+/// shard owns its clients outright and no locking is needed between shards. Built with
+/// [`TransactionEngineBuilder::build_sharded`](crate::tx_engine::TransactionEngineBuilder::build_sharded)
+pub struct ShardedTransactionEngine {
+    shards: Vec<TransactionEngine>,
+    shard_fn: ShardFn,
+}
+
+impl ShardedTransactionEngine {
+    /// Creates `shards` engines from `engine`'s configuration, assigning clients by `client % shards`
+    pub(crate) fn new(engine: TransactionEngine, shards: usize) -> Self {
+        let count = shards.max(1);
+        let config = engine.config.clone();
+        let mut shards = Vec::with_capacity(count);
+        shards.push(engine);
+        shards.extend((1..count).map(|_| TransactionEngine::new(config.clone())));
+
+        Self {
+            shards,
+            shard_fn: Box::new(move |client| client as usize % count),
+        }
+    }
+
+    /// Replaces the function assigning clients to shards. It must always return the same shard for a client
+    pub fn with_shard_fn(mut self, shard_fn: ShardFn) -> Self {
+        self.shard_fn = shard_fn;
+        self
+    }
+
+    /// Returns the engine processing each shard
+    pub fn shards(&self) -> &[TransactionEngine] {
+        &self.shards
+    }
+
+    /// Partitions the rows by client and applies each shard's rows on its own thread, in input order. On error, rows
+    /// of other shards may have been applied. Transfers must stay within a shard, so nothing is applied if any
+    /// row's target client belongs to another shard
+    pub fn process_rows(
+        &mut self,
+        rows: Vec<InputRow>,
+    ) -> Result<TransactionSummary, TransactionError> {
+        let mut partitions = self.shards.iter().map(|_| Vec::new()).collect::<Vec<_>>();
+        let shard_of = |client| (self.shard_fn)(client) % partitions.len();
+        let mut sharded_rows = Vec::with_capacity(rows.len());
+        for row in rows {
+            let shard = shard_of(row.client);
+            if row
+                .target_client
+                .is_some_and(|target| shard_of(target) != shard)
+            {
+                return Err(TransactionError::CrossShardTransfer { tx: row.tx });
+            }
+            sharded_rows.push((shard, row));
+        }
+
+        for (shard, row) in sharded_rows {
+            partitions[shard].push(row);
+        }
+
+        thread::scope(|scope| {
+            let tasks = self
+                .shards
+                .iter_mut()
+                .zip(partitions)
+                .map(|(engine, rows)| {
+                    scope.spawn(move || {
+                        rows.into_iter()
+                            .try_for_each(|row| engine.process_input_row(row).map(|_| ()))
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // joined in order so that the first shard's error is returned
+            tasks.into_iter().try_for_each(|task| match task.join() {
+                Ok(result) => result,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+        })?;
+
+        Ok(self.summary())
+    }
+
+    /// Returns the counters of every shard added together
+    pub fn summary(&self) -> TransactionSummary {
+        self.shards.iter().map(TransactionEngine::summary).fold(
+            TransactionSummary::default(),
+            |total, stats| TransactionSummary {
+                transactions_processed: total.transactions_processed + stats.transactions_processed,
+                clients: total.clients + stats.clients,
+                locked_clients: total.locked_clients + stats.locked_clients,
+                open_disputes: total.open_disputes + stats.open_disputes,
+                rejections: total.rejections + stats.rejections,
+                skipped: total.skipped + stats.skipped,
+                flagged: total.flagged + stats.flagged,
+                events_dropped: total.events_dropped + stats.events_dropped,
+                total_chargebacked: total.total_chargebacked + stats.total_chargebacked,
+                total_chargeback_count: total
+                    .total_chargeback_count
+                    .saturating_add(stats.total_chargeback_count),
+            },
+        )
+    }
+
+    /// Merges the clients and history of every shard into a single engine
+    pub fn into_engine(self) -> Result<TransactionEngine, TransactionError> {
+        let mut shards = self.shards.into_iter();
+        let mut merged = shards.next().expect("there is always at least one shard");
+        for shard in shards {
+            merged.merge(shard)?;
+        }
+
+        Ok(merged)
+    }
+
+    /// Merges the shards and writes every client's balances to the output
+    #[cfg(feature = "async")]
+    pub async fn write_output<W: AsyncWrite + Unpin>(
+        self,
+        output: W,
+    ) -> Result<(), TransactionError> {
+        self.into_engine()?.write_output(output).await
+    }
+}
@@ -0,0 +1,33 @@
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::AsyncRead;
+use tokio::net::UnixStream;
+
+/// Whether `path` is a Unix domain socket. Checked from its metadata so the socket is never opened as a file
+pub async fn is_socket(path: &Path) -> bool {
+    tokio::fs::metadata(path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_socket())
+}
+
+/// Connects to the Unix domain socket at `path`, giving up after `timeout` if set
+pub async fn connect(
+    path: &Path,
+    timeout: Option<Duration>,
+) -> io::Result<Box<dyn AsyncRead + Unpin + Send + Sync>> {
+    let stream = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, UnixStream::connect(path))
+            .await
+            .map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("timed out connecting to {}", path.display()),
+                )
+            })??,
+        None => UnixStream::connect(path).await?,
+    };
+
+    Ok(Box::new(stream))
+}
@@ -0,0 +1,38 @@
+use crate::tx_engine::{OutputRow, TransactionEngine, TransactionError};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+impl TransactionEngine {
+    /// Like [`Self::process_streaming`], but writes each client update as a Server-Sent Event, i.e.
+    /// `data: {json}\n\n`, for browser dashboards. Every event is flushed as soon as it is written. Starts from an empty
+    /// state like [`Self::process`]
+    pub async fn process_sse<R: AsyncRead + Unpin + Send + Sync, W: AsyncWrite + Unpin>(
+        &mut self,
+        input: R,
+        mut output: W,
+    ) -> Result<(), TransactionError> {
+        self.clear();
+        let (updates_tx, mut updates_rx) =
+            tokio::sync::mpsc::channel::<OutputRow>(self.config.max_in_flight_rows.max(1));
+
+        let reader = self.send_updates(input, updates_tx);
+        let writer = async move {
+            while let Some(row) = updates_rx.recv().await {
+                output.write_all(&sse_event(&row)?).await?;
+                output.flush().await?;
+            }
+
+            Ok::<_, TransactionError>(())
+        };
+
+        // not try_join: if the reader fails, the writer must still drain the updates already emitted
+        let (read, written) = tokio::join!(reader, writer);
+        read.and(written)
+    }
+}
+
+fn sse_event(row: &OutputRow) -> Result<Vec<u8>, TransactionError> {
+    let mut event = b"data: ".to_vec();
+    serde_json::to_writer(&mut event, row).map_err(std::io::Error::from)?;
+    event.extend_from_slice(b"\n\n");
+    Ok(event)
+}
@@ -0,0 +1,143 @@
+use crate::tx_engine::parse::RawInputRow;
+use crate::tx_engine::schema::validate_headers;
+use crate::tx_engine::{
+    InputRow, LineTerminator, TransactionEngine, TransactionError, TransactionSummary,
+};
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+
+impl TransactionEngine {
+    /// Blocking counterpart of [`Self::process`] for callers without an async runtime, reading and writing through
+    /// `std::io` with the synchronous `csv` crate. Rows are applied exactly as by [`Self::process`], but the input must
+    /// be UTF-8, only lines starting with `#` count as comments and the `shutdown` signal is not observed. Starts from
+    /// an empty state like [`Self::process`]
+    pub fn process_sync<R: Read, W: Write>(
+        &mut self,
+        input: R,
+        output: W,
+    ) -> Result<TransactionSummary, TransactionError> {
+        self.clear();
+        if let Err(err) = self.process_rows_sync(input) {
+            if self.config.flush_partial_output_on_error {
+                // the processing error takes precedence over any error while writing the partial output
+                let _ = self.write_balances_sync(output);
+            }
+
+            return Err(err);
+        }
+
+        self.write_balances_sync(output)?;
+        Ok(self.summary())
+    }
+
+    fn process_rows_sync<R: Read>(&mut self, input: R) -> Result<(), TransactionError> {
+        // whitespace around numeric fields would otherwise fail to parse before the normalizer sees the row
+        let trim = if self.config.normalize_input {
+            csv::Trim::All
+        } else {
+            csv::Trim::None
+        };
+
+        // use "flexible" to allow empty input fields for disputes, resolves, and chargebacks
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(trim)
+            .comment(self.config.skip_comment_lines.then_some(b'#'))
+            .from_reader(input);
+
+        let headers = reader.headers()?.clone();
+        if self.config.strict_mode {
+            let headers = validate_headers(&headers)?;
+            reader.set_headers(headers.iter().collect());
+        } else {
+            // serde would silently pick one of the duplicated columns
+            let mut seen = HashSet::new();
+            if let Some(name) = headers.iter().find(|name| !seen.insert(*name)) {
+                return Err(TransactionError::DuplicateHeader {
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        let mut rows = reader.into_deserialize::<RawInputRow>().peekable();
+        let mut rows_read = 0;
+        while let Some(result) = rows.next() {
+            if Self::row_limit_reached(&self.config, rows_read) {
+                return Ok(());
+            }
+            rows_read += 1;
+
+            match result
+                .map_err(TransactionError::from)
+                .and_then(InputRow::try_from)
+            {
+                Ok(row) => {
+                    self.process_input_row(row)?;
+                }
+
+                // only a row followed by more rows is a genuine error
+                Err(_) if self.config.ignore_partial_final_row && rows.peek().is_none() => {
+                    return Ok(())
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes every client's balances to the output, ended as configured by `output_trailing_newline` and
+    /// `output_trailing_blank_line`
+    fn write_balances_sync<W: Write>(&self, mut output: W) -> Result<(), TransactionError> {
+        self.check_config()?;
+        let mut rows = self
+            .clients
+            .iter()
+            .filter(|(_, row)| self.is_output(row))
+            .collect::<Vec<_>>();
+        self.sort_output(&mut rows);
+
+        let terminator = match self.config.output_line_terminator {
+            LineTerminator::Lf => csv::Terminator::Any(b'\n'),
+            LineTerminator::CrLf => csv::Terminator::CRLF,
+        };
+        let quote_style = match (
+            self.config.output_always_quote,
+            self.config.quote_numeric_output,
+        ) {
+            (true, true) => csv::QuoteStyle::Always,
+            (true, false) => csv::QuoteStyle::NonNumeric,
+            (false, _) => csv::QuoteStyle::Necessary,
+        };
+
+        let mut writer = csv::WriterBuilder::new()
+            .terminator(terminator)
+            .quote_style(quote_style)
+            .from_writer(Vec::new());
+        for (_, row) in rows {
+            writer.serialize(row.formatted(&self.config))?;
+        }
+        let mut written = writer
+            .into_inner()
+            .map_err(|err| TransactionError::Io(err.into_error()))?;
+
+        let line_ending = self.config.output_line_terminator.as_bytes();
+        let ended = self.config.output_trailing_newline || self.config.output_trailing_blank_line;
+        if !ended && written.ends_with(line_ending) {
+            written.truncate(written.len() - line_ending.len());
+        }
+        if self.config.output_trailing_blank_line {
+            written.extend_from_slice(line_ending);
+        }
+
+        // read back before anything reaches the output, so that invalid output is never written
+        if self.config.validate_output {
+            self.validate_output_sync(&String::from_utf8_lossy(&written))
+                .map_err(|errors| TransactionError::OutputValidationFailed { errors })?;
+        }
+
+        output.write_all(&written)?;
+        Ok(output.flush()?)
+    }
+}
@@ -0,0 +1,281 @@
+use crate::tx_engine::{
+    BalanceCorrection, CounterOverflow, Decimal, HistoryEvictionPolicy, HistoryKey, InputRow,
+    InvariantViolation, Transaction, TransactionEngine, TransactionEngineConfig, TransactionError,
+    TransactionType,
+};
+use std::sync::Arc;
+
+const DEPOSITS: &[u8] =
+    b"type,client,tx,amount\ndeposit,1,1,1.0\ndeposit,2,2,1.0\ndeposit,3,3,1.0\n";
+
+#[tokio::test]
+async fn clear_restores_configured_capacity() {
+    let mut engine = TransactionEngine::builder()
+        .with_client_capacity(0)
+        .with_history_capacity(0)
+        .build();
+    assert_eq!(engine.clients.capacity(), 0);
+
+    engine.process(DEPOSITS, tokio::io::sink()).await.unwrap();
+    assert!(engine.clients.capacity() >= 3);
+    assert!(engine.history.capacity() >= 3);
+
+    engine.clear();
+    assert_eq!(engine.clients.capacity(), 0);
+    assert_eq!(engine.history.capacity(), 0);
+}
+
+#[tokio::test]
+async fn engine_is_created_with_configured_capacity() {
+    let engine = TransactionEngine::builder()
+        .with_client_capacity(u16::MAX as usize)
+        .with_history_capacity(10)
+        .build();
+    assert!(engine.clients.capacity() >= u16::MAX as usize);
+    assert!(engine.history.capacity() >= 10);
+}
+
+fn engine_with_rows(
+    config: &Arc<TransactionEngineConfig>,
+    rows: &[(&str, u16, u32)],
+) -> TransactionEngine {
+    let mut engine = TransactionEngine::new(config.clone());
+    for &(tx_type, client, tx) in rows {
+        let amount = (tx_type == "deposit").then_some(Decimal::ONE);
+        engine
+            .process_input_row(InputRow::new(tx_type, client, tx, amount))
+            .unwrap();
+    }
+    engine
+}
+
+#[test]
+fn merge_keeps_counters_and_dispute_ages() {
+    let config = TransactionEngine::builder()
+        .with_dispute_expiry_rows(3)
+        .build()
+        .config;
+    // dispute of client 1 opened just now, dispute of client 2 one row ago
+    let mut engine = engine_with_rows(&config, &[("deposit", 1, 1), ("dispute", 1, 1)]);
+    let other = engine_with_rows(
+        &config,
+        &[("deposit", 2, 2), ("dispute", 2, 2), ("deposit", 2, 3)],
+    );
+
+    engine.merge(other).unwrap();
+    assert_eq!(engine.rows_processed, 5);
+    assert_eq!(engine.dispute_summary().opened, 2);
+    assert_eq!(engine.open_disputes().len(), 2);
+
+    // two more rows age client 2's dispute past the window, but not client 1's
+    for tx in 4..6 {
+        engine
+            .process_input_row(InputRow::new("deposit", 3, tx, Some(Decimal::ONE)))
+            .unwrap();
+    }
+    let open = engine.open_disputes();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].client, 1);
+    assert_eq!(engine.dispute_summary().resolved, 1);
+}
+
+#[test]
+fn merge_rejects_client_in_another_ledger() {
+    let config = TransactionEngine::builder()
+        .with_multi_ledger(true)
+        .build()
+        .config;
+    let mut engine = engine_with_rows(&config, &[("deposit", 1, 1)]);
+    let mut other = TransactionEngine::new(config.clone());
+    let mut row = InputRow::new("deposit", 1, 2, Some(Decimal::ONE));
+    row.ledger = Some("savings".to_string());
+    other.process_input_row(row).unwrap();
+
+    assert!(matches!(
+        engine.merge(other),
+        Err(TransactionError::DuplicateClient(1))
+    ));
+}
+
+#[test]
+fn saturated_counters_stay_at_their_maximum() {
+    let mut engine = engine_with_rows(&TransactionEngine::builder().build().config, &[]);
+    engine.rows_processed = u64::MAX;
+    engine
+        .process_input_row(InputRow::new("deposit", 1, 1, Some(Decimal::ONE)))
+        .unwrap();
+    assert_eq!(engine.rows_processed, u64::MAX);
+
+    let account = (1, String::new(), String::new());
+    engine.clients.get_mut(&account).unwrap().rejection_count = u32::MAX;
+    engine
+        .process_input_row(InputRow::new("withdrawal", 1, 2, Some(Decimal::TEN)))
+        .unwrap();
+    assert_eq!(engine.clients[&account].rejection_count, u32::MAX);
+
+    let client_row = engine.clients.get_mut(&account).unwrap();
+    client_row.transaction_count = u64::MAX;
+    client_row.dispute_activity_count = u32::MAX;
+    engine
+        .process_input_row(InputRow::new("dispute", 1, 1, None))
+        .unwrap();
+    assert_eq!(engine.clients[&account].transaction_count, u64::MAX);
+    assert_eq!(engine.clients[&account].dispute_activity_count, u32::MAX);
+    assert_eq!(engine.clients[&account].held, Decimal::ONE);
+}
+
+#[test]
+fn deposit_that_does_not_fit_in_the_history_is_not_counted() {
+    let config = TransactionEngine::builder()
+        .with_max_history_entries(1, HistoryEvictionPolicy::Reject)
+        .build()
+        .config;
+    let mut engine = engine_with_rows(&config, &[("deposit", 1, 1)]);
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("deposit", 1, 2, Some(Decimal::ONE))),
+        Err(TransactionError::HistoryFull { max: 1 })
+    ));
+    assert_eq!(engine.rows_processed, 1);
+
+    // a saturated counter was not incremented, so undoing the row must not decrement it
+    engine.rows_processed = u64::MAX;
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("deposit", 1, 3, Some(Decimal::ONE))),
+        Err(TransactionError::HistoryFull { max: 1 })
+    ));
+    assert_eq!(engine.rows_processed, u64::MAX);
+}
+
+#[test]
+fn overflowing_counter_is_an_error() {
+    let config = TransactionEngine::builder()
+        .with_counter_overflow(CounterOverflow::Error)
+        .with_track_chargeback_totals(true)
+        .build()
+        .config;
+    let mut engine = engine_with_rows(&config, &[("deposit", 1, 1)]);
+    engine.rows_processed = u64::MAX;
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("deposit", 1, 2, Some(Decimal::ONE))),
+        Err(TransactionError::CounterOverflow {
+            counter: "rows_processed"
+        })
+    ));
+
+    engine.rows_processed = 0;
+    let account = (1, String::new(), String::new());
+    engine.clients.get_mut(&account).unwrap().chargeback_count = u32::MAX;
+    engine
+        .process_input_row(InputRow::new("dispute", 1, 1, None))
+        .unwrap();
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("chargeback", 1, 1, None)),
+        Err(TransactionError::CounterOverflow {
+            counter: "chargeback_count"
+        })
+    ));
+    // counted before the funds move
+    assert_eq!(engine.clients[&account].held, Decimal::ONE);
+    assert!(!engine.clients[&account].locked);
+
+    engine.clients.get_mut(&account).unwrap().transaction_count = u64::MAX;
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("deposit", 1, 3, Some(Decimal::ONE))),
+        Err(TransactionError::CounterOverflow {
+            counter: "transaction_count"
+        })
+    ));
+}
+
+#[test]
+fn withdrawal_is_limited_by_available_funds_only() {
+    let mut engine = engine_with_rows(&TransactionEngine::builder().build().config, &[]);
+    engine
+        .process_input_row(InputRow::new("deposit", 1, 1, Some(Decimal::TEN)))
+        .unwrap();
+    // leaves the total below the available funds, which no transaction can do
+    engine
+        .apply_correction(1, BalanceCorrection::AdjustHeld(Decimal::from(-5)))
+        .unwrap();
+
+    engine
+        .process_input_row(InputRow::new("withdrawal", 1, 2, Some(Decimal::from(8))))
+        .unwrap();
+    let row = &engine.clients[&(1, String::new(), String::new())];
+    assert_eq!(row.available, Decimal::from(2));
+    assert_eq!(row.total, Decimal::from(-3));
+    assert_eq!(row.rejection_count, 0);
+}
+
+#[test]
+fn unbalanced_account_is_a_violation() {
+    let mut engine = engine_with_rows(&TransactionEngine::builder().build().config, &[]);
+    engine
+        .process_input_row(InputRow::new("deposit", 1, 1, Some(Decimal::TEN)))
+        .unwrap();
+    let account = (1, String::new(), String::new());
+    engine.clients.get_mut(&account).unwrap().total = Decimal::ONE;
+
+    assert_eq!(
+        engine.verify_invariants(),
+        Err(vec![InvariantViolation::Unbalanced {
+            account,
+            available: Decimal::TEN,
+            held: Decimal::ZERO,
+            total: Decimal::ONE,
+        }])
+    );
+}
+
+fn engine_with_two_open_disputes() -> TransactionEngine {
+    let mut engine = engine_with_rows(&TransactionEngine::builder().build().config, &[]);
+    for (tx, amount) in [(1, Decimal::TEN), (2, Decimal::from(5))] {
+        engine
+            .process_input_row(InputRow::new("deposit", 1, tx, Some(amount)))
+            .unwrap();
+        engine
+            .process_input_row(InputRow::new("dispute", 1, tx, None))
+            .unwrap();
+    }
+    engine
+}
+
+#[test]
+fn chargebacks_release_only_their_own_dispute_of_several() {
+    let mut engine = engine_with_two_open_disputes();
+    let account = (1, String::new(), String::new());
+
+    engine
+        .process_input_row(InputRow::new("chargeback", 1, 1, None))
+        .unwrap();
+    assert_eq!(engine.clients[&account].held, Decimal::from(5));
+    assert_eq!(engine.clients[&account].total, Decimal::from(5));
+
+    engine
+        .process_input_row(InputRow::new("chargeback", 1, 2, None))
+        .unwrap();
+    assert_eq!(engine.clients[&account].held, Decimal::ZERO);
+    assert_eq!(engine.clients[&account].total, Decimal::ZERO);
+}
+
+#[test]
+fn chargeback_checks_the_funds_held_for_its_own_dispute() {
+    let mut engine = engine_with_two_open_disputes();
+    let dispute_key = HistoryKey {
+        client: 1,
+        tx: 1,
+        tx_type: TransactionType::Dispute,
+    };
+    if let Some(Transaction::Dispute { held, .. }) = engine.history.get_mut(&dispute_key) {
+        *held = Some(Decimal::from(4));
+    }
+
+    // the account holds 15 in total, more than the 10 disputed, but only 4 for this dispute
+    assert!(matches!(
+        engine.process_input_row(InputRow::new("chargeback", 1, 1, None)),
+        Err(TransactionError::ChargebackAmountMismatch { tx: 1, expected, held })
+            if expected == Decimal::TEN && held == Decimal::from(4)
+    ));
+    let account = (1, String::new(), String::new());
+    assert_eq!(engine.clients[&account].held, Decimal::from(15));
+}
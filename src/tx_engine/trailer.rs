@@ -0,0 +1,109 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Holds back the line terminator ending the last row written so far, so that the end of the output can be chosen
+/// once everything has been written. Other bytes are passed straight through. A terminator split across writes is
+/// still recognized
+pub struct TrailingNewline<W> {
+    inner: W,
+    terminator: &'static [u8],
+    // Length of the prefix of the terminator held back at the end of the output so far
+    held: usize,
+    // Length of the held prefix already passed on while releasing it
+    released: usize,
+}
+
+impl<W: AsyncWrite + Unpin> TrailingNewline<W> {
+    pub fn new(inner: W, terminator: &'static [u8]) -> Self {
+        Self {
+            inner,
+            terminator,
+            held: 0,
+            released: 0,
+        }
+    }
+
+    /// Ends the output with the held terminator if `newline` is set, followed by an empty line if `blank_line` is set
+    pub async fn finish(mut self, newline: bool, blank_line: bool) -> io::Result<()> {
+        let held = &self.terminator[self.released..self.held];
+        // only part of a terminator is data rather than a line ending
+        if self.held < self.terminator.len() || newline || blank_line {
+            self.inner.write_all(held).await?;
+        }
+
+        if blank_line {
+            self.inner.write_all(self.terminator).await?;
+        }
+
+        self.inner.flush().await
+    }
+
+    /// Passes on the held bytes, which turned out not to end the output
+    fn poll_release(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.released < self.held {
+            let pending = &self.terminator[self.released..self.held];
+            match ready!(Pin::new(&mut self.inner).poll_write(cx, pending))? {
+                0 => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                written => self.released += written,
+            }
+        }
+
+        self.held = 0;
+        self.released = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for TrailingNewline<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        // the rest of a terminator whose start is held
+        if this.released == 0 && this.held < this.terminator.len() {
+            let rest = &this.terminator[this.held..];
+            let continued = rest.len().min(buf.len());
+            if this.held > 0 && buf[..continued] == rest[..continued] {
+                this.held += continued;
+                return Poll::Ready(Ok(continued));
+            }
+        }
+
+        // more output follows, so the held bytes were not the end
+        ready!(this.poll_release(cx))?;
+
+        let held = (1..=this.terminator.len())
+            .rev()
+            .find(|&len| buf.ends_with(&this.terminator[..len]))
+            .unwrap_or(0);
+        let (data, _) = buf.split_at(buf.len() - held);
+        if data.is_empty() {
+            this.held = held;
+            return Poll::Ready(Ok(buf.len()));
+        }
+
+        let written = ready!(Pin::new(&mut this.inner).poll_write(cx, data))?;
+        if written == data.len() {
+            this.held = held;
+            Poll::Ready(Ok(buf.len()))
+        } else {
+            Poll::Ready(Ok(written))
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
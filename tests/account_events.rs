@@ -0,0 +1,123 @@
+use transactions_demo::tx_engine::{AccountEvent, Balances, Decimal, TransactionEngine};
+
+const INPUT: &str = "\
+type,client,tx,amount,target_client
+deposit,1,1,10.0,
+deposit,1,2,5.0,
+withdrawal,1,3,3.0,
+withdrawal,1,4,50.0,
+transfer,1,5,2.0,2
+dispute,1,1,,
+resolve,1,1,,
+dispute,1,2,,
+chargeback,1,2,,
+";
+
+fn balances(available: i64, held: i64, total: i64) -> Balances {
+    Balances {
+        available: Decimal::from(available),
+        held: Decimal::from(held),
+        total: Decimal::from(total),
+    }
+}
+
+#[tokio::test]
+async fn events_are_recorded_in_order() {
+    let mut engine = TransactionEngine::builder()
+        .with_track_account_events(true)
+        .build();
+    engine.process_str(INPUT).await.unwrap();
+
+    // the rejected withdrawal is not an event
+    assert_eq!(
+        engine.client_event_sequence(1),
+        [
+            AccountEvent::Deposited {
+                tx: 1,
+                amount: Decimal::from(10),
+                balance_after: balances(10, 0, 10)
+            },
+            AccountEvent::Deposited {
+                tx: 2,
+                amount: Decimal::from(5),
+                balance_after: balances(15, 0, 15)
+            },
+            AccountEvent::Withdrew {
+                tx: 3,
+                amount: Decimal::from(3),
+                balance_after: balances(12, 0, 12)
+            },
+            AccountEvent::TransferSent {
+                tx: 5,
+                amount: Decimal::from(2),
+                balance_after: balances(10, 0, 10)
+            },
+            AccountEvent::DisputeOpened {
+                tx: 1,
+                amount: Decimal::from(10),
+                held_after: Decimal::from(10)
+            },
+            AccountEvent::DisputeResolved {
+                tx: 1,
+                amount: Decimal::from(10),
+                held_after: Decimal::ZERO
+            },
+            AccountEvent::DisputeOpened {
+                tx: 2,
+                amount: Decimal::from(5),
+                held_after: Decimal::from(5)
+            },
+            AccountEvent::AccountChargebacked {
+                tx: 2,
+                amount: Decimal::from(5)
+            },
+            AccountEvent::AccountLocked,
+        ]
+    );
+    assert_eq!(
+        engine.client_event_sequence(2),
+        [AccountEvent::TransferReceived {
+            tx: 5,
+            amount: Decimal::from(2),
+            balance_after: balances(2, 0, 2)
+        }]
+    );
+}
+
+#[tokio::test]
+async fn replayed_events_rebuild_each_account() {
+    let mut engine = TransactionEngine::builder()
+        .with_track_account_events(true)
+        .build();
+    engine.process_str(INPUT).await.unwrap();
+
+    for client in engine.client_ids() {
+        let replayed =
+            TransactionEngine::replay_events(client, engine.client_event_sequence(client));
+        let account = engine.query_client(client).unwrap();
+        assert_eq!(
+            (
+                replayed.available(),
+                replayed.held(),
+                replayed.total(),
+                replayed.locked()
+            ),
+            (
+                account.available(),
+                account.held(),
+                account.total(),
+                account.locked()
+            ),
+            "client {}",
+            client
+        );
+    }
+}
+
+#[tokio::test]
+async fn events_are_not_recorded_by_default() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+
+    assert!(engine.client_event_sequence(1).is_empty());
+}
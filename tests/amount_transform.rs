@@ -0,0 +1,52 @@
+use transactions_demo::tx_engine::{Decimal, TransactionEngine, TransactionError};
+
+fn cents_engine() -> TransactionEngine {
+    TransactionEngine::builder()
+        .with_amount_transform(|cents| cents.checked_div(Decimal::from(100)))
+        .build()
+}
+
+#[tokio::test]
+async fn integer_cents_are_converted_to_decimals() {
+    let mut engine = cents_engine();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,1050\ndeposit,1,2,7\nwithdrawal,1,3,125\n")
+        .await
+        .unwrap();
+
+    // 10.50 + 0.07 - 1.25
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,9.32,0,9.32,false\n"
+    );
+}
+
+#[tokio::test]
+async fn disputes_hold_the_converted_amount() {
+    let mut engine = cents_engine();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,250\ndeposit,1,2,1\ndispute,1,1,\n")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0.01,2.50,2.51,false\n"
+    );
+}
+
+#[tokio::test]
+async fn failed_transform_is_an_invalid_amount() {
+    let mut engine = TransactionEngine::builder()
+        .with_amount_transform(|amount| (amount <= Decimal::from(100)).then_some(amount))
+        .build();
+    let err = engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,50\ndeposit,1,2,150\n")
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        err,
+        TransactionError::InvalidAmount { tx: 2, amount } if amount == Decimal::from(150)
+    ));
+}
@@ -0,0 +1,76 @@
+use arrow::array::{Array, BooleanArray, Decimal128Array, UInt16Array};
+use arrow::datatypes::DataType;
+use arrow::ipc::reader::FileReader;
+use std::io::Cursor;
+use transactions_demo::tx_engine::TransactionEngine;
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,2,1,10.0
+deposit,1,2,1.2345
+withdrawal,2,3,2.5
+deposit,3,4,4.0
+dispute,3,4,
+deposit,4,5,3.0
+dispute,4,5,
+chargeback,4,5,
+";
+
+#[tokio::test]
+async fn ipc_output_reads_back_with_schema_and_values() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+    let mut ipc = Vec::new();
+    engine.write_arrow_ipc(&mut ipc).unwrap();
+
+    let reader = FileReader::try_new(Cursor::new(ipc), None).unwrap();
+    let schema = reader.schema();
+    let columns = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name().as_str(), field.data_type().clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        columns,
+        [
+            ("client", DataType::UInt16),
+            ("available", DataType::Decimal128(18, 4)),
+            ("held", DataType::Decimal128(18, 4)),
+            ("total", DataType::Decimal128(18, 4)),
+            ("locked", DataType::Boolean),
+        ]
+    );
+
+    let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    let decimals = |index: usize| {
+        let column = batch
+            .column(index)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        (0..column.len())
+            .map(|row| column.value_as_string(row))
+            .collect::<Vec<_>>()
+    };
+
+    let clients = batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<UInt16Array>()
+        .unwrap();
+    assert_eq!(clients.values(), &[1, 2, 3, 4]);
+    assert_eq!(decimals(1), ["1.2345", "7.5000", "0.0000", "0.0000"]);
+    assert_eq!(decimals(2), ["0.0000", "0.0000", "4.0000", "0.0000"]);
+    assert_eq!(decimals(3), ["1.2345", "7.5000", "4.0000", "0.0000"]);
+    let locked = batch
+        .column(4)
+        .as_any()
+        .downcast_ref::<BooleanArray>()
+        .unwrap();
+    assert_eq!(
+        locked.iter().collect::<Vec<_>>(),
+        [Some(false), Some(false), Some(false), Some(true)]
+    );
+}
@@ -0,0 +1,49 @@
+use transactions_demo::tx_engine::{Decimal, TransactionEngine, TransactionError};
+
+#[tokio::test]
+async fn loaded_checkpoint_carries_on_with_open_disputes() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    engine
+        .process_str(
+            "type,client,tx,amount,ledger\ndeposit,1,1,10.0,savings\ndeposit,2,2,4.0,\ndispute,1,1,,\n",
+        )
+        .await
+        .unwrap();
+    let mut checkpoint = Vec::new();
+    engine.save_checkpoint(&mut checkpoint).unwrap();
+
+    let mut restarted = TransactionEngine::builder().with_multi_ledger(true).build();
+    restarted.load_checkpoint(checkpoint.as_slice()).unwrap();
+    assert_eq!(
+        restarted.to_csv_string().await.unwrap(),
+        engine.to_csv_string().await.unwrap()
+    );
+    assert_eq!(restarted.open_disputes().len(), 1);
+
+    // the chargeback reaches the ledger of the disputed deposit
+    restarted
+        .feed(b"type,client,tx,amount\nchargeback,1,1,\n")
+        .await
+        .unwrap();
+    assert_eq!(
+        restarted.to_csv_string().await.unwrap(),
+        "client,ledger,available,held,total,locked\n1,savings,0,0,0,true\n2,,4,0,4,false\n"
+    );
+    assert_eq!(restarted.verify_invariants(), Ok(()));
+}
+
+#[tokio::test]
+async fn unreadable_checkpoint_keeps_the_state() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\n")
+        .await
+        .unwrap();
+
+    let result = engine.load_checkpoint(b"{\"accounts\":".as_slice());
+    assert!(matches!(result, Err(TransactionError::Checkpoint(_))));
+    assert_eq!(
+        engine.query_client(1).map(|row| row.available()),
+        Some(Decimal::TEN)
+    );
+}
@@ -0,0 +1,37 @@
+use tokio::io::AsyncReadExt;
+use transactions_demo::tx_engine::{HashingReader, TransactionEngine};
+
+#[tokio::test]
+async fn digest_matches_known_sha256() {
+    let mut reader = HashingReader::new("abc".as_bytes());
+    // nothing was read yet, so this is the digest of the empty input
+    assert_eq!(
+        reader.hex_digest(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    let mut read = String::new();
+    reader.read_to_string(&mut read).await.unwrap();
+    assert_eq!(read, "abc");
+    assert_eq!(
+        reader.hex_digest(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[tokio::test]
+async fn digest_covers_the_processed_input() {
+    let input = "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,2.5\n";
+    let mut reader = HashingReader::new(input.as_bytes());
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process(&mut reader, tokio::io::sink())
+        .await
+        .unwrap();
+
+    // sha256sum of the input
+    assert_eq!(
+        reader.hex_digest(),
+        "f490252861087ccd008c467ff980063dd586b25bb4db948ffeddfd9c15bfd59c"
+    );
+}
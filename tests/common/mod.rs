@@ -0,0 +1,20 @@
+//! Helpers shared by the integration tests. Each test crate only uses some of them
+#![allow(dead_code)]
+
+use transactions_demo::tx_engine::{RejectionReason, TransactionEngine};
+
+/// Processes `input` and returns the balances output
+pub async fn process(engine: &mut TransactionEngine, input: &str) -> String {
+    let mut output = Vec::new();
+    engine.process(input.as_bytes(), &mut output).await.unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+/// The client, tx and reason of every rejection, in the order they were refused
+pub fn rejection_reasons(engine: &TransactionEngine) -> Vec<(u16, u32, RejectionReason)> {
+    engine
+        .rejections()
+        .iter()
+        .map(|rejection| (rejection.client, rejection.tx, rejection.reason))
+        .collect()
+}
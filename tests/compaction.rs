@@ -0,0 +1,76 @@
+use transactions_demo::tx_engine::{Decimal, RejectionReason, TransactionEngine};
+
+mod common;
+use common::rejection_reasons;
+
+const RESOLVED: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,1,2,5.0
+dispute,1,1,
+resolve,1,1,
+";
+
+const REDISPUTES: &str = "type,client,tx,amount\ndispute,1,1,\ndispute,1,2,\n";
+
+async fn feed(engine: &mut TransactionEngine, input: &str) {
+    engine.feed(input.as_bytes()).await.unwrap();
+    engine.finish().await.unwrap();
+}
+
+#[tokio::test]
+async fn compacted_transaction_can_no_longer_be_disputed() {
+    let mut engine = TransactionEngine::builder().build();
+    feed(&mut engine, RESOLVED).await;
+    engine.compact_history();
+    feed(&mut engine, REDISPUTES).await;
+
+    // tx 2 was never disputed, so it is kept
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::TransactionNotFound)]
+    );
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::new(5, 0));
+}
+
+#[tokio::test]
+async fn concluded_dispute_can_be_raised_again_without_compaction() {
+    let mut engine = TransactionEngine::builder().build();
+    feed(&mut engine, RESOLVED).await;
+    feed(&mut engine, REDISPUTES).await;
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::new(15, 0));
+}
+
+#[tokio::test]
+async fn reopened_dispute_is_kept_until_it_concludes() {
+    let mut engine = TransactionEngine::builder().build();
+    feed(
+        &mut engine,
+        "type,client,tx,amount\ndeposit,1,1,10.0\ndispute,1,1,\nresolve,1,1,\ndispute,1,1,\n",
+    )
+    .await;
+    engine.compact_history();
+    feed(&mut engine, "type,client,tx,amount\nchargeback,1,1,\n").await;
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0,0,0,true\n"
+    );
+}
+
+#[tokio::test]
+async fn history_is_compacted_automatically() {
+    let mut engine = TransactionEngine::builder().with_auto_compact(1).build();
+    engine
+        .process_str(&format!("{}dispute,1,1,\n", RESOLVED))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::TransactionNotFound)]
+    );
+}
@@ -0,0 +1,108 @@
+use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+use std::path::PathBuf;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use transactions_demo::tx_engine::{decompress_detected, Compression, TransactionEngine};
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.0
+withdrawal,1,3,2.5
+dispute,2,2,
+";
+
+async fn compress<E: AsyncWrite + Unpin>(mut encoder: E) -> E {
+    encoder.write_all(INPUT.as_bytes()).await.unwrap();
+    encoder.shutdown().await.unwrap();
+    encoder
+}
+
+async fn gzip() -> Vec<u8> {
+    compress(GzipEncoder::new(Vec::new())).await.into_inner()
+}
+
+async fn zstd() -> Vec<u8> {
+    compress(ZstdEncoder::new(Vec::new())).await.into_inner()
+}
+
+async fn plain_output() -> Vec<u8> {
+    let mut output = Vec::new();
+    TransactionEngine::builder()
+        .build()
+        .process(INPUT.as_bytes(), &mut output)
+        .await
+        .unwrap();
+    output
+}
+
+/// Processes the bytes from a file, as compression is only detected for inputs opened by the engine
+async fn output_of_file(name: &str, contents: &[u8]) -> Vec<u8> {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("tx-compression-{}-{}", std::process::id(), name));
+    tokio::fs::write(&path, contents).await.unwrap();
+
+    let mut output = Vec::new();
+    let result = TransactionEngine::builder()
+        .with_detect_compression(true)
+        .build()
+        .process_file(&path, &mut output)
+        .await;
+    tokio::fs::remove_file(&path).await.unwrap();
+    result.unwrap();
+    output
+}
+
+#[tokio::test]
+async fn formats_are_detected_by_their_magic_bytes() {
+    for (input, expected) in [
+        (INPUT.as_bytes().to_vec(), Compression::None),
+        (gzip().await, Compression::Gzip),
+        (zstd().await, Compression::Zstd),
+    ] {
+        let (compression, _) = decompress_detected(std::io::Cursor::new(input))
+            .await
+            .unwrap();
+        assert_eq!(compression, expected);
+    }
+}
+
+#[tokio::test]
+async fn gzip_input_gives_the_plain_output() {
+    assert_eq!(
+        output_of_file("gzip", &gzip().await).await,
+        plain_output().await
+    );
+}
+
+#[tokio::test]
+async fn zstd_input_gives_the_plain_output() {
+    assert_eq!(
+        output_of_file("zstd", &zstd().await).await,
+        plain_output().await
+    );
+}
+
+#[tokio::test]
+async fn uncompressed_input_passes_through() {
+    assert_eq!(
+        output_of_file("plain", INPUT.as_bytes()).await,
+        plain_output().await
+    );
+}
+
+#[tokio::test]
+async fn concatenated_gzip_members_are_read_in_full() {
+    let (first, second) = INPUT.split_at(INPUT.find("withdrawal").unwrap());
+    let mut input = Vec::new();
+    for part in [first, second] {
+        let mut encoder = GzipEncoder::new(Vec::new());
+        encoder.write_all(part.as_bytes()).await.unwrap();
+        encoder.shutdown().await.unwrap();
+        input.extend(encoder.into_inner());
+    }
+
+    assert_eq!(
+        output_of_file("members", &input).await,
+        plain_output().await
+    );
+}
@@ -0,0 +1,229 @@
+use transactions_demo::tx_engine::{
+    ConfigError, Decimal, HistoryEvictionPolicy, RoundAmountRule, TransactionEngine,
+    TransactionEngineBuilder, TransactionEngineConfig, TransactionError, TransactionType,
+};
+
+const INPUT: &str = "type,client,tx,amount\ndeposit,1,1,1.0\n";
+
+fn config_errors(builder: TransactionEngineBuilder) -> Vec<ConfigError> {
+    match builder.try_build() {
+        Err(TransactionError::InvalidConfig { errors }) => errors,
+        Err(err) => panic!("expected an invalid config, got {:?}", err),
+        Ok(_) => panic!("expected an invalid config"),
+    }
+}
+
+#[test]
+fn default_config_is_valid() {
+    assert_eq!(TransactionEngineConfig::default().validate(), Ok(()));
+    assert!(TransactionEngine::builder().try_build().is_ok());
+}
+
+#[test]
+fn negative_limits_are_rejected() {
+    let errors = config_errors(
+        TransactionEngine::builder()
+            .with_max_deposit_amount(Decimal::from(-1))
+            .with_max_withdrawal_amount(Decimal::from(-2))
+            .with_held_zero_threshold(Decimal::from(-3))
+            .with_deposit_dedup_tolerance(Decimal::from(-4))
+            .with_max_amount(TransactionType::Transfer, Decimal::from(-5)),
+    );
+
+    assert_eq!(
+        errors,
+        [
+            ConfigError::NegativeValue {
+                setting: "max_deposit_amount",
+                value: Decimal::from(-1)
+            },
+            ConfigError::NegativeValue {
+                setting: "max_withdrawal_amount",
+                value: Decimal::from(-2)
+            },
+            ConfigError::NegativeValue {
+                setting: "held_zero_threshold",
+                value: Decimal::from(-3)
+            },
+            ConfigError::NegativeValue {
+                setting: "deposit_dedup_tolerance",
+                value: Decimal::from(-4)
+            },
+            ConfigError::NegativeValue {
+                setting: "max_amount_by_type",
+                value: Decimal::from(-5)
+            },
+        ]
+    );
+}
+
+#[test]
+fn empty_time_window_is_rejected() {
+    #[allow(clippy::reversed_empty_ranges)]
+    let errors = config_errors(TransactionEngine::builder().with_time_window(20..=10));
+    assert_eq!(
+        errors,
+        [ConfigError::EmptyTimeWindow { start: 20, end: 10 }]
+    );
+}
+
+#[test]
+fn skipping_every_row_is_rejected() {
+    let errors = config_errors(
+        TransactionEngine::builder()
+            .with_max_rows(5)
+            .with_skip_rows(5),
+    );
+    assert_eq!(
+        errors,
+        [ConfigError::SkipsEveryRow {
+            skip_rows: 5,
+            max_rows: 5
+        }]
+    );
+
+    // one row is left to process
+    assert!(TransactionEngine::builder()
+        .with_max_rows(5)
+        .with_skip_rows(4)
+        .try_build()
+        .is_ok());
+}
+
+#[test]
+fn alias_to_an_unknown_type_is_rejected() {
+    let errors = config_errors(TransactionEngine::builder().with_type_alias("credit", "deposti"));
+    assert_eq!(
+        errors,
+        [ConfigError::UnknownAliasTarget {
+            alias: "credit".to_string(),
+            target: "deposti".to_string()
+        }]
+    );
+}
+
+#[test]
+fn invalid_output_columns_are_rejected() {
+    let errors = config_errors(
+        TransactionEngine::builder().with_output_columns(["client", "total", "client"]),
+    );
+    assert_eq!(
+        errors,
+        [ConfigError::DuplicateOutputColumn {
+            name: "client".to_string()
+        }]
+    );
+
+    let errors =
+        config_errors(TransactionEngine::builder().with_output_columns(Vec::<String>::new()));
+    assert_eq!(errors, [ConfigError::NoOutputColumns]);
+}
+
+#[test]
+fn low_balance_handler_without_threshold_is_rejected() {
+    let errors = config_errors(TransactionEngine::builder().on_low_balance(|_| {}));
+    assert_eq!(errors, [ConfigError::LowBalanceHandlerWithoutThreshold]);
+
+    assert!(TransactionEngine::builder()
+        .on_low_balance(|_| {})
+        .with_low_balance_threshold(Decimal::ONE)
+        .try_build()
+        .is_ok());
+}
+
+#[test]
+fn fraud_rejection_without_rules_is_rejected() {
+    let errors = config_errors(TransactionEngine::builder().with_reject_suspected_fraud(true));
+    assert_eq!(errors, [ConfigError::FraudRejectionWithoutRules]);
+
+    assert!(TransactionEngine::builder()
+        .with_reject_suspected_fraud(true)
+        .with_fraud_rule(RoundAmountRule::default())
+        .try_build()
+        .is_ok());
+}
+
+#[test]
+fn overdraft_limit_with_min_available_is_rejected() {
+    let errors = config_errors(
+        TransactionEngine::builder()
+            .with_overdraft_limit(Decimal::ONE)
+            .with_min_available(Decimal::from(-1)),
+    );
+    assert_eq!(errors, [ConfigError::OverdraftLimitWithMinAvailable]);
+}
+
+#[test]
+fn history_limit_must_be_usable() {
+    let errors = config_errors(
+        TransactionEngine::builder().with_max_history_entries(0, HistoryEvictionPolicy::EvictLru),
+    );
+    assert_eq!(errors, [ConfigError::NoHistoryEntries]);
+
+    let config = TransactionEngineConfig {
+        history_eviction_policy: HistoryEvictionPolicy::EvictLru,
+        ..TransactionEngineConfig::default()
+    };
+    assert_eq!(
+        config.validate(),
+        Err(vec![ConfigError::EvictionWithoutHistoryLimit])
+    );
+}
+
+#[test]
+fn every_problem_is_reported() {
+    let errors = config_errors(
+        TransactionEngine::builder()
+            .with_reject_suspected_fraud(true)
+            .with_output_columns(["balance"]),
+    );
+    assert_eq!(
+        errors,
+        [
+            ConfigError::UnknownOutputColumn {
+                name: "balance".to_string()
+            },
+            ConfigError::FraudRejectionWithoutRules,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn built_engine_checks_the_config_before_processing() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_deposit_amount(Decimal::from(-1))
+        .build();
+
+    let mut output = Vec::new();
+    let result = engine.process(INPUT.as_bytes(), &mut output).await;
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::NegativeValue { setting: "max_deposit_amount", value: Decimal::from(-1) }]
+    ));
+    assert!(output.is_empty());
+
+    assert!(matches!(
+        engine.process_str(INPUT).await,
+        Err(TransactionError::InvalidConfig { .. })
+    ));
+    assert!(engine.client_ids().is_empty());
+}
+
+#[tokio::test]
+async fn built_engine_checks_the_config_before_writing_empty_input() {
+    let mut engine = TransactionEngine::builder()
+        .with_output_columns(["client", "balance"])
+        .build();
+
+    let mut output = Vec::new();
+    let result = engine
+        .process("type,client,tx,amount\n".as_bytes(), &mut output)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::UnknownOutputColumn { name: "balance".to_string() }]
+    ));
+    assert!(output.is_empty());
+}
@@ -0,0 +1,50 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+const EARLIER: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.0
+deposit,3,3,1.0
+deposit,4,4,6.0
+";
+
+/// Client 1 withdraws, client 2 is charged back, client 3 is gone, client 4 is unchanged and client 5 is new
+const LATER: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.0
+deposit,4,4,6.0
+withdrawal,1,5,3.0
+dispute,2,2,
+chargeback,2,2,
+deposit,5,6,2.5
+";
+
+async fn delta(earlier: &str, later: &str) -> String {
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    engine
+        .process_delta(earlier.as_bytes(), later.as_bytes(), &mut output)
+        .await
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn delta_of_two_inputs_lists_the_changed_clients() {
+    assert_eq!(
+        delta(EARLIER, LATER).await,
+        "\
+client,available_delta,held_delta,total_delta,was_locked,locked
+1,-3,0,-3,false,false
+2,-5,0,-5,false,true
+3,-1,0,-1,false,false
+5,2.5,0,2.5,false,false
+"
+    );
+}
+
+#[tokio::test]
+async fn delta_of_identical_inputs_is_empty() {
+    assert_eq!(delta(EARLIER, EARLIER).await, "");
+}
@@ -0,0 +1,61 @@
+use transactions_demo::tx_engine::{Decimal, TransactionEngine};
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+dispute,1,1,
+deposit,2,2,1.0
+deposit,2,3,1.0
+deposit,2,4,1.0
+";
+
+#[tokio::test]
+async fn dispute_open_past_the_window_is_resolved() {
+    let mut engine = TransactionEngine::builder()
+        .with_dispute_expiry_rows(3)
+        .build();
+    let mut output = Vec::new();
+    engine.process(INPUT.as_bytes(), &mut output).await.unwrap();
+
+    // the third row after the dispute released the held funds
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n2,3,0,3,false\n"
+    );
+    assert!(engine.open_disputes().is_empty());
+    let summary = engine.dispute_summary();
+    assert_eq!(summary.resolved, 1);
+    assert_eq!(summary.resolved_amount, Decimal::new(10, 0));
+}
+
+#[tokio::test]
+async fn dispute_within_the_window_stays_open() {
+    let mut engine = TransactionEngine::builder()
+        .with_dispute_expiry_rows(4)
+        .build();
+    engine
+        .process(INPUT.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.held(), Decimal::new(10, 0));
+    assert_eq!(account.available(), Decimal::ZERO);
+    assert_eq!(engine.open_disputes().len(), 1);
+}
+
+#[tokio::test]
+async fn expired_dispute_can_no_longer_be_charged_back() {
+    let mut engine = TransactionEngine::builder()
+        .with_dispute_expiry_rows(3)
+        .build();
+    let input = format!("{}chargeback,1,1,\n", INPUT);
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.total(), Decimal::new(10, 0));
+    assert!(!account.locked());
+}
@@ -0,0 +1,246 @@
+use transactions_demo::tx_engine::{
+    Decimal, LockedDisputePolicy, RejectionReason, TransactionEngine,
+};
+
+mod common;
+use common::rejection_reasons;
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,1,2,5.0
+deposit,2,3,20.0
+deposit,3,4,7.0
+dispute,1,1,
+dispute,1,1,
+resolve,1,1,
+dispute,1,2,
+chargeback,1,2,
+dispute,2,3,
+dispute,2,3,
+dispute,3,4,
+";
+
+#[tokio::test]
+async fn dispute_summary_counts_every_outcome() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+
+    let summary = engine.dispute_summary();
+    assert_eq!(
+        (summary.opened, summary.opened_amount),
+        (4, Decimal::new(42, 0))
+    );
+    assert_eq!(
+        (summary.resolved, summary.resolved_amount),
+        (1, Decimal::new(10, 0))
+    );
+    assert_eq!(
+        (summary.charged_back, summary.charged_back_amount),
+        (1, Decimal::new(5, 0))
+    );
+    assert_eq!(
+        (summary.open, summary.open_amount),
+        (2, Decimal::new(27, 0))
+    );
+}
+
+#[tokio::test]
+async fn dispute_of_a_disputed_transaction_is_rejected() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [
+            (1, 1, RejectionReason::AlreadyDisputed),
+            (2, 3, RejectionReason::AlreadyDisputed)
+        ]
+    );
+
+    // the funds were only held once, so resolving the dispute released all of them
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::ZERO);
+    assert_eq!(engine.query_client(2).unwrap().held(), Decimal::new(20, 0));
+    assert_eq!(engine.verify_invariants(), Ok(()));
+}
+
+#[tokio::test]
+async fn dispute_with_a_matching_claimed_amount_is_applied() {
+    let mut engine = TransactionEngine::builder()
+        .with_match_dispute_amounts(true)
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\ndispute,1,1,10.0\n")
+        .await
+        .unwrap();
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::new(10, 0));
+}
+
+#[tokio::test]
+async fn dispute_with_a_different_claimed_amount_is_rejected() {
+    let input = "type,client,tx,amount\ndeposit,1,1,10.0\ndispute,1,1,12.5\n";
+    let mut engine = TransactionEngine::builder()
+        .with_match_dispute_amounts(true)
+        .build();
+    engine.process_str(input).await.unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::DisputeAmountMismatch)]
+    );
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::ZERO);
+
+    // the claimed amount is ignored unless matching is enabled
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(input).await.unwrap();
+    assert!(engine.rejections().is_empty());
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::new(10, 0));
+}
+
+/// Locks client 1 through a chargeback of tx 1, then disputes tx 2
+const LOCKED_DISPUTE: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,1,2,5.0
+dispute,1,1,
+chargeback,1,1,
+dispute,1,2,
+";
+
+#[tokio::test]
+async fn dispute_of_a_locked_account_is_rejected_by_default() {
+    let mut engine = TransactionEngine::builder()
+        .with_locked_dispute_policy(LockedDisputePolicy::Ignore)
+        .build();
+    engine.process_str(LOCKED_DISPUTE).await.unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 2, RejectionReason::AccountLocked)]
+    );
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,5,0,5,true\n"
+    );
+}
+
+#[tokio::test]
+async fn dispute_of_a_locked_account_is_applied_when_processing() {
+    let mut engine = TransactionEngine::builder()
+        .with_locked_dispute_policy(LockedDisputePolicy::Process)
+        .build();
+    engine.process_str(LOCKED_DISPUTE).await.unwrap();
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0,5,5,true\n"
+    );
+}
+
+#[tokio::test]
+async fn resolving_an_undisputed_transaction_is_rejected() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\nresolve,1,1,\ndispute,1,1,\nresolve,1,1,\nresolve,1,1,\n")
+        .await
+        .unwrap();
+
+    // before the dispute and again once it was resolved
+    assert_eq!(
+        rejection_reasons(&engine),
+        [
+            (1, 1, RejectionReason::NotDisputed),
+            (1, 1, RejectionReason::NotDisputed)
+        ]
+    );
+    assert_eq!(
+        engine.query_client(1).unwrap().available(),
+        Decimal::new(10, 0)
+    );
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn resolving_an_unknown_transaction_is_ignored() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\nresolve,1,2,\nresolve,2,1,\n")
+        .await
+        .unwrap();
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(
+        engine.query_client(1).unwrap().available(),
+        Decimal::new(10, 0)
+    );
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn disputes_beyond_the_open_limit_are_rejected() {
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,1,2,2.0
+deposit,1,3,4.0
+deposit,2,4,8.0
+dispute,1,1,
+dispute,1,2,
+dispute,1,3,
+dispute,2,4,
+resolve,1,1,
+dispute,1,3,
+";
+    let mut engine = TransactionEngine::builder()
+        .with_max_open_disputes_per_client(2)
+        .build();
+    engine.process_str(input).await.unwrap();
+
+    // the limit is per client, and resolving a dispute makes room for another
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 3, RejectionReason::TooManyOpenDisputes)]
+    );
+    assert_eq!(engine.query_client(1).unwrap().held(), Decimal::new(6, 0));
+    assert_eq!(engine.query_client(2).unwrap().held(), Decimal::new(8, 0));
+}
+
+/// Client 1 deposits 100 and withdraws 60 of it before disputing the deposit
+const PARTLY_WITHDRAWN: &str = "\
+type,client,tx,amount
+deposit,1,1,100.0
+withdrawal,1,2,60.0
+dispute,1,1,
+";
+
+#[tokio::test]
+async fn dispute_of_a_partly_withdrawn_deposit_holds_the_full_amount() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(PARTLY_WITHDRAWN).await.unwrap();
+
+    assert!(engine.rejections().is_empty());
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,-60,100,40,false\n"
+    );
+}
+
+#[tokio::test]
+async fn dispute_exceeding_the_total_is_rejected_when_configured() {
+    let mut engine = TransactionEngine::builder()
+        .with_reject_disputes_exceeding_total(true)
+        .build();
+    engine.process_str(PARTLY_WITHDRAWN).await.unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::DisputeExceedsTotal)]
+    );
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,40,0,40,false\n"
+    );
+}
@@ -0,0 +1,59 @@
+use transactions_demo::tx_engine::{InputEncoding, TransactionEngine, TransactionError};
+
+const HEADER: &[u8] = b"type,client,tx,amount,category\n";
+
+/// Processes `input` as Windows-1252 and returns the offset of the reported invalid byte
+async fn invalid_byte_offset(input: &[u8]) -> u64 {
+    let mut engine = TransactionEngine::builder()
+        .with_input_encoding(InputEncoding::Windows1252)
+        .build();
+    match engine.process_bytes(input).await {
+        Err(TransactionError::EncodingError {
+            byte_offset,
+            encoding,
+        }) => {
+            assert_eq!(encoding, InputEncoding::Windows1252);
+            byte_offset
+        }
+        other => panic!("expected an encoding error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn undefined_windows_1252_byte_is_reported_with_its_offset() {
+    let mut input = HEADER.to_vec();
+    input.extend_from_slice(b"deposit,1,1,10.0,caf\xe9\ndeposit,1,2,5.0,\x81\n");
+
+    assert_eq!(invalid_byte_offset(&input).await, (input.len() - 2) as u64);
+}
+
+#[tokio::test]
+async fn offset_counts_the_bytes_of_earlier_chunks() {
+    let mut input = HEADER.to_vec();
+    for tx in 1..=1_000 {
+        input.extend_from_slice(format!("deposit,1,{},1.0,retail\n", tx).as_bytes());
+    }
+    let offset = input.len() as u64 + 19;
+    input.extend_from_slice(b"deposit,1,1001,1.0,\x9d\n");
+
+    assert!(
+        offset > 8 * 1024,
+        "the invalid byte is past the first chunk"
+    );
+    assert_eq!(invalid_byte_offset(&input).await, offset);
+}
+
+#[tokio::test]
+async fn latin1_accepts_every_byte() {
+    let mut input = HEADER.to_vec();
+    input.extend_from_slice(b"deposit,1,1,10.0,caf\xe9\x81\n");
+    let mut engine = TransactionEngine::builder()
+        .with_input_encoding(InputEncoding::Latin1)
+        .build();
+    engine.process_bytes(&input).await.unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n"
+    );
+}
@@ -0,0 +1,95 @@
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError};
+
+const INPUT: &str = "type,client,tx,amount,currency\ndeposit,1,1,10.0,€\n";
+
+#[tokio::test]
+async fn character_split_across_chunks_is_parsed() {
+    let mut engine = TransactionEngine::builder()
+        .with_multi_currency(true)
+        .build();
+    let bytes = INPUT.as_bytes();
+    // inside the 3-byte euro sign
+    let split = INPUT.find('€').unwrap() + 1;
+
+    engine.feed(&bytes[..split]).await.unwrap();
+    engine.feed(&bytes[split..split + 1]).await.unwrap();
+    engine.feed(&bytes[split + 1..]).await.unwrap();
+    engine.finish().await.unwrap();
+
+    let output = engine.to_csv_string().await.unwrap();
+    assert_eq!(
+        output,
+        "client,currency,available,held,total,locked\n1,€,10,0,10,false\n"
+    );
+}
+
+#[tokio::test]
+async fn invalid_bytes_are_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.feed(b"type,client,tx,amount\n").await.unwrap();
+    let result = engine.feed(b"deposit,1,1,\xff\n").await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidUtf8 { byte_offset: 34 })
+    ));
+}
+
+#[tokio::test]
+async fn character_dangling_at_finish_is_an_error() {
+    let mut engine = TransactionEngine::builder()
+        .with_multi_currency(true)
+        .build();
+    let split = INPUT.find('€').unwrap() + 1;
+    engine.feed(&INPUT.as_bytes()[..split]).await.unwrap();
+    let result = engine.finish().await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidUtf8 { byte_offset }) if byte_offset == split as u64 - 1
+    ));
+}
+
+/// Comments, CRLF line endings, a quoted field and a final row without a line ending
+const SPLIT_INPUT: &str = "\
+\r\n# exported nightly\r\ntype,client,tx,amount\r\ndeposit,1,1,10.0\r\n\"withdrawal\",1,2,\"2.5\"\r\n\
+# a comment between rows\r\ndeposit,2,3,4.0\r\ndispute,2,3,\r\ndeposit,1,4,1.25";
+
+fn split_engine() -> TransactionEngine {
+    TransactionEngine::builder()
+        .with_skip_comment_lines(true)
+        .build()
+}
+
+async fn fed_output(chunks: &[&[u8]]) -> String {
+    let mut engine = split_engine();
+    for chunk in chunks {
+        engine.feed(chunk).await.unwrap();
+    }
+    engine.finish().await.unwrap();
+    engine.to_csv_string().await.unwrap()
+}
+
+#[tokio::test]
+async fn split_at_every_byte_position_gives_the_same_balances() {
+    let mut engine = split_engine();
+    engine.process_str(SPLIT_INPUT).await.unwrap();
+    let expected = engine.to_csv_string().await.unwrap();
+    assert_eq!(
+        expected,
+        "client,available,held,total,locked\n1,8.75,0,8.75,false\n2,0,4,4,false\n"
+    );
+
+    let bytes = SPLIT_INPUT.as_bytes();
+    for split in 0..=bytes.len() {
+        assert_eq!(
+            fed_output(&[&bytes[..split], &bytes[split..]]).await,
+            expected,
+            "split at byte {}",
+            split
+        );
+    }
+
+    let byte_by_byte = bytes.chunks(1).collect::<Vec<_>>();
+    assert_eq!(fed_output(&byte_by_byte).await, expected);
+}
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use transactions_demo::tx_engine::{
+    ClientFilter, RejectionReason, SkipReason, TransactionEngine, TransactionType,
+};
+
+mod common;
+use common::rejection_reasons;
+
+fn skip_reasons(engine: &TransactionEngine) -> Vec<(u32, SkipReason)> {
+    engine
+        .skipped()
+        .iter()
+        .map(|skipped| (skipped.tx, skipped.reason.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn rows_outside_the_category_allow_list_are_skipped_and_counted() {
+    let input = "\
+type,client,tx,amount,category
+deposit,1,1,10.0,retail
+deposit,1,2,5.0,Gambling
+deposit,1,3,1.0,
+";
+    let mut engine = TransactionEngine::builder()
+        .with_allowed_categories(["retail"])
+        .build();
+    let summary = engine.process_str(input).await.unwrap();
+
+    // rows without a category are processed
+    assert_eq!(
+        skip_reasons(&engine),
+        [(2, SkipReason::CategoryNotAllowed("gambling".to_string()))]
+    );
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,11,0,11,false\n"
+    );
+}
+
+const CLIENTS: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.0
+deposit,3,3,7.0
+dispute,1,1,
+withdrawal,3,4,1.0
+dispute,2,2,
+";
+
+#[tokio::test]
+async fn only_allowed_clients_are_processed_and_output() {
+    let mut engine = TransactionEngine::builder()
+        .with_allowed_clients([1, 2])
+        .build();
+    engine.process_str(CLIENTS).await.unwrap();
+
+    // disputes of the allowed clients' transactions are still applied
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0,10,10,false\n2,0,5,5,false\n"
+    );
+    assert_eq!(
+        rejection_reasons(&engine),
+        [
+            (3, 3, RejectionReason::ClientNotAllowed),
+            (3, 4, RejectionReason::ClientNotAllowed)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn clients_outside_the_whitelist_are_rejected() {
+    let mut engine = TransactionEngine::builder()
+        .with_client_filter(ClientFilter::Whitelist(HashSet::from([1, 3])))
+        .build();
+    engine.process_str(CLIENTS).await.unwrap();
+
+    assert_eq!(
+        engine.client_ids(),
+        [1, 3],
+        "rejected clients never get an account"
+    );
+    assert_eq!(
+        rejection_reasons(&engine),
+        [
+            (2, 2, RejectionReason::ClientNotAllowed),
+            (2, 2, RejectionReason::ClientNotAllowed)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn clients_in_the_blacklist_are_rejected() {
+    let mut engine = TransactionEngine::builder()
+        .with_client_filter(ClientFilter::Blacklist(HashSet::from([1])))
+        .build();
+    engine.process_str(CLIENTS).await.unwrap();
+
+    assert_eq!(engine.client_ids(), [2, 3]);
+    assert_eq!(
+        rejection_reasons(&engine),
+        [
+            (1, 1, RejectionReason::ClientNotAllowed),
+            (1, 1, RejectionReason::ClientNotAllowed)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn transfer_to_a_filtered_client_is_rejected() {
+    let mut engine = TransactionEngine::builder()
+        .with_client_filter(ClientFilter::Blacklist(HashSet::from([2])))
+        .build();
+    engine
+        .process_str("type,client,tx,amount,target_client\ndeposit,1,1,10.0,\ntransfer,1,2,4.0,2\n")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 2, RejectionReason::ClientNotAllowed)]
+    );
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n"
+    );
+}
+
+#[tokio::test]
+async fn rows_outside_the_time_window_are_skipped() {
+    let input = "\
+type,client,tx,amount,timestamp
+deposit,1,1,1.0,99
+deposit,1,2,2.0,100
+deposit,1,3,4.0,150
+deposit,1,4,8.0,200
+deposit,1,5,16.0,201
+deposit,1,6,32.0,
+dispute,1,1,,150
+";
+    let mut engine = TransactionEngine::builder()
+        .with_time_window(100..=200)
+        .build();
+    let summary = engine.process_str(input).await.unwrap();
+
+    // both ends of the window are included
+    assert_eq!(
+        skip_reasons(&engine),
+        [
+            (1, SkipReason::OutsideTimeWindow(Some(99))),
+            (5, SkipReason::OutsideTimeWindow(Some(201))),
+            (6, SkipReason::OutsideTimeWindow(None))
+        ]
+    );
+    assert_eq!(summary.skipped, 3);
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::TransactionNotFound)]
+    );
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,14,0,14,false\n"
+    );
+}
+
+#[tokio::test]
+async fn disabled_types_are_skipped_and_counted() {
+    let input = "\
+type,client,tx,amount
+deposit,1,1,10.0
+withdrawal,1,2,4.0
+dispute,1,1,
+Dispute,1,1,
+";
+    let mut engine = TransactionEngine::builder()
+        .with_disabled_type(TransactionType::Withdrawal)
+        .with_disabled_type(TransactionType::Dispute)
+        .build();
+    let summary = engine.process_str(input).await.unwrap();
+
+    // the type is checked after normalization
+    assert_eq!(
+        skip_reasons(&engine),
+        [
+            (2, SkipReason::TypeDisabled(TransactionType::Withdrawal)),
+            (1, SkipReason::TypeDisabled(TransactionType::Dispute)),
+            (1, SkipReason::TypeDisabled(TransactionType::Dispute))
+        ]
+    );
+    assert_eq!(summary.skipped, 3);
+    assert!(engine.rejections().is_empty());
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n"
+    );
+}
@@ -0,0 +1,124 @@
+use transactions_demo::tx_engine::{
+    AuditFlag, Decimal, Decision, RejectionReason, RoundAmountRule, Transaction, TransactionEngine,
+    TransactionError, VelocityRule,
+};
+
+mod common;
+use common::rejection_reasons;
+
+#[tokio::test]
+async fn rejected_deposits_do_not_count_towards_velocity() {
+    let mut engine = TransactionEngine::builder()
+        .with_fraud_rule(VelocityRule::new(2, 10, 80))
+        .with_max_deposit_amount(Decimal::new(100, 0))
+        .with_veto(|transaction: &Transaction| match transaction.tx() {
+            3 => Decision::Reject("blocked"),
+            _ => Decision::Accept,
+        })
+        .build();
+    // tx 2 exceeds the limit and tx 3 is vetoed, so only tx 1 and 4 are scored
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,1,2,500.0
+deposit,1,3,1.0
+deposit,1,4,1.0
+";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert!(engine.audit_log().is_empty());
+    assert_eq!(engine.rejections().len(), 2);
+    assert_eq!(engine.full_report().accounts[0].total(), Decimal::new(2, 0));
+}
+
+#[tokio::test]
+async fn third_deposit_in_window_is_flagged_but_applied() {
+    let mut engine = TransactionEngine::builder()
+        .with_fraud_rule(VelocityRule::new(2, 10, 80))
+        .build();
+    let input = "type,client,tx,amount\ndeposit,1,1,1.0\ndeposit,1,2,1.0\ndeposit,1,3,1.0\n";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert_eq!(engine.audit_log().len(), 1);
+    assert_eq!(engine.audit_log()[0].tx, 3);
+    assert_eq!(
+        engine.audit_log()[0].flag,
+        AuditFlag::FraudSuspected { score: 80 }
+    );
+    assert_eq!(engine.full_report().accounts[0].total(), Decimal::new(3, 0));
+}
+
+#[tokio::test]
+async fn suspected_fraud_is_rejected_when_configured() {
+    let mut engine = TransactionEngine::builder()
+        .with_fraud_rule(RoundAmountRule {
+            multiple: Decimal::new(1000, 0),
+            score: 90,
+        })
+        .with_reject_suspected_fraud(true)
+        .build();
+    let input = "type,client,tx,amount\ndeposit,1,1,5000.0\ndeposit,1,2,12.5\n";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::FraudSuspected
+    );
+    assert_eq!(engine.audit_log().len(), 1);
+    assert_eq!(
+        engine.full_report().accounts[0].total(),
+        Decimal::new(125, 1)
+    );
+}
+
+/// Rejects deposits over 100 and aborts on tx 99
+fn threshold_veto(transaction: &Transaction) -> Decision {
+    match transaction.amount() {
+        _ if transaction.tx() == 99 => Decision::Abort,
+        Some(amount) if amount > Decimal::new(100, 0) => Decision::Reject("over limit"),
+        _ => Decision::Accept,
+    }
+}
+
+#[tokio::test]
+async fn veto_over_threshold_is_reported_as_vetoed() {
+    let mut engine = TransactionEngine::builder()
+        .with_veto(threshold_veto)
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,100.0\ndeposit,1,2,100.5\n")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 2, RejectionReason::Vetoed("over limit"))]
+    );
+    assert_eq!(
+        engine.query_client(1).unwrap().total(),
+        Decimal::new(100, 0)
+    );
+}
+
+#[tokio::test]
+async fn aborting_veto_stops_processing() {
+    let mut engine = TransactionEngine::builder()
+        .with_veto(threshold_veto)
+        .build();
+    let result = engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,1.0\ndeposit,1,99,1.0\ndeposit,1,3,1.0\n")
+        .await;
+
+    assert!(matches!(result, Err(TransactionError::Aborted { tx: 99 })));
+    assert!(engine.rejections().is_empty());
+}
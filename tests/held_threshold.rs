@@ -0,0 +1,71 @@
+use transactions_demo::tx_engine::{BalanceCorrection, Decimal, TransactionEngine};
+
+#[tokio::test]
+async fn repeated_dispute_cycles_leave_nothing_held() {
+    let mut engine = TransactionEngine::builder()
+        .with_held_zero_threshold(Decimal::new(1, 8))
+        .build();
+    let mut input = String::from("type,client,tx,amount\ndeposit,1,1,0.1\n");
+    for _ in 0..1000 {
+        input.push_str("dispute,1,1,\nresolve,1,1,\n");
+    }
+    engine.process_str(&input).await.unwrap();
+
+    let row = engine.query_client(1).unwrap();
+    assert_eq!(row.held(), Decimal::ZERO);
+    assert_eq!(row.available(), Decimal::new(1, 1));
+    assert_eq!(row.total(), Decimal::new(1, 1));
+}
+
+#[tokio::test]
+async fn resolve_keeps_funds_held_by_other_disputes() {
+    let mut engine = TransactionEngine::builder()
+        .with_held_zero_threshold(Decimal::ONE)
+        .build();
+    let input = "\
+type,client,tx,amount
+deposit,1,1,0.5
+deposit,1,2,0.5
+dispute,1,1,
+dispute,1,2,
+resolve,1,1,
+";
+    engine.process_str(input).await.unwrap();
+    let row = engine.query_client(1).unwrap();
+    assert_eq!(row.held(), Decimal::new(5, 1));
+    assert_eq!(row.total(), Decimal::ONE);
+
+    engine
+        .feed(b"type,client,tx,amount\nchargeback,1,2,\n")
+        .await
+        .unwrap();
+    let row = engine.query_client(1).unwrap();
+    assert_eq!(row.held(), Decimal::ZERO);
+    assert_eq!(row.available(), Decimal::new(5, 1));
+    assert_eq!(row.total(), Decimal::new(5, 1));
+    assert!(row.locked());
+}
+
+#[tokio::test]
+async fn residual_is_released_to_available_funds() {
+    let mut engine = TransactionEngine::builder()
+        .with_held_zero_threshold(Decimal::new(1, 8))
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,1.0\n")
+        .await
+        .unwrap();
+    // leaves a residual like one left by amounts that went through f64
+    engine
+        .apply_correction(1, BalanceCorrection::AdjustHeld(Decimal::new(1, 10)))
+        .unwrap();
+
+    engine
+        .feed(b"type,client,tx,amount\ndispute,1,1,\nresolve,1,1,\n")
+        .await
+        .unwrap();
+    let row = engine.query_client(1).unwrap();
+    assert_eq!(row.held(), Decimal::ZERO);
+    assert_eq!(row.available(), Decimal::new(10000000001, 10));
+    assert_eq!(row.total(), Decimal::new(10000000001, 10));
+}
@@ -0,0 +1,124 @@
+use transactions_demo::tx_engine::{
+    Decimal, HistoryEvictionPolicy, RejectionReason, TransactionEngine, TransactionError,
+};
+
+#[tokio::test]
+async fn rejected_rows_do_not_evict_disputable_deposits() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(2, HistoryEvictionPolicy::EvictLru)
+        .build();
+    // the withdrawal is rejected, so the history still has room for both deposits
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,1,2,2.0
+withdrawal,1,3,100.0
+dispute,1,1,
+";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::InsufficientFunds
+    );
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.held(), Decimal::new(1, 0));
+    assert_eq!(account.available(), Decimal::new(2, 0));
+}
+
+#[tokio::test]
+async fn least_recently_referenced_deposit_is_evicted() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(2, HistoryEvictionPolicy::EvictLru)
+        .build();
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,1,2,2.0
+deposit,1,3,4.0
+dispute,1,1,
+dispute,1,2,
+";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    // tx 1 made room for tx 3
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(engine.rejections()[0].tx, 1);
+    assert_eq!(engine.full_report().accounts[0].held(), Decimal::new(2, 0));
+}
+
+#[tokio::test]
+async fn full_history_rejects_only_transactions_that_would_be_applied() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(1, HistoryEvictionPolicy::Reject)
+        .build();
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+withdrawal,1,2,5.0
+deposit,1,3,1.0
+";
+    let result = engine.process(input.as_bytes(), tokio::io::sink()).await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::HistoryFull { max: 1 })
+    ));
+    // the withdrawal was rejected rather than failing, and the deposit that did not fit was undone
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(engine.summary().transactions_processed, 2);
+    assert_eq!(engine.full_report().accounts[0].total(), Decimal::ONE);
+}
+
+#[tokio::test]
+async fn deposit_of_new_client_that_does_not_fit_creates_no_account() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(1, HistoryEvictionPolicy::Reject)
+        .build();
+    let input = "type,client,tx,amount\ndeposit,1,1,1.0\ndeposit,2,2,1.0\n";
+    let result = engine.process(input.as_bytes(), tokio::io::sink()).await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::HistoryFull { max: 1 })
+    ));
+    assert_eq!(engine.client_ids(), vec![1]);
+}
+
+#[tokio::test]
+async fn disputed_deposit_survives_eviction() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(3, HistoryEvictionPolicy::EvictLru)
+        .build();
+    // tx 1 is the least recently referenced once tx 2 is deposited, but it is under dispute when tx 3 needs room
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,1,2,2.0
+dispute,1,1,
+deposit,1,3,4.0
+resolve,1,1,
+dispute,1,2,
+";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    // tx 1 could still be resolved, while tx 2 was evicted
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(engine.rejections()[0].tx, 2);
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::TransactionNotFound
+    );
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.held(), Decimal::ZERO);
+    assert_eq!(account.available(), Decimal::new(7, 0));
+}
@@ -0,0 +1,205 @@
+use transactions_demo::tx_engine::{
+    AuditFlag, Decimal, HistoryEvictionPolicy, TransactionEngine, TransactionError,
+};
+
+/// Client 1 has 100 available, client 2 is locked with 50 available and client 3 has nothing available
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,100.0
+deposit,2,2,50.0
+deposit,2,3,10.0
+dispute,2,3,
+chargeback,2,3,
+deposit,3,4,5.0
+withdrawal,3,5,5.0
+";
+
+#[tokio::test]
+async fn interest_is_credited_to_unlocked_accounts_with_available_funds() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q1")
+        .unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,101.00,0,101.00,false\n2,50,0,50,true\n3,0,0,0,false\n"
+    );
+    let audit_log = engine.audit_log();
+    assert_eq!(audit_log.len(), 1);
+    assert_eq!((audit_log[0].client, audit_log[0].tx), (1, u32::MAX));
+    match &audit_log[0].flag {
+        AuditFlag::InterestApplied {
+            period,
+            rate,
+            amount,
+        } => {
+            assert_eq!(period, "2024-Q1");
+            assert_eq!(*rate, Decimal::new(1, 2));
+            assert_eq!(*amount, Decimal::ONE);
+        }
+        flag => panic!("unexpected audit flag {:?}", flag),
+    }
+}
+
+#[tokio::test]
+async fn interest_is_recorded_as_a_disputable_deposit() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q1")
+        .unwrap();
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q2")
+        .unwrap();
+
+    // the second application used the next tx down
+    let txs = engine
+        .audit_log()
+        .iter()
+        .map(|entry| entry.tx)
+        .collect::<Vec<_>>();
+    assert_eq!(txs, [u32::MAX, u32::MAX - 1]);
+
+    engine
+        .feed(format!("type,client,tx,amount\ndispute,1,{},\n", u32::MAX).as_bytes())
+        .await
+        .unwrap();
+    let row = engine.query_client(1).unwrap();
+    assert_eq!(row.held(), Decimal::ONE);
+    assert_eq!(row.total(), Decimal::new(10201, 2));
+}
+
+#[tokio::test]
+async fn locked_accounts_earn_interest_when_configured() {
+    let mut engine = TransactionEngine::builder()
+        .with_interest_on_locked(true)
+        .build();
+    engine.process_str(INPUT).await.unwrap();
+    engine.apply_interest(Decimal::new(1, 1), "2024").unwrap();
+
+    assert_eq!(
+        engine.query_client(1).unwrap().available(),
+        Decimal::new(110, 0)
+    );
+    assert_eq!(
+        engine.query_client(2).unwrap().available(),
+        Decimal::new(55, 0)
+    );
+    assert_eq!(engine.query_client(3).unwrap().available(), Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn interest_tx_used_by_an_input_transaction_is_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str(&format!(
+            "type,client,tx,amount\ndeposit,1,1,100.0\ndeposit,2,{},10.0\n",
+            u32::MAX
+        ))
+        .await
+        .unwrap();
+    let result = engine.apply_interest(Decimal::new(1, 2), "2024-Q1");
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InterestTxInUse {
+            client: 2,
+            tx: u32::MAX
+        })
+    ));
+    // nothing was credited
+    assert_eq!(
+        engine.query_client(1).unwrap().total(),
+        Decimal::new(100, 0)
+    );
+    assert!(engine.audit_log().is_empty());
+}
+
+#[tokio::test]
+async fn negative_interest_rate_is_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(INPUT).await.unwrap();
+    let result = engine.apply_interest(Decimal::new(-1, 2), "2024-Q1");
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidInterestRate { rate }) if rate == Decimal::new(-1, 2)
+    ));
+    assert_eq!(
+        engine.query_client(1).unwrap().total(),
+        Decimal::new(100, 0)
+    );
+    assert!(engine.audit_log().is_empty());
+
+    // the failed call did not use up a tx
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q1")
+        .unwrap();
+    assert_eq!(engine.audit_log()[0].tx, u32::MAX);
+}
+
+#[tokio::test]
+async fn interest_with_multiple_accounts_per_client_is_an_error() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    engine
+        .process_str("type,client,tx,amount,ledger\ndeposit,1,1,100.0,\ndeposit,1,2,50.0,savings\n")
+        .await
+        .unwrap();
+    let result = engine.apply_interest(Decimal::new(1, 2), "2024-Q1");
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InterestWithMultipleAccounts)
+    ));
+    assert!(engine.audit_log().is_empty());
+}
+
+#[tokio::test]
+async fn interest_that_does_not_fit_in_the_history_credits_nothing() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(3, HistoryEvictionPolicy::Reject)
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,100.0\ndeposit,2,2,50.0\n")
+        .await
+        .unwrap();
+    let result = engine.apply_interest(Decimal::new(1, 2), "2024-Q1");
+
+    // only one of the two credits fits
+    assert!(matches!(
+        result,
+        Err(TransactionError::HistoryFull { max: 3 })
+    ));
+    assert_eq!(
+        engine.query_client(1).unwrap().total(),
+        Decimal::new(100, 0)
+    );
+    assert_eq!(engine.query_client(2).unwrap().total(), Decimal::new(50, 0));
+    assert!(engine.audit_log().is_empty());
+}
+
+#[tokio::test]
+async fn interest_deposits_can_be_evicted() {
+    let mut engine = TransactionEngine::builder()
+        .with_max_history_entries(2, HistoryEvictionPolicy::EvictLru)
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,100.0\ndeposit,2,2,50.0\n")
+        .await
+        .unwrap();
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q1")
+        .unwrap();
+    // the first period's credits make room for the second's
+    engine
+        .apply_interest(Decimal::new(1, 2), "2024-Q2")
+        .unwrap();
+
+    assert_eq!(engine.audit_log().len(), 4);
+    assert_eq!(
+        engine.query_client(1).unwrap().total(),
+        Decimal::new(1020100, 4)
+    );
+}
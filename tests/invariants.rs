@@ -0,0 +1,177 @@
+use transactions_demo::tx_engine::{
+    AuditFlag, BalanceCorrection, Decimal, InvariantViolation, ReconciliationError,
+    TotalFloorPolicy, TransactionEngine, TransactionError,
+};
+
+#[tokio::test]
+async fn process_bytes_applies_rows_without_output() {
+    let mut engine = TransactionEngine::builder().build();
+    let summary = engine
+        .process_bytes(b"type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,2,2,5.0\ndispute,1,1,\n")
+        .await
+        .unwrap();
+
+    assert_eq!(summary.transactions_processed, 3);
+    assert_eq!(engine.client_ids(), [1, 2]);
+    assert_eq!(engine.verify_invariants(), Ok(()));
+}
+
+#[tokio::test]
+async fn process_bytes_reports_invalid_input_as_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    let result = engine
+        .process_bytes(b"type,client,tx,amount\nteleport,1,1,10.0\n")
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidTransactionType(_))
+    ));
+}
+
+#[tokio::test]
+async fn disputes_count_towards_the_ledger_of_their_transaction() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    engine
+        .process_bytes(
+            b"type,client,tx,amount,ledger\ndeposit,1,1,10.0,savings\ndeposit,1,2,3.0,\ndispute,1,1,,\n",
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(engine.verify_invariants(), Ok(()));
+}
+
+#[tokio::test]
+async fn held_correction_is_a_violation() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_bytes(b"type,client,tx,amount\ndeposit,1,1,10.0\n")
+        .await
+        .unwrap();
+    engine
+        .apply_correction(1, BalanceCorrection::AdjustHeld(Decimal::ONE))
+        .unwrap();
+
+    assert_eq!(
+        engine.verify_invariants(),
+        Err(vec![InvariantViolation::HeldMismatch {
+            account: (1, String::new(), String::new()),
+            held: Decimal::ONE,
+            disputed: Decimal::ZERO,
+        }])
+    );
+}
+
+/// A chargeback of a deposit that was mostly withdrawn since drops client 1's total to -8
+const CHARGEBACK_BELOW_ZERO: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+withdrawal,1,2,8.0
+dispute,1,1,
+chargeback,1,1,
+deposit,2,3,1.0
+";
+
+#[tokio::test]
+async fn total_below_floor_is_flagged_by_default() {
+    let mut engine = TransactionEngine::builder()
+        .with_total_floor(Decimal::ZERO)
+        .build();
+    engine.process_str(CHARGEBACK_BELOW_ZERO).await.unwrap();
+
+    let flagged = engine
+        .audit_log()
+        .iter()
+        .map(|entry| (entry.client, entry.tx, entry.flag.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flagged,
+        [(
+            1,
+            1,
+            AuditFlag::TotalBelowFloor {
+                total: Decimal::from(-8)
+            }
+        )]
+    );
+    // processing continued after the breach
+    assert_eq!(engine.client_ids(), [1, 2]);
+}
+
+#[tokio::test]
+async fn total_below_floor_aborts_when_configured() {
+    let mut engine = TransactionEngine::builder()
+        .with_total_floor(Decimal::ZERO)
+        .with_total_floor_policy(TotalFloorPolicy::Abort)
+        .build();
+    let result = engine.process_str(CHARGEBACK_BELOW_ZERO).await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::TotalBelowFloor { client: 1, tx: 1, total }) if total == Decimal::from(-8)
+    ));
+    assert!(engine.audit_log().is_empty());
+    assert_eq!(engine.client_ids(), [1]);
+}
+
+/// An engine restored from a checkpoint whose client 1 has a total 2 above its available funds
+async fn engine_with_unbalanced_account(continuous_reconciliation: bool) -> TransactionEngine {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\n")
+        .await
+        .unwrap();
+    let mut checkpoint = Vec::new();
+    engine.save_checkpoint(&mut checkpoint).unwrap();
+    let tampered = String::from_utf8(checkpoint)
+        .unwrap()
+        .replace("\"total\":\"10\"", "\"total\":\"12\"");
+
+    let mut restored = TransactionEngine::builder()
+        .with_continuous_reconciliation(continuous_reconciliation)
+        .build();
+    restored.load_checkpoint(tampered.as_bytes()).unwrap();
+    restored
+}
+
+#[tokio::test]
+async fn unbalanced_account_is_recorded_at_each_transaction() {
+    let mut engine = engine_with_unbalanced_account(true).await;
+    engine
+        .feed(b"type,client,tx,amount\ndeposit,1,2,1.0\ndeposit,2,3,1.0\nwithdrawal,1,4,3.0\n")
+        .await
+        .unwrap();
+
+    // client 2 is balanced, and the withdrawal keeps client 1 off by 2
+    assert_eq!(
+        engine.reconciliation_errors(),
+        [
+            ReconciliationError {
+                client: 1,
+                tx: 2,
+                available: Decimal::from(11),
+                held: Decimal::ZERO,
+                total: Decimal::from(13)
+            },
+            ReconciliationError {
+                client: 1,
+                tx: 4,
+                available: Decimal::from(8),
+                held: Decimal::ZERO,
+                total: Decimal::from(10)
+            }
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unbalanced_account_is_not_recorded_by_default() {
+    let mut engine = engine_with_unbalanced_account(false).await;
+    engine
+        .feed(b"type,client,tx,amount\ndeposit,1,2,1.0\n")
+        .await
+        .unwrap();
+
+    assert!(engine.reconciliation_errors().is_empty());
+}
@@ -0,0 +1,102 @@
+use rdkafka::config::ClientConfig;
+use rdkafka::consumer::{Consumer, StreamConsumer};
+use rdkafka::mocking::MockCluster;
+use rdkafka::producer::{DefaultProducerContext, FutureProducer, FutureRecord};
+use rdkafka::{Offset, TopicPartitionList};
+use std::time::Duration;
+use transactions_demo::tx_engine::{KafkaPayload, TransactionEngine};
+
+const TOPIC: &str = "transactions";
+
+/// Produces `payloads` to a single-partition topic of a fresh mock cluster, where `None` is a message without a
+/// payload
+async fn mock_cluster_with(
+    payloads: &[Option<&str>],
+) -> MockCluster<'static, DefaultProducerContext> {
+    let cluster = MockCluster::new(1).unwrap();
+    cluster.create_topic(TOPIC, 1, 1).unwrap();
+
+    let producer: FutureProducer = ClientConfig::new()
+        .set("bootstrap.servers", cluster.bootstrap_servers())
+        .create()
+        .unwrap();
+    for payload in payloads {
+        let mut record = FutureRecord::<(), str>::to(TOPIC);
+        if let Some(payload) = payload {
+            record = record.payload(payload);
+        }
+        producer.send(record, Duration::from_secs(5)).await.unwrap();
+    }
+
+    cluster
+}
+
+/// Consumes every message produced to the cluster, then writes the balances
+async fn process_kafka(
+    cluster: &MockCluster<'static, DefaultProducerContext>,
+    payload: KafkaPayload,
+    messages: i64,
+) -> String {
+    let consumer: StreamConsumer = ClientConfig::new()
+        .set("bootstrap.servers", cluster.bootstrap_servers())
+        .set("group.id", "transactions-demo")
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "earliest")
+        .create()
+        .unwrap();
+    consumer.subscribe(&[TOPIC]).unwrap();
+
+    // every message was applied once the consumer moved past the last one
+    let consumed = async {
+        let mut last = TopicPartitionList::new();
+        last.add_partition_offset(TOPIC, 0, Offset::Offset(messages))
+            .unwrap();
+        while consumer.position().ok().as_ref() != Some(&last) {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    };
+
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    tokio::time::timeout(
+        Duration::from_secs(30),
+        engine.process_kafka(&consumer, payload, consumed, &mut output),
+    )
+    .await
+    .expect("every message is consumed")
+    .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn csv_messages_are_applied_in_order() {
+    let cluster = mock_cluster_with(&[
+        Some("deposit,1,1,10.0"),
+        Some("withdrawal,1,2,2.5"),
+        None,
+        Some("deposit,2,3,1.0"),
+        Some("dispute,2,3"),
+    ])
+    .await;
+
+    // the message without a payload is skipped
+    assert_eq!(
+        process_kafka(&cluster, KafkaPayload::Csv, 5).await,
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n2,0,1,1,false\n"
+    );
+}
+
+#[tokio::test]
+async fn json_messages_are_applied_in_order() {
+    let cluster = mock_cluster_with(&[
+        Some(r#"{"type":"deposit","client":1,"tx":1,"amount":"10.0"}"#),
+        Some(r#"{"type":"withdrawal","client":1,"tx":2,"amount":2.5}"#),
+        Some(r#"{"type":"dispute","client":1,"tx":1,"amount":null}"#),
+    ])
+    .await;
+
+    assert_eq!(
+        process_kafka(&cluster, KafkaPayload::Json, 3).await,
+        "client,available,held,total,locked\n1,-2.5,10,7.5,false\n"
+    );
+}
@@ -0,0 +1,33 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+#[tokio::test]
+async fn ledger_has_the_running_balances_after_each_applied_transaction() {
+    let input = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,3.0
+withdrawal,1,3,4.0
+withdrawal,1,4,100.0
+dispute,1,1,
+resolve,1,1,
+";
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    engine
+        .process_ledger(input.as_bytes(), &mut output)
+        .await
+        .unwrap();
+
+    // the rejected withdrawal has no row
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+client,tx,type,amount,available,held,total,locked
+1,1,deposit,10,10,0,10,false
+2,2,deposit,3,3,0,3,false
+1,3,withdrawal,4,6,0,6,false
+1,1,dispute,10,-4,10,6,false
+1,1,resolve,10,6,0,6,false
+"
+    );
+}
@@ -0,0 +1,77 @@
+use transactions_demo::tx_engine::{RejectionReason, TransactionEngine, TransactionError};
+
+mod common;
+use common::rejection_reasons;
+
+const FIRST: &str = "\
+type,client,tx,amount,timestamp
+deposit,1,1,5.0,10
+withdrawal,1,3,8.0,30
+withdrawal,2,4,3.0,40
+";
+
+const SECOND: &str = "\
+type,client,tx,amount,timestamp
+deposit,1,2,5.0,20
+deposit,2,5,3.0,40
+";
+
+async fn merge(engine: &mut TransactionEngine, inputs: [&str; 2]) -> String {
+    let mut output = Vec::new();
+    engine
+        .process_merged_by_timestamp(
+            inputs.iter().map(|input| input.as_bytes()).collect(),
+            &mut output,
+        )
+        .await
+        .unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[tokio::test]
+async fn interleaved_inputs_are_applied_in_timestamp_order() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = merge(&mut engine, [FIRST, SECOND]).await;
+
+    // the withdrawal at 30 only succeeds after both deposits, and at 40 the first input's withdrawal comes first
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,2,0,2,false\n2,3,0,3,false\n"
+    );
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(2, 4, RejectionReason::InsufficientFunds)]
+    );
+}
+
+#[tokio::test]
+async fn ties_are_applied_in_input_order() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = merge(&mut engine, [SECOND, FIRST]).await;
+
+    // the deposit at 40 now comes first, funding the withdrawal
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,2,0,2,false\n2,0,0,0,false\n"
+    );
+    assert!(engine.rejections().is_empty());
+}
+
+#[tokio::test]
+async fn row_without_a_timestamp_is_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    let result = engine
+        .process_merged_by_timestamp(
+            vec![
+                FIRST.as_bytes(),
+                "type,client,tx,amount,timestamp\ndeposit,1,7,1.0,\n".as_bytes(),
+            ],
+            tokio::io::sink(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::MissingTimestamp { tx: 7 })
+    ));
+}
@@ -0,0 +1,69 @@
+use transactions_demo::tx_engine::{Decimal, RejectionReason, TransactionEngine};
+
+mod common;
+use common::rejection_reasons;
+
+async fn engine_with_floor(floor: Decimal, input: &str) -> TransactionEngine {
+    let mut engine = TransactionEngine::builder()
+        .with_min_available(floor)
+        .build();
+    engine.process_str(input).await.unwrap();
+    engine
+}
+
+#[tokio::test]
+async fn withdrawal_may_reach_the_floor_but_not_go_below_it() {
+    let input =
+        "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,5.0\nwithdrawal,1,3,0.01\n";
+    let engine = engine_with_floor(Decimal::from(5), input).await;
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 3, RejectionReason::BelowMinimum)]
+    );
+    assert_eq!(
+        engine.full_report().accounts[0].available(),
+        Decimal::from(5)
+    );
+}
+
+#[tokio::test]
+async fn negative_floor_allows_withdrawing_into_the_red() {
+    let input =
+        "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,15.0\nwithdrawal,1,3,0.1\n";
+    let engine = engine_with_floor(Decimal::from(-5), input).await;
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 3, RejectionReason::BelowMinimum)]
+    );
+    assert_eq!(
+        engine.full_report().accounts[0].available(),
+        Decimal::from(-5)
+    );
+}
+
+#[tokio::test]
+async fn dispute_may_reach_the_floor() {
+    let input = "type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,1,2,3.0\ndispute,1,1,\n";
+    let engine = engine_with_floor(Decimal::from(3), input).await;
+
+    assert!(engine.rejections().is_empty());
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.available(), Decimal::from(3));
+    assert_eq!(account.held(), Decimal::from(10));
+}
+
+#[tokio::test]
+async fn dispute_below_the_floor_is_rejected() {
+    let input = "type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,1,2,3.0\nwithdrawal,1,3,0.5\ndispute,1,1,\n";
+    let engine = engine_with_floor(Decimal::from(3), input).await;
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 1, RejectionReason::BelowMinimum)]
+    );
+    let account = &engine.full_report().accounts[0];
+    assert_eq!(account.available(), Decimal::new(125, 1));
+    assert_eq!(account.held(), Decimal::ZERO);
+}
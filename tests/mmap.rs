@@ -0,0 +1,43 @@
+use std::fmt::Write;
+use std::path::Path;
+use transactions_demo::tx_engine::TransactionEngine;
+
+/// Deposits, withdrawals and disputes spread over a hundred clients, large enough to need several reads
+fn mixed_input() -> String {
+    let mut input = String::from("type,client,tx,amount\n");
+    for tx in 1..=10_000u32 {
+        let client = tx % 100;
+        let row = match tx % 7 {
+            0 => format!("withdrawal,{},{},1.5\n", client, tx),
+            3 => format!("dispute,{},{},\n", client, tx - 1),
+            5 => format!("resolve,{},{},\n", client, tx - 2),
+            _ => format!("deposit,{},{},{}.25\n", client, tx, tx % 50),
+        };
+        input.write_str(&row).unwrap();
+    }
+    input
+}
+
+async fn output_of_file(path: &Path, use_mmap: bool) -> Vec<u8> {
+    let mut output = Vec::new();
+    TransactionEngine::builder()
+        .with_mmap(use_mmap)
+        .build()
+        .process_file(path, &mut output)
+        .await
+        .unwrap();
+    output
+}
+
+#[tokio::test]
+async fn mapped_input_gives_the_same_output_as_reading_it() {
+    let path = std::env::temp_dir().join(format!("tx-mmap-{}.csv", std::process::id()));
+    tokio::fs::write(&path, mixed_input()).await.unwrap();
+
+    let mapped = output_of_file(&path, true).await;
+    let read = output_of_file(&path, false).await;
+    tokio::fs::remove_file(&path).await.unwrap();
+
+    assert_eq!(mapped.iter().filter(|&&byte| byte == b'\n').count(), 101);
+    assert_eq!(mapped, read);
+}
@@ -0,0 +1,71 @@
+use tokio::sync::mpsc;
+use transactions_demo::tx_engine::{Decimal, InputRow, TransactionEngine};
+
+#[tokio::test]
+async fn summary_counts_rows_of_every_channel() {
+    let (first_tx, first_rx) = mpsc::channel(4);
+    let (second_tx, second_rx) = mpsc::channel(4);
+    tokio::spawn(async move {
+        first_tx
+            .send(InputRow::new("deposit", 1, 1, Some(Decimal::new(5, 0))))
+            .await
+            .unwrap();
+        first_tx
+            .send(InputRow::new("withdrawal", 1, 2, Some(Decimal::new(9, 0))))
+            .await
+            .unwrap();
+    });
+    tokio::spawn(async move {
+        second_tx
+            .send(InputRow::new("deposit", 2, 3, Some(Decimal::new(2, 0))))
+            .await
+            .unwrap();
+    });
+
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    let summary = engine
+        .process_multi_channel(vec![first_rx, second_rx], &mut output)
+        .await
+        .unwrap();
+
+    assert_eq!(summary.transactions_processed, 3);
+    assert_eq!(summary.clients, 2);
+    assert_eq!(summary.rejections, 1);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,5,0,5,false\n2,2,0,2,false\n"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn concurrent_first_deposits_create_one_account() {
+    let mut receivers = Vec::new();
+    for depositor in 0..2u32 {
+        let (sender, receiver) = mpsc::channel(8);
+        receivers.push(receiver);
+        // both depositors race to create client 7, each with its own tx IDs
+        tokio::spawn(async move {
+            for tx in depositor * 100..depositor * 100 + 100 {
+                sender
+                    .send(InputRow::new("deposit", 7, tx, Some(Decimal::ONE)))
+                    .await
+                    .unwrap();
+            }
+        });
+    }
+
+    let mut engine = TransactionEngine::builder().build();
+    let summary = engine
+        .process_multi_channel(receivers, tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert_eq!(engine.client_ids(), [7]);
+    assert_eq!(
+        engine.query_client(7).unwrap().total(),
+        Decimal::new(200, 0)
+    );
+    assert_eq!(summary.transactions_processed, 200);
+    assert_eq!(summary.rejections, 0);
+}
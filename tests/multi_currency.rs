@@ -0,0 +1,65 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+mod common;
+use common::process;
+
+#[tokio::test]
+async fn writes_one_row_per_client_and_currency() {
+    let mut engine = TransactionEngine::builder()
+        .with_multi_currency(true)
+        .build();
+    let input = "\
+type,client,tx,amount,currency
+deposit,2,1,5.0,USD
+deposit,1,2,3.0,USD
+deposit,1,3,10.0,EUR
+withdrawal,1,4,1.0,USD
+dispute,1,3,,
+";
+    let output = process(&mut engine, input).await;
+
+    // the dispute applies to the currency of the deposit it references
+    assert_eq!(
+        output,
+        "\
+client,currency,available,held,total,locked
+1,EUR,0,10,10,false
+1,USD,2,0,2,false
+2,USD,5,0,5,false
+"
+    );
+}
+
+#[tokio::test]
+async fn currency_column_is_ignored_by_default() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount,currency\ndeposit,1,1,3.0,USD\ndeposit,1,2,10.0,EUR\n",
+    )
+    .await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,13,0,13,false\n"
+    );
+}
+
+#[tokio::test]
+async fn withdrawal_only_draws_on_its_currency() {
+    let mut engine = TransactionEngine::builder()
+        .with_multi_currency(true)
+        .build();
+    let input = "\
+type,client,tx,amount,currency
+deposit,1,1,10.0,EUR
+withdrawal,1,2,1.0,USD
+";
+    let output = process(&mut engine, input).await;
+
+    assert_eq!(
+        output,
+        "client,currency,available,held,total,locked\n1,EUR,10,0,10,false\n1,USD,0,0,0,false\n"
+    );
+    assert_eq!(engine.rejections().len(), 1);
+}
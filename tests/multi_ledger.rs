@@ -0,0 +1,19 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+#[tokio::test]
+async fn client_ids_are_sorted_and_distinct_across_ledgers() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    let input = "\
+type,client,tx,amount,ledger
+deposit,9,1,1.0,savings
+deposit,3,2,1.0,checking
+deposit,9,3,1.0,checking
+deposit,3,4,1.0,savings
+deposit,1,5,1.0,checking
+deposit,9,6,1.0,
+";
+    engine.process_str(input).await.unwrap();
+
+    // client 9 has three accounts and client 3 two, but each is listed once
+    assert_eq!(engine.client_ids(), [1, 3, 9]);
+}
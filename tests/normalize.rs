@@ -0,0 +1,68 @@
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError, TransactionType};
+
+const SYNONYM_INPUT: &str = "type,client,tx,amount\ncredit,1,1,10.0\ndebit,1,2,4.0\n";
+
+#[tokio::test]
+async fn synonyms_are_resolved_without_normalization() {
+    let mut engine = TransactionEngine::builder()
+        .with_normalize_input(false)
+        .with_type_synonym("credit", TransactionType::Deposit)
+        .with_type_synonym("Debit", TransactionType::Withdrawal)
+        .build();
+    engine.process_str(SYNONYM_INPUT).await.unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,6,0,6,false\n"
+    );
+}
+
+#[tokio::test]
+async fn synonyms_are_resolved_with_normalization() {
+    let mut engine = TransactionEngine::builder()
+        .with_normalize_input(true)
+        .with_type_synonym("credit", TransactionType::Deposit)
+        .with_type_synonym("debit", TransactionType::Withdrawal)
+        .build();
+    engine.process_str(SYNONYM_INPUT).await.unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,6,0,6,false\n"
+    );
+}
+
+#[tokio::test]
+async fn padded_and_mixed_case_types_are_normalized() {
+    let input = "\
+type,client,tx,amount
+ DEPOSIT ,1,1,10.0
+Deposit,2,2,3.0
+  withDrawal,1,3, 4.0
+DISPUTE ,2,2,
+";
+    let mut engine = TransactionEngine::builder()
+        .with_normalize_input(true)
+        .build();
+    engine.process_str(input).await.unwrap();
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,6,0,6,false\n2,0,3,3,false\n"
+    );
+}
+
+#[tokio::test]
+async fn padded_types_are_invalid_without_normalization() {
+    let mut engine = TransactionEngine::builder()
+        .with_normalize_input(false)
+        .build();
+    let result = engine
+        .process_str("type,client,tx,amount\n DEPOSIT ,1,1,10.0\n")
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidTransactionType(ty)) if ty == " DEPOSIT "
+    ));
+}
@@ -0,0 +1,65 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError};
+
+const INPUT: &str = "type,client,tx,amount\ndeposit,1,1,10.0\n";
+const OUTPUT: &str = "client,available,held,total,locked\n1,10,0,10,false\n";
+
+/// A directory unique to the test that does not exist yet
+fn missing_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tx-output-file-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+async fn write_output(engine: &mut TransactionEngine, path: &Path) -> Result<(), TransactionError> {
+    let output = engine.create_output_file(path).await?;
+    engine.process(INPUT.as_bytes(), output).await
+}
+
+#[tokio::test]
+async fn missing_parent_directory_is_reported_by_default() {
+    let dir = missing_dir("error");
+    let path = dir.join("nested").join("out.csv");
+    let mut engine = TransactionEngine::builder().build();
+
+    match write_output(&mut engine, &path).await {
+        Err(TransactionError::MissingOutputDirectory(missing)) => {
+            assert_eq!(missing, dir.join("nested"))
+        }
+        result => panic!("expected a missing output directory, got {:?}", result),
+    }
+    assert!(!dir.exists());
+}
+
+#[tokio::test]
+async fn missing_parent_directory_is_created_when_enabled() {
+    let dir = missing_dir("create");
+    let path = dir.join("nested").join("out.csv");
+    let mut engine = TransactionEngine::builder()
+        .with_create_output_dirs(true)
+        .build();
+
+    write_output(&mut engine, &path).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), OUTPUT);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn existing_output_file_is_overwritten() {
+    let dir = missing_dir("overwrite");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("out.csv");
+    fs::write(
+        &path,
+        "stale contents that are longer than the new output\n".repeat(4),
+    )
+    .unwrap();
+
+    let mut engine = TransactionEngine::builder().build();
+    write_output(&mut engine, &path).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), OUTPUT);
+
+    fs::remove_dir_all(dir).unwrap();
+}
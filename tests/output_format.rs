@@ -0,0 +1,279 @@
+use transactions_demo::tx_engine::{
+    ConfigError, LineTerminator, LockedFormat, SortField, TransactionEngine,
+    TransactionEngineBuilder, TransactionError,
+};
+
+const INPUT: &str = "type,client,tx,amount\ndeposit,1,1,1.5\ndeposit,2,2,2.0\n";
+
+async fn output_of(builder: TransactionEngineBuilder) -> Vec<u8> {
+    let mut engine = builder.build();
+    let mut output = Vec::new();
+    engine.process(INPUT.as_bytes(), &mut output).await.unwrap();
+    output
+}
+
+#[tokio::test]
+async fn trailing_newline_by_default() {
+    let output = output_of(TransactionEngine::builder()).await;
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1.5,0,1.5,false\n2,2,0,2,false\n"
+    );
+}
+
+#[tokio::test]
+async fn no_trailing_newline() {
+    let output = output_of(TransactionEngine::builder().with_output_trailing_newline(false)).await;
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1.5,0,1.5,false\n2,2,0,2,false"
+    );
+}
+
+#[tokio::test]
+async fn trailing_blank_line() {
+    let output =
+        output_of(TransactionEngine::builder().with_output_trailing_blank_line(true)).await;
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1.5,0,1.5,false\n2,2,0,2,false\n\n"
+    );
+}
+
+#[tokio::test]
+async fn trailing_blank_line_without_trailing_newline() {
+    let output = output_of(
+        TransactionEngine::builder()
+            .with_output_trailing_newline(false)
+            .with_output_trailing_blank_line(true),
+    )
+    .await;
+    // the blank line needs the last row to be ended
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1.5,0,1.5,false\n2,2,0,2,false\n\n"
+    );
+}
+
+#[tokio::test]
+async fn crlf_terminator_ends_every_line() {
+    let output = output_of(
+        TransactionEngine::builder()
+            .with_output_line_terminator(LineTerminator::CrLf)
+            .with_output_trailing_blank_line(true),
+    )
+    .await;
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\r\n1,1.5,0,1.5,false\r\n2,2,0,2,false\r\n\r\n"
+    );
+}
+
+#[tokio::test]
+async fn always_quote_quotes_numeric_fields_by_default() {
+    let output = output_of(TransactionEngine::builder().with_output_always_quote(true)).await;
+    assert_eq!(
+        output,
+        b"\"client\",\"available\",\"held\",\"total\",\"locked\"\n\"1\",\"1.5\",\"0\",\"1.5\",\"false\"\n\"2\",\"2\",\"0\",\"2\",\"false\"\n"
+    );
+}
+
+#[tokio::test]
+async fn always_quote_without_quote_numeric_output_leaves_numbers_unquoted() {
+    let output = output_of(
+        TransactionEngine::builder()
+            .with_output_always_quote(true)
+            .with_quote_numeric_output(false),
+    )
+    .await;
+    assert_eq!(
+        output,
+        b"\"client\",\"available\",\"held\",\"total\",\"locked\"\n1,1.5,0,1.5,\"false\"\n2,2,0,2,\"false\"\n"
+    );
+}
+
+#[tokio::test]
+async fn unquoted_numeric_fields_without_always_quote_match_the_default() {
+    let output = output_of(TransactionEngine::builder().with_quote_numeric_output(false)).await;
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1.5,0,1.5,false\n2,2,0,2,false\n"
+    );
+}
+
+#[tokio::test]
+async fn output_columns_select_and_order_the_columns() {
+    let output =
+        output_of(TransactionEngine::builder().with_output_columns(["client", "total"])).await;
+    assert_eq!(output, b"client,total\n1,1.5\n2,2\n");
+
+    let output =
+        output_of(TransactionEngine::builder().with_output_columns(["total", "client"])).await;
+    assert_eq!(output, b"total,client\n1.5,1\n2,2\n");
+}
+
+#[test]
+fn unknown_output_column_is_a_config_error() {
+    let result = TransactionEngine::builder()
+        .with_output_columns(["client", "balance"])
+        .try_build();
+
+    match result {
+        Err(TransactionError::InvalidConfig { errors }) => assert_eq!(
+            errors,
+            [ConfigError::UnknownOutputColumn {
+                name: "balance".to_string()
+            }]
+        ),
+        _ => panic!("expected an invalid config"),
+    }
+}
+
+#[tokio::test]
+async fn unknown_output_column_of_a_built_engine_is_not_dropped() {
+    let mut engine = TransactionEngine::builder()
+        .with_output_columns(["client", "balance"])
+        .build();
+
+    let mut output = Vec::new();
+    let result = engine.process(INPUT.as_bytes(), &mut output).await;
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::UnknownOutputColumn { name: "balance".to_string() }]
+    ));
+    assert!(output.is_empty());
+
+    let mut output = Vec::new();
+    let result = engine
+        .process_streaming(INPUT.as_bytes(), &mut output)
+        .await;
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { .. })
+    ));
+    assert!(output.is_empty());
+}
+
+#[tokio::test]
+async fn only_clients_with_applied_disputes_are_output() {
+    let input = "\
+type,client,tx,amount
+deposit,1,1,1.0
+deposit,2,2,2.0
+deposit,3,3,3.0
+deposit,4,4,4.0
+dispute,1,1,
+resolve,1,1,
+dispute,3,3,
+chargeback,3,3,
+dispute,4,9,
+";
+    let mut engine = TransactionEngine::builder()
+        .with_disputed_clients_only(true)
+        .build();
+    let mut output = Vec::new();
+    engine.process(input.as_bytes(), &mut output).await.unwrap();
+
+    // client 4's dispute referenced an unknown transaction and was never applied
+    assert_eq!(
+        output,
+        b"client,available,held,total,locked\n1,1,0,1,false\n3,0,0,0,true\n"
+    );
+}
+
+#[tokio::test]
+async fn locked_format_is_written_and_read_back() {
+    let input =
+        "type,client,tx,amount\ndeposit,1,1,1.0\ndeposit,2,2,2.0\ndispute,2,2,\nchargeback,2,2,\n";
+    for (format, unlocked, locked) in [
+        (LockedFormat::TrueFalse, "false", "true"),
+        (LockedFormat::OneZero, "0", "1"),
+        (LockedFormat::YesNo, "no", "yes"),
+    ]
+    .iter()
+    .copied()
+    {
+        let mut engine = TransactionEngine::builder()
+            .with_locked_field_format(format)
+            .build();
+        engine.process_str(input).await.unwrap();
+        let output = engine.to_csv_string().await.unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "client,available,held,total,locked\n1,1,0,1,{}\n2,0,0,0,{}\n",
+                unlocked, locked
+            )
+        );
+
+        let accounts = TransactionEngine::from_output_csv_str(&output)
+            .await
+            .unwrap();
+        let mut read_back = accounts
+            .values()
+            .map(|row| (row.client(), row.locked()))
+            .collect::<Vec<_>>();
+        read_back.sort_unstable();
+        assert_eq!(read_back, [(1, false), (2, true)], "{:?}", format);
+    }
+}
+
+#[tokio::test]
+async fn unknown_locked_value_fails_to_read_back() {
+    let result = TransactionEngine::from_output_csv_str(
+        "client,available,held,total,locked\n1,1,0,1,maybe\n",
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+/// Creates the accounts in the order 3, 1, 4, 2. Clients 1 and 4 tie on total, and client 1's funds are all held
+const SORT_INPUT: &str = "\
+type,client,tx,amount
+deposit,3,1,5.0
+deposit,1,2,20.0
+deposit,4,3,20.0
+deposit,2,4,8.0
+dispute,1,2,
+";
+
+/// The clients in the order they were output
+async fn client_order(sort_by: SortField) -> Vec<u16> {
+    let mut engine = TransactionEngine::builder()
+        .with_sort_output_by(sort_by)
+        .build();
+    engine.process_str(SORT_INPUT).await.unwrap();
+    engine
+        .to_csv_string()
+        .await
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap().parse().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn sorted_by_client_id() {
+    assert_eq!(client_order(SortField::ClientId).await, [1, 2, 3, 4]);
+}
+
+#[tokio::test]
+async fn sorted_by_total_balance() {
+    // ties are broken by client ID
+    assert_eq!(client_order(SortField::TotalBalance).await, [1, 4, 2, 3]);
+}
+
+#[tokio::test]
+async fn sorted_by_available_balance() {
+    assert_eq!(
+        client_order(SortField::AvailableBalance).await,
+        [4, 2, 3, 1]
+    );
+}
+
+#[tokio::test]
+async fn sorted_by_insertion_order() {
+    assert_eq!(client_order(SortField::InsertionOrder).await, [3, 1, 4, 2]);
+}
@@ -0,0 +1,80 @@
+use transactions_demo::tx_engine::{
+    ConfigError, Decimal, RejectionReason, TransactionEngine, TransactionError,
+};
+
+mod common;
+use common::process;
+
+const INPUT: &str =
+    "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,14.0\nwithdrawal,1,3,2.0\n";
+
+#[tokio::test]
+async fn overdraft_within_limit_is_applied_and_beyond_it_rejected() {
+    let mut engine = TransactionEngine::builder()
+        .with_overdraft_limit(Decimal::from(5))
+        .build();
+    let output = process(&mut engine, INPUT).await;
+
+    // the first withdrawal overdraws by 4, the second would overdraw by 6
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,-4,0,-4,false\n"
+    );
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::OverdraftLimitExceeded
+    );
+}
+
+#[tokio::test]
+async fn no_overdraft_by_default() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = process(&mut engine, INPUT).await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,8,0,8,false\n"
+    );
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::InsufficientFunds
+    );
+}
+
+#[test]
+fn overdraft_limit_must_be_valid() {
+    let result = TransactionEngine::builder()
+        .with_overdraft_limit(Decimal::from(-1))
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::NegativeValue { setting: "overdraft_limit", value: Decimal::from(-1) }]
+    ));
+
+    let result = TransactionEngine::builder()
+        .with_overdraft_limit(Decimal::ONE)
+        .with_min_available(Decimal::ZERO)
+        .try_build();
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::OverdraftLimitWithMinAvailable]
+    ));
+}
+
+#[tokio::test]
+async fn overdraft_limit_with_min_available_fails_before_processing() {
+    let mut engine = TransactionEngine::builder()
+        .with_overdraft_limit(Decimal::from(5))
+        .with_min_available(Decimal::from(-5))
+        .build();
+
+    assert!(matches!(
+        engine.process_str(INPUT).await,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::OverdraftLimitWithMinAvailable]
+    ));
+    assert!(engine.rejections().is_empty());
+}
@@ -0,0 +1,91 @@
+use std::fmt::Write;
+use transactions_demo::tx_engine::{TransactionEngine, TransactionEngineBuilder};
+
+/// (parse_workers, parse_chunk_size) pairs, including chunks of a single row and chunks larger than the input
+const PARSE_SETTINGS: [(usize, usize); 5] = [(2, 1), (2, 7), (3, 64), (4, 1000), (8, 100_000)];
+
+/// Deposits, withdrawals and disputes of a few clients in a fixed pseudo-random order, so that some withdrawals are
+/// rejected and some disputes refer to unknown transactions
+fn input(rows: u32) -> String {
+    let mut input = String::from("type,client,tx,amount\n");
+    let mut seed: u32 = 7;
+    for tx in 1..=rows {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        let client = (seed >> 16) % 20;
+        let amount = (seed >> 8) % 1000;
+        let written = match seed % 7 {
+            0..=2 => writeln!(input, "deposit,{},{},{}.25", client, tx, amount),
+            3 | 4 => writeln!(input, "withdrawal,{},{},{}.5", client, tx, amount / 4),
+            5 => writeln!(input, "dispute,{},{},", client, tx / 2),
+            _ => writeln!(input, "resolve,{},{},", client, tx / 2),
+        };
+        written.unwrap();
+    }
+    input
+}
+
+/// Output and rejections of processing `input`, or the error it failed with
+async fn run(builder: TransactionEngineBuilder, input: &str) -> Result<(String, String), String> {
+    let mut engine = builder.build();
+    let mut output = Vec::new();
+    engine
+        .process(input.as_bytes(), &mut output)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok((
+        String::from_utf8(output).unwrap(),
+        format!("{:?}", engine.rejections()),
+    ))
+}
+
+/// Asserts that every parse setting gives the same result as serial parsing
+async fn assert_matches_serial(
+    configure: fn(TransactionEngineBuilder) -> TransactionEngineBuilder,
+    input: &str,
+) {
+    let serial = run(configure(TransactionEngine::builder()), input).await;
+    for &(workers, chunk_size) in PARSE_SETTINGS.iter() {
+        let builder = TransactionEngine::builder()
+            .with_parse_workers(workers)
+            .with_parse_chunk_size(chunk_size);
+        assert_eq!(
+            run(configure(builder), input).await,
+            serial,
+            "{} workers, chunks of {} rows",
+            workers,
+            chunk_size
+        );
+    }
+}
+
+#[tokio::test]
+async fn concurrent_parsing_matches_serial_parsing() {
+    assert_matches_serial(|builder| builder, &input(5000)).await;
+}
+
+#[tokio::test]
+async fn malformed_middle_row_fails_like_serial_parsing() {
+    let rows = input(3000);
+    let mut malformed = rows.clone();
+    malformed.push_str("deposit,1,5,ten\n");
+    malformed.push_str(rows.split_once('\n').unwrap().1);
+
+    assert_matches_serial(|builder| builder, &malformed).await;
+}
+
+#[tokio::test]
+async fn partial_final_row_is_ignored_like_serial_parsing() {
+    let mut input = input(3000);
+    input.push_str("deposit,7000");
+
+    assert_matches_serial(
+        |builder| builder.with_ignore_partial_final_row(true),
+        &input,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn max_rows_stops_like_serial_parsing() {
+    assert_matches_serial(|builder| builder.with_max_rows(2500), &input(5000)).await;
+}
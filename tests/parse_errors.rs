@@ -0,0 +1,48 @@
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError};
+
+async fn parse_error(input: &str) -> TransactionError {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_bytes(input.as_bytes()).await.unwrap_err()
+}
+
+#[tokio::test]
+async fn unparseable_client_names_the_field() {
+    let err = parse_error("type,client,tx,amount\ndeposit,abc,1,1.0\n").await;
+
+    match err {
+        TransactionError::FieldParseError {
+            field,
+            value,
+            expected_type,
+        } => {
+            assert_eq!(field, "client");
+            assert_eq!(value, "abc");
+            assert_eq!(expected_type, "u16");
+        }
+        err => panic!("expected a field parse error, got {:?}", err),
+    }
+}
+
+#[tokio::test]
+async fn unparseable_amount_names_the_field() {
+    let err = parse_error("type,client,tx,amount\ndeposit,1,1,ten\n").await;
+
+    assert!(matches!(
+        err,
+        TransactionError::FieldParseError {
+            field: "amount",
+            expected_type: "decimal",
+            ..
+        }
+    ));
+}
+
+#[tokio::test]
+async fn repeated_column_is_a_duplicate_header() {
+    let err = parse_error("type,client,tx,amount,amount\ndeposit,1,1,1.0,2.0\n").await;
+
+    match err {
+        TransactionError::DuplicateHeader { name } => assert_eq!(name, "amount"),
+        err => panic!("expected a duplicate header, got {:?}", err),
+    }
+}
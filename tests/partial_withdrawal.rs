@@ -0,0 +1,90 @@
+use transactions_demo::tx_engine::{AuditFlag, Decimal, RejectionReason, TransactionEngine};
+
+mod common;
+use common::rejection_reasons;
+
+async fn partial_engine(input: &str) -> TransactionEngine {
+    let mut engine = TransactionEngine::builder()
+        .with_partial_withdrawal(true)
+        .build();
+    engine.process_str(input).await.unwrap();
+    engine
+}
+
+fn audit_flags(engine: &TransactionEngine) -> Vec<(u16, u32, AuditFlag)> {
+    engine
+        .audit_log()
+        .iter()
+        .map(|entry| (entry.client, entry.tx, entry.flag.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn overdrawing_withdrawal_drains_the_available_funds() {
+    let engine =
+        partial_engine("type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,15.0\n").await;
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0,0,0,false\n"
+    );
+    assert_eq!(
+        audit_flags(&engine),
+        [(
+            1,
+            2,
+            AuditFlag::PartialWithdrawal {
+                requested: Decimal::from(15),
+                withdrawn: Decimal::from(10),
+                shortfall: Decimal::from(5),
+            }
+        )]
+    );
+    assert!(engine.rejections().is_empty());
+}
+
+#[tokio::test]
+async fn withdrawing_exactly_the_available_funds_is_not_flagged() {
+    let engine =
+        partial_engine("type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,10.0\n").await;
+
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,0,0,0,false\n"
+    );
+    assert!(engine.audit_log().is_empty());
+    assert!(engine.rejections().is_empty());
+}
+
+#[tokio::test]
+async fn withdrawal_without_available_funds_is_rejected() {
+    let engine = partial_engine(
+        "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,10.0\nwithdrawal,1,3,5.0\n",
+    )
+    .await;
+
+    // nothing is left to withdraw, so there is no partial withdrawal to flag
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 3, RejectionReason::InsufficientFunds)]
+    );
+    assert!(engine.audit_log().is_empty());
+}
+
+#[tokio::test]
+async fn overdrawing_withdrawal_is_rejected_by_default() {
+    let mut engine = TransactionEngine::builder().build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,15.0\n")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rejection_reasons(&engine),
+        [(1, 2, RejectionReason::InsufficientFunds)]
+    );
+    assert_eq!(
+        engine.to_csv_string().await.unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n"
+    );
+}
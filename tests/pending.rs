@@ -0,0 +1,68 @@
+use transactions_demo::tx_engine::{Decimal, RejectionReason, TransactionEngine};
+
+mod common;
+use common::process;
+
+#[tokio::test]
+async fn settle_moves_pending_deposit_to_available() {
+    let mut engine = TransactionEngine::builder()
+        .with_track_pending(true)
+        .build();
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount,pending\ndeposit,1,1,5.0,true\ndeposit,1,2,1.0,\n",
+    )
+    .await;
+    assert_eq!(
+        output,
+        "client,available,held,pending,total,locked\n1,1,0,5,1,false\n"
+    );
+
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount,pending\ndeposit,1,1,5.0,true\nsettle,1,1,,\nsettle,1,1,,\n",
+    )
+    .await;
+    // settling twice credits the deposit once
+    assert_eq!(
+        output,
+        "client,available,held,pending,total,locked\n1,5,0,0,5,false\n"
+    );
+}
+
+#[tokio::test]
+async fn dispute_holds_pending_funds() {
+    let mut engine = TransactionEngine::builder()
+        .with_track_pending(true)
+        .build();
+    let input = "\
+type,client,tx,amount,pending
+deposit,1,1,5.0,true
+dispute,1,1,,
+resolve,1,1,,
+settle,1,1,,
+";
+    let output = process(&mut engine, input).await;
+
+    // the dispute took the funds out of pending, so the resolve released them and the settle had nothing left to do
+    assert_eq!(
+        output,
+        "client,available,held,pending,total,locked\n1,5,0,0,5,false\n"
+    );
+}
+
+#[tokio::test]
+async fn pending_deposit_without_tracking_is_rejected() {
+    let mut engine = TransactionEngine::builder().build();
+    process(
+        &mut engine,
+        "type,client,tx,amount,pending\ndeposit,1,1,5.0,true\n",
+    )
+    .await;
+
+    assert_eq!(
+        engine.rejections()[0].reason,
+        RejectionReason::PendingNotTracked
+    );
+    assert_eq!(engine.full_report().accounts[0].total(), Decimal::ZERO);
+}
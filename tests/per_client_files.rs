@@ -0,0 +1,132 @@
+#![cfg(unix)]
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError};
+
+/// Creates an empty directory unique to the test
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("tx-per-client-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn mkfifo(path: &Path) {
+    let status = Command::new("mkfifo").arg(path).status().unwrap();
+    assert!(status.success());
+}
+
+#[tokio::test]
+async fn merges_accounts_of_every_file() {
+    let dir = test_dir("merge");
+    let (first, second) = (dir.join("client1.csv"), dir.join("client2.csv"));
+    fs::write(
+        &first,
+        "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,4.0\n",
+    )
+    .unwrap();
+    fs::write(
+        &second,
+        "type,client,tx,amount\ndeposit,2,3,5.0\ndispute,2,3,\n",
+    )
+    .unwrap();
+
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    engine
+        .process_per_client_files(&[&second, &first], &mut output)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,6,0,6,false\n2,0,5,5,false\n"
+    );
+    assert_eq!(engine.summary().transactions_processed, 4);
+    assert_eq!(engine.dispute_summary().opened, 1);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn processes_files_concurrently() {
+    let dir = test_dir("concurrent");
+    let (first, second) = (dir.join("client1.csv"), dir.join("client2.csv"));
+    mkfifo(&first);
+    mkfifo(&second);
+
+    // opening a FIFO for writing blocks until it is opened for reading. Writing the second file before the first only
+    // completes if both are read at the same time
+    let writer = {
+        let (first, second) = (first.clone(), second.clone());
+        std::thread::spawn(move || {
+            for (path, contents) in [
+                (second, "type,client,tx,amount\ndeposit,2,2,2.0\n"),
+                (first, "type,client,tx,amount\ndeposit,1,1,1.0\n"),
+            ] {
+                fs::OpenOptions::new()
+                    .write(true)
+                    .open(path)
+                    .unwrap()
+                    .write_all(contents.as_bytes())
+                    .unwrap();
+            }
+        })
+    };
+
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    tokio::time::timeout(
+        Duration::from_secs(10),
+        engine.process_per_client_files(&[&first, &second], &mut output),
+    )
+    .await
+    .expect("the files were not read concurrently")
+    .unwrap();
+    writer.join().unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,1,0,1,false\n2,2,0,2,false\n"
+    );
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn failing_file_stops_the_others() {
+    let dir = test_dir("failing");
+    let (pending, missing) = (dir.join("pending.csv"), dir.join("missing.csv"));
+    // never written to while processing, so reading it never completes
+    mkfifo(&pending);
+
+    let mut engine = TransactionEngine::builder().build();
+    let result = tokio::time::timeout(
+        Duration::from_secs(10),
+        engine.process_per_client_files(&[&pending, &missing], tokio::io::sink()),
+    )
+    .await
+    .expect("waited for the remaining files after a failure");
+    assert!(matches!(result, Err(TransactionError::Io(_))));
+
+    // unblocks the thread still opening the FIFO
+    drop(fs::OpenOptions::new().write(true).open(&pending).unwrap());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn client_in_two_files_is_an_error() {
+    let dir = test_dir("duplicate");
+    let (first, second) = (dir.join("a.csv"), dir.join("b.csv"));
+    fs::write(&first, "type,client,tx,amount\ndeposit,1,1,1.0\n").unwrap();
+    fs::write(&second, "type,client,tx,amount\ndeposit,1,2,1.0\n").unwrap();
+
+    let mut engine = TransactionEngine::builder().build();
+    let result = engine
+        .process_per_client_files(&[&first, &second], tokio::io::sink())
+        .await;
+    assert!(matches!(result, Err(TransactionError::DuplicateClient(1))));
+    fs::remove_dir_all(dir).unwrap();
+}
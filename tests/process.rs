@@ -0,0 +1,87 @@
+use transactions_demo::tx_engine::{Decimal, TransactionEngine};
+
+mod common;
+use common::process;
+
+#[tokio::test]
+async fn process_writes_balances_of_every_client() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,2,2,5.5\nwithdrawal,1,3,2.5\n",
+    )
+    .await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n2,5.5,0,5.5,false\n"
+    );
+}
+
+#[tokio::test]
+async fn each_process_call_starts_from_an_empty_state() {
+    let mut engine = TransactionEngine::builder().build();
+    process(&mut engine, "type,client,tx,amount\ndeposit,1,1,10.0\n").await;
+
+    // the deposit of the first input is gone, so the withdrawal is rejected
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount\ndeposit,2,2,3.0\nwithdrawal,1,3,4.0\n",
+    )
+    .await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,0,0,0,false\n2,3,0,3,false\n"
+    );
+    assert_eq!(engine.rejections().len(), 1);
+    assert_eq!(engine.summary().transactions_processed, 2);
+    assert_eq!(engine.full_report().accounts[1].total(), Decimal::new(3, 0));
+}
+
+#[tokio::test]
+async fn sample_output_matches_the_sample_input() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = process(&mut engine, include_str!("../inputs/single_entry.csv")).await;
+
+    assert_eq!(output, include_str!("../out.csv"));
+}
+
+#[tokio::test]
+async fn balances_applied_before_an_error_are_flushed_when_configured() {
+    let input = "type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,2,2,5.0\ndeposit,1,3,abc\ndeposit,1,4,1.0\n";
+
+    let mut output = Vec::new();
+    let mut engine = TransactionEngine::builder()
+        .with_flush_partial_output_on_error(true)
+        .build();
+    assert!(engine.process(input.as_bytes(), &mut output).await.is_err());
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,10,0,10,false\n2,5,0,5,false\n"
+    );
+
+    // nothing is written by default
+    let mut output = Vec::new();
+    let mut engine = TransactionEngine::builder().build();
+    assert!(engine.process(input.as_bytes(), &mut output).await.is_err());
+    assert!(output.is_empty());
+}
+
+#[tokio::test]
+async fn processing_stops_after_max_rows() {
+    let mut engine = TransactionEngine::builder().with_max_rows(3).build();
+    // the rejected withdrawal counts towards the limit, and the unparseable row after it is never read
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,50.0\ndeposit,2,3,5.0\ndeposit,abc,4,1.0\ndeposit,1,5,1.0\n",
+    )
+    .await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,10,0,10,false\n2,5,0,5,false\n"
+    );
+    assert_eq!(engine.summary().transactions_processed, 3);
+    assert_eq!(engine.summary().rejections, 1);
+}
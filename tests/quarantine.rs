@@ -0,0 +1,51 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,1,2,abc
+refund,2,3,1.0
+deposit,2,4,-1.0
+withdrawal,1,5,2.5
+";
+
+#[tokio::test]
+async fn bad_rows_are_quarantined_and_good_rows_applied() {
+    let mut engine = TransactionEngine::builder().build();
+    let (mut output, mut quarantine) = (Vec::new(), Vec::new());
+    let quarantined = engine
+        .process_with_quarantine(INPUT.as_bytes(), &mut output, &mut quarantine)
+        .await
+        .unwrap();
+
+    assert_eq!(quarantined, 3);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n"
+    );
+    // each bad row verbatim, with its line in the input and why it was quarantined
+    assert_eq!(
+        String::from_utf8(quarantine).unwrap(),
+        "type,client,tx,amount,line,error\n\
+         deposit,1,2,abc,3,\"Invalid value for field amount: \"\"abc\"\" is not a valid decimal\"\n\
+         refund,2,3,1.0,4,Invalid transaction type: refund\n\
+         deposit,2,4,-1.0,5,Transaction 4 has an invalid amount: -1\n"
+    );
+}
+
+#[tokio::test]
+async fn quarantine_has_only_the_header_without_bad_rows() {
+    let mut engine = TransactionEngine::builder().build();
+    let mut quarantine = Vec::new();
+    let quarantined = engine
+        .process_with_quarantine(
+            "type,client,tx,amount\ndeposit,1,1,1.0\n".as_bytes(),
+            tokio::io::sink(),
+            &mut quarantine,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(quarantined, 0);
+    assert_eq!(quarantine, b"type,client,tx,amount,line,error\n");
+}
@@ -0,0 +1,45 @@
+use std::sync::{Arc, Mutex};
+use transactions_demo::tx_engine::{Rejection, RejectionReason, RejectionSink, TransactionEngine};
+
+mod common;
+use common::rejection_reasons;
+
+/// Collects every rejection it receives, shared with the test through the `Arc`
+#[derive(Clone, Default)]
+struct CollectingSink(Arc<Mutex<Vec<Rejection>>>);
+
+impl RejectionSink for CollectingSink {
+    fn record(&self, rejection: &Rejection) {
+        self.0.lock().unwrap().push(rejection.clone());
+    }
+}
+
+#[tokio::test]
+async fn sink_receives_every_rejection_in_order() {
+    let sink = CollectingSink::default();
+    let mut engine = TransactionEngine::builder()
+        .with_rejection_sink(sink.clone())
+        .build();
+    engine
+        .process_str("type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,50.0\ndispute,1,9,\ndispute,1,1,\ndispute,1,1,\n")
+        .await
+        .unwrap();
+
+    let received = sink
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|rejection| (rejection.client, rejection.tx, rejection.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        received,
+        [
+            (1, 2, RejectionReason::InsufficientFunds),
+            (1, 9, RejectionReason::TransactionNotFound),
+            (1, 1, RejectionReason::AlreadyDisputed)
+        ]
+    );
+    // the engine still keeps its own list
+    assert_eq!(received, rejection_reasons(&engine));
+}
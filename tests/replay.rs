@@ -0,0 +1,76 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+const LOG: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+deposit,2,2,5.0
+withdrawal,1,3,2.5
+dispute,2,2,
+";
+
+async fn expected_balances() -> String {
+    let mut engine = TransactionEngine::builder().build();
+    engine.process_str(LOG).await.unwrap();
+    engine.to_csv_string().await.unwrap()
+}
+
+#[tokio::test]
+async fn log_replays_to_matching_balances() {
+    let expected = TransactionEngine::from_output_csv_str(&expected_balances().await)
+        .await
+        .unwrap();
+
+    let mut engine = TransactionEngine::builder().build();
+    let diverging = engine
+        .verify_replay(LOG.as_bytes(), &expected)
+        .await
+        .unwrap();
+    assert!(diverging.is_empty());
+}
+
+#[tokio::test]
+async fn tampered_log_is_detected() {
+    let expected = TransactionEngine::from_output_csv_str(&expected_balances().await)
+        .await
+        .unwrap();
+    let tampered = LOG.replace("withdrawal,1,3,2.5", "withdrawal,1,3,0.5");
+
+    let mut engine = TransactionEngine::builder().build();
+    let diverging = engine
+        .verify_replay(tampered.as_bytes(), &expected)
+        .await
+        .unwrap();
+    assert_eq!(diverging, [(1, String::new(), String::new())]);
+
+    let mut delta = Vec::new();
+    engine
+        .write_delta_output(&expected, &mut delta)
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(delta).unwrap(),
+        "client,available_delta,held_delta,total_delta,was_locked,locked\n1,2.0,0,2.0,false,false\n"
+    );
+}
+
+#[tokio::test]
+async fn accounts_missing_from_either_side_diverge() {
+    let expected = TransactionEngine::from_output_csv_str(&expected_balances().await)
+        .await
+        .unwrap();
+    // client 2's rows were dropped from the log, and client 3 was added
+    let tampered = "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,3,2.5\ndeposit,3,4,1.0\n";
+
+    let mut engine = TransactionEngine::builder().build();
+    let diverging = engine
+        .verify_replay(tampered.as_bytes(), &expected)
+        .await
+        .unwrap();
+    assert_eq!(
+        diverging,
+        [
+            (2, String::new(), String::new()),
+            (3, String::new(), String::new())
+        ]
+    );
+}
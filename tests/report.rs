@@ -0,0 +1,210 @@
+use transactions_demo::tx_engine::{
+    AuditFlag, Decimal, RejectionReason, SkipReason, TransactionEngine,
+};
+
+/// Touches every section of the report: two accounts, an open dispute, a rejection, a skipped row and a flagged
+/// partial withdrawal
+const INPUT: &str = "\
+type,client,tx,amount,category
+deposit,1,1,10.0,
+deposit,2,2,4.0,
+withdrawal,2,3,6.0,
+deposit,1,4,3.0,
+dispute,1,4,,
+dispute,1,99,,
+deposit,1,5,1.0,gambling
+";
+
+async fn report_engine() -> TransactionEngine {
+    let mut engine = TransactionEngine::builder()
+        .with_partial_withdrawal(true)
+        .with_allowed_categories(["retail"])
+        .build();
+    engine.process_str(INPUT).await.unwrap();
+    engine
+}
+
+#[tokio::test]
+async fn report_lists_the_accounts_by_client() {
+    let report = report_engine().await.full_report();
+
+    let accounts = report
+        .accounts
+        .iter()
+        .map(|account| {
+            (
+                account.client(),
+                account.available(),
+                account.held(),
+                account.total(),
+                account.locked(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        accounts,
+        [
+            (
+                1,
+                Decimal::from(10),
+                Decimal::from(3),
+                Decimal::from(13),
+                false
+            ),
+            (2, Decimal::ZERO, Decimal::ZERO, Decimal::ZERO, false),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn report_lists_the_open_disputes() {
+    let report = report_engine().await.full_report();
+
+    assert_eq!(report.open_disputes.len(), 1);
+    let dispute = &report.open_disputes[0];
+    assert_eq!((dispute.client, dispute.tx), (1, 4));
+    assert_eq!(dispute.amount, Decimal::from(3));
+}
+
+#[tokio::test]
+async fn report_lists_the_rejections() {
+    let report = report_engine().await.full_report();
+
+    assert_eq!(report.rejections.len(), 1);
+    let rejection = &report.rejections[0];
+    assert_eq!((rejection.client, rejection.tx), (1, 99));
+    assert_eq!(rejection.reason, RejectionReason::TransactionNotFound);
+}
+
+#[tokio::test]
+async fn report_lists_the_skipped_rows() {
+    let report = report_engine().await.full_report();
+
+    assert_eq!(report.skipped.len(), 1);
+    let skipped = &report.skipped[0];
+    assert_eq!((skipped.client, skipped.tx), (1, 5));
+    assert_eq!(
+        skipped.reason,
+        SkipReason::CategoryNotAllowed("gambling".to_string())
+    );
+}
+
+#[tokio::test]
+async fn report_lists_the_flagged_transactions() {
+    let report = report_engine().await.full_report();
+
+    assert_eq!(report.audit_log.len(), 1);
+    let entry = &report.audit_log[0];
+    assert_eq!((entry.client, entry.tx), (2, 3));
+    assert!(matches!(
+        entry.flag,
+        AuditFlag::PartialWithdrawal { requested, withdrawn, shortfall }
+            if requested == Decimal::from(6) && withdrawn == Decimal::from(4) && shortfall == Decimal::from(2)
+    ));
+}
+
+#[tokio::test]
+async fn report_summarizes_the_run() {
+    let report = report_engine().await.full_report();
+
+    let summary = &report.summary;
+    assert_eq!(summary.transactions_processed, 6);
+    assert_eq!(summary.clients, 2);
+    assert_eq!(summary.locked_clients, 0);
+    assert_eq!(summary.open_disputes, 1);
+    assert_eq!(summary.rejections, 1);
+    assert_eq!(summary.skipped, 1);
+    assert_eq!(summary.flagged, 1);
+}
+
+#[tokio::test]
+async fn report_serializes_every_section() {
+    let report = report_engine().await.full_report();
+    let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+
+    for section in [
+        "accounts",
+        "open_disputes",
+        "rejections",
+        "skipped",
+        "audit_log",
+        "summary",
+    ] {
+        assert!(json.get(section).is_some(), "missing section {}", section);
+    }
+    assert!(json.get("terminal_width").is_none());
+}
+
+/// Splits a line of the displayed table into its trimmed cells
+fn table_cells(line: &str) -> Vec<&str> {
+    line.split('|').map(str::trim).collect()
+}
+
+/// The client, amount columns parsed as decimals, and the remaining columns of a displayed account
+fn account_cells(line: &str) -> (&str, Vec<Decimal>, Vec<&str>) {
+    let cells = table_cells(line);
+    let amounts = cells[1..4]
+        .iter()
+        .map(|cell| cell.parse().unwrap())
+        .collect();
+    (cells[0], amounts, cells[4..].to_vec())
+}
+
+#[tokio::test]
+async fn report_displays_the_accounts_by_total_descending() {
+    let table = report_engine().await.report().to_string();
+    let lines = table.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 4);
+    assert!(lines.iter().all(|line| line.len() <= 80));
+    assert_eq!(
+        table_cells(lines[0]),
+        [
+            "client",
+            "available",
+            "held",
+            "total",
+            "locked",
+            "disputes",
+            "transactions"
+        ]
+    );
+    // every row of client 1 counts, including the dispute that was rejected
+    assert_eq!(
+        account_cells(lines[2]),
+        (
+            "1",
+            vec![Decimal::from(10), Decimal::from(3), Decimal::from(13)],
+            vec!["false", "1", "4"]
+        )
+    );
+    assert_eq!(
+        account_cells(lines[3]),
+        (
+            "2",
+            vec![Decimal::ZERO, Decimal::ZERO, Decimal::ZERO],
+            vec!["false", "0", "2"]
+        )
+    );
+}
+
+#[tokio::test]
+async fn report_displays_a_row_per_ledger() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    engine
+        .process_str("type,client,tx,amount,ledger\ndeposit,1,1,10.0,savings\ndeposit,1,2,5.0,\ndispute,1,1,,\n")
+        .await
+        .unwrap();
+    let report = engine.report();
+
+    assert_eq!(report.open_disputes[0].ledger.as_deref(), Some("savings"));
+    let table = report.to_string();
+    let lines = table.lines().map(table_cells).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[0][..2], ["client", "ledger"]);
+    // the dispute is only counted for the ledger of the deposit it references
+    assert_eq!(lines[2][..2], ["1", "savings"]);
+    assert_eq!(lines[2][5..], ["false", "1", "2"]);
+    assert_eq!(lines[3][..2], ["1", ""]);
+    assert_eq!(lines[3][5..], ["false", "0", "1"]);
+}
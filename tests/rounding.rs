@@ -0,0 +1,72 @@
+use transactions_demo::tx_engine::{AmountRounding, TransactionEngine};
+
+/// Balances output after applying `input` with `rounding`
+async fn balances(rounding: AmountRounding, input: &str) -> String {
+    let mut engine = TransactionEngine::builder()
+        .with_amount_rounding(rounding)
+        .build();
+    engine.process_str(input).await.unwrap();
+    engine.to_csv_string().await.unwrap()
+}
+
+#[tokio::test]
+async fn deposit_is_rounded_to_four_decimal_places() {
+    let input = "type,client,tx,amount\ndeposit,1,1,1.23456\n";
+    for (rounding, expected) in [
+        (AmountRounding::HalfUp, "1.2346"),
+        (AmountRounding::HalfEven, "1.2346"),
+        (AmountRounding::Truncate, "1.2345"),
+    ] {
+        assert_eq!(
+            balances(rounding, input).await,
+            format!(
+                "client,available,held,total,locked\n1,{0},0,{0},false\n",
+                expected
+            ),
+            "{:?}",
+            rounding
+        );
+    }
+}
+
+#[tokio::test]
+async fn midpoint_depends_on_the_rounding_mode() {
+    let input = "type,client,tx,amount\ndeposit,1,1,1.23445\n";
+    for (rounding, expected) in [
+        (AmountRounding::HalfUp, "1.2345"),
+        (AmountRounding::HalfEven, "1.2344"),
+        (AmountRounding::Truncate, "1.2344"),
+    ] {
+        assert_eq!(
+            balances(rounding, input).await,
+            format!(
+                "client,available,held,total,locked\n1,{0},0,{0},false\n",
+                expected
+            ),
+            "{:?}",
+            rounding
+        );
+    }
+}
+
+#[tokio::test]
+async fn withdrawal_is_rounded_before_it_is_applied() {
+    let input = "type,client,tx,amount\ndeposit,1,1,2.0\nwithdrawal,1,2,1.23456\n";
+    assert_eq!(
+        balances(AmountRounding::HalfUp, input).await,
+        "client,available,held,total,locked\n1,0.7654,0,0.7654,false\n"
+    );
+    assert_eq!(
+        balances(AmountRounding::Truncate, input).await,
+        "client,available,held,total,locked\n1,0.7655,0,0.7655,false\n"
+    );
+}
+
+#[tokio::test]
+async fn history_stores_the_rounded_amount() {
+    let input = "type,client,tx,amount\ndeposit,1,1,1.23456\ndispute,1,1,\n";
+    assert_eq!(
+        balances(AmountRounding::Truncate, input).await,
+        "client,available,held,total,locked\n1,0.0000,1.2345,1.2345,false\n"
+    );
+}
@@ -0,0 +1,80 @@
+use transactions_demo::tx_engine::{RejectionReason, TransactionEngine, TransactionEngineBuilder};
+
+/// Deposits 10 for client 1 as tx 1, then applies `row`
+async fn rejections_after(builder: TransactionEngineBuilder, row: &str) -> Vec<RejectionReason> {
+    let mut engine = builder.with_validate_row_shape(true).build();
+    engine
+        .process_str(&format!(
+            "type,client,tx,amount,target_client\ndeposit,1,1,10.0,\n{}\n",
+            row
+        ))
+        .await
+        .unwrap();
+    engine
+        .rejections()
+        .iter()
+        .map(|rejection| rejection.reason)
+        .collect()
+}
+
+#[tokio::test]
+async fn deposit_without_amount_is_rejected() {
+    let rejections = rejections_after(TransactionEngine::builder(), "deposit,1,2,,").await;
+    assert_eq!(rejections, [RejectionReason::MissingAmount]);
+}
+
+#[tokio::test]
+async fn withdrawal_without_amount_is_rejected() {
+    let rejections = rejections_after(TransactionEngine::builder(), "withdrawal,1,2,,").await;
+    assert_eq!(rejections, [RejectionReason::MissingAmount]);
+}
+
+#[tokio::test]
+async fn transfer_without_amount_is_rejected() {
+    let rejections = rejections_after(TransactionEngine::builder(), "transfer,1,2,,2").await;
+    assert_eq!(rejections, [RejectionReason::MissingAmount]);
+}
+
+#[tokio::test]
+async fn dispute_with_amount_is_rejected() {
+    let rejections = rejections_after(TransactionEngine::builder(), "dispute,1,1,10.0,").await;
+    assert_eq!(rejections, [RejectionReason::UnexpectedAmount]);
+
+    // the amount is compared with the disputed transaction's instead
+    let rejections = rejections_after(
+        TransactionEngine::builder().with_match_dispute_amounts(true),
+        "dispute,1,1,10.0,",
+    )
+    .await;
+    assert!(rejections.is_empty());
+}
+
+#[tokio::test]
+async fn resolve_with_amount_is_rejected() {
+    let rejections = rejections_after(
+        TransactionEngine::builder(),
+        "dispute,1,1,,\nresolve,1,1,10.0,",
+    )
+    .await;
+    assert_eq!(rejections, [RejectionReason::UnexpectedAmount]);
+}
+
+#[tokio::test]
+async fn chargeback_with_amount_is_rejected() {
+    let rejections = rejections_after(
+        TransactionEngine::builder(),
+        "dispute,1,1,,\nchargeback,1,1,10.0,",
+    )
+    .await;
+    assert_eq!(rejections, [RejectionReason::UnexpectedAmount]);
+}
+
+#[tokio::test]
+async fn well_formed_rows_are_applied() {
+    let rejections = rejections_after(
+        TransactionEngine::builder(),
+        "withdrawal,1,2,1.0,\ntransfer,1,3,1.0,2\ndispute,1,1,,\nresolve,1,1,,",
+    )
+    .await;
+    assert!(rejections.is_empty());
+}
@@ -0,0 +1,73 @@
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+use transactions_demo::tx_engine::TransactionEngine;
+
+const INPUT: &str = "type,client,tx,amount\ndeposit,1,1,10.0\nwithdrawal,1,2,2.5\n";
+
+/// Serves `INPUT` as the body of every request like an S3 `GetObject`, recording the request lines
+async fn serve_object() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let recorded = recorded.clone();
+            tokio::spawn(async move {
+                let mut stream = BufReader::new(stream);
+                let mut request_line = String::new();
+                stream.read_line(&mut request_line).await.unwrap();
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push(request_line.trim_end().to_string());
+
+                // the headers are not needed, but must be read before responding
+                let mut line = String::new();
+                while stream.read_line(&mut line).await.unwrap() > 2 {
+                    line.clear();
+                }
+
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"1\"\r\nLast-Modified: Tue, 15 Nov 1994 08:12:31 GMT\r\nConnection: close\r\n\r\n{}",
+                    INPUT.len(),
+                    INPUT
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            });
+        }
+    });
+
+    (endpoint, requests)
+}
+
+#[tokio::test]
+async fn s3_object_is_processed_like_a_local_file() {
+    let (endpoint, requests) = serve_object().await;
+    // the only test in this binary, so the environment is not shared with another test
+    std::env::set_var("AWS_ENDPOINT", &endpoint);
+    std::env::set_var("AWS_ALLOW_HTTP", "true");
+    std::env::set_var("AWS_REGION", "us-east-1");
+    std::env::set_var("AWS_ACCESS_KEY_ID", "test");
+    std::env::set_var("AWS_SECRET_ACCESS_KEY", "test");
+
+    let mut output = Vec::new();
+    TransactionEngine::builder()
+        .build()
+        .process_file("s3://transactions/daily/input.csv", &mut output)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n"
+    );
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["GET /transactions/daily/input.csv HTTP/1.1"]
+    );
+}
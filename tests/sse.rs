@@ -0,0 +1,23 @@
+use transactions_demo::tx_engine::TransactionEngine;
+
+#[tokio::test]
+async fn each_update_is_a_data_event() {
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    engine
+        .process_sse(
+            "type,client,tx,amount\ndeposit,1,1,10.0\ndeposit,2,2,2.5\ndispute,1,1,\n".as_bytes(),
+            &mut output,
+        )
+        .await
+        .unwrap();
+
+    // one event per update, in the order the rows were applied, each ended by a blank line
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\
+data: {\"client\":1,\"available\":\"10\",\"held\":\"0\",\"total\":\"10\",\"locked\":false}\n\n\
+data: {\"client\":2,\"available\":\"2.5\",\"held\":\"0\",\"total\":\"2.5\",\"locked\":false}\n\n\
+data: {\"client\":1,\"available\":\"0\",\"held\":\"10\",\"total\":\"10\",\"locked\":false}\n\n"
+    );
+}
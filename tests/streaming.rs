@@ -0,0 +1,161 @@
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use transactions_demo::tx_engine::TransactionEngine;
+
+const ROWS: u64 = 100_000;
+const MAX_IN_FLIGHT_ROWS: usize = 8;
+
+/// Generates `ROWS` deposits one row per read, counting the rows handed out
+struct CountingInput {
+    rows_read: Arc<AtomicU64>,
+    header_sent: bool,
+}
+
+impl AsyncRead for CountingInput {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !self.header_sent {
+            self.header_sent = true;
+            buf.put_slice(b"type,client,tx,amount\n");
+            return Poll::Ready(Ok(()));
+        }
+
+        let row = self.rows_read.load(Ordering::SeqCst);
+        if row < ROWS {
+            let line = format!("deposit,{},{},1.0\n", row % 100, row);
+            buf.put_slice(line.as_bytes());
+            self.rows_read.fetch_add(1, Ordering::SeqCst);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Accepts no bytes until it is opened, simulating a consumer that stalls
+#[derive(Clone, Default)]
+struct GatedOutput {
+    open: Arc<AtomicBool>,
+    waker: Arc<Mutex<Option<Waker>>>,
+    written: Arc<Mutex<Vec<u8>>>,
+}
+
+impl GatedOutput {
+    fn open(&self) {
+        self.open.store(true, Ordering::SeqCst);
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+    }
+}
+
+impl AsyncWrite for GatedOutput {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if !self.open.load(Ordering::SeqCst) {
+            *self.waker.lock().unwrap() = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        self.written.lock().unwrap().extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if !self.open.load(Ordering::SeqCst) {
+            *self.waker.lock().unwrap() = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// Waits until no further rows are read for a while
+async fn wait_until_stalled(rows_read: &AtomicU64) -> u64 {
+    let mut last = rows_read.load(Ordering::SeqCst);
+    loop {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let now = rows_read.load(Ordering::SeqCst);
+        if now == last {
+            return now;
+        }
+        last = now;
+    }
+}
+
+#[tokio::test]
+async fn stalled_output_stops_reading_input() {
+    let rows_read = Arc::new(AtomicU64::new(0));
+    let input = CountingInput {
+        rows_read: rows_read.clone(),
+        header_sent: false,
+    };
+    let output = GatedOutput::default();
+
+    let mut engine = TransactionEngine::builder()
+        .with_max_in_flight_rows(MAX_IN_FLIGHT_ROWS)
+        .with_output_buffer_size(64)
+        .build();
+    let writer = output.clone();
+    let processing = tokio::spawn(async move {
+        engine
+            .process_streaming(input, writer)
+            .await
+            .map(|_| engine)
+    });
+
+    // only what fits in the channel and the serializer's buffers is read ahead of the stalled output
+    let stalled_at = wait_until_stalled(&rows_read).await;
+    assert!(stalled_at > 0);
+    assert!(
+        stalled_at < 100,
+        "read {} rows ahead of a stalled output",
+        stalled_at
+    );
+
+    output.open();
+    let engine = processing.await.unwrap().unwrap();
+    assert_eq!(rows_read.load(Ordering::SeqCst), ROWS);
+    assert_eq!(engine.summary().transactions_processed, ROWS);
+
+    let written = output.written.lock().unwrap();
+    let lines = written
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.is_empty());
+    // one update per deposit, after the header
+    assert_eq!(lines.count() as u64, ROWS + 1);
+}
+
+#[tokio::test]
+async fn streaming_writes_update_after_each_transaction() {
+    let mut engine = TransactionEngine::builder().build();
+    let mut output = Vec::new();
+    engine
+        .process_streaming(
+            "type,client,tx,amount\ndeposit,1,1,2.0\ndeposit,2,2,1.0\nwithdrawal,1,3,0.5\n"
+                .as_bytes(),
+            &mut output,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,2,0,2,false\n2,1,0,1,false\n1,1.5,0,1.5,false\n"
+    );
+}
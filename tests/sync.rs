@@ -0,0 +1,90 @@
+use transactions_demo::tx_engine::{
+    ConfigError, Decimal, OutputValidationError, TransactionEngine, TransactionError,
+};
+
+const INPUT: &str = "\
+type,client,tx,amount
+deposit,1,1,10.0
+withdrawal,1,2,2.5
+deposit,2,3,1.0
+dispute,2,3,
+";
+
+fn process_sync(engine: &mut TransactionEngine, input: &str) -> String {
+    let mut output = Vec::new();
+    engine.process_sync(input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn process_sync_writes_balances() {
+    let mut engine = TransactionEngine::builder().build();
+
+    assert_eq!(
+        process_sync(&mut engine, INPUT),
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n2,0,1,1,false\n"
+    );
+}
+
+#[test]
+fn process_sync_starts_from_an_empty_state() {
+    let mut engine = TransactionEngine::builder().build();
+    process_sync(&mut engine, INPUT);
+
+    assert_eq!(
+        process_sync(&mut engine, "type,client,tx,amount\ndeposit,3,4,1.0\n"),
+        "client,available,held,total,locked\n3,1,0,1,false\n"
+    );
+    assert_eq!(engine.client_ids(), [3]);
+}
+
+#[test]
+fn process_sync_validates_output_when_configured() {
+    let mut engine = TransactionEngine::builder()
+        .with_validate_output(true)
+        .build();
+
+    assert_eq!(
+        process_sync(&mut engine, INPUT),
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n2,0,1,1,false\n"
+    );
+    assert_eq!(
+        engine.validate_output_sync("client,available,held,total,locked\n1,7.5,0,8,false\n"),
+        Err(vec![OutputValidationError::BalanceMismatch {
+            line: 2,
+            available: Decimal::new(75, 1),
+            held: Decimal::ZERO,
+            total: Decimal::from(8),
+        }])
+    );
+}
+
+#[test]
+fn process_sync_rejects_unknown_output_columns() {
+    let mut engine = TransactionEngine::builder()
+        .with_output_columns(["client", "balance"])
+        .build();
+
+    let mut output = Vec::new();
+    let result = engine.process_sync("type,client,tx,amount\n".as_bytes(), &mut output);
+    assert!(matches!(
+        result,
+        Err(TransactionError::InvalidConfig { errors })
+            if errors == [ConfigError::UnknownOutputColumn { name: "balance".to_string() }]
+    ));
+    assert!(output.is_empty());
+}
+
+#[test]
+fn process_sync_rejects_a_repeated_column() {
+    let mut engine = TransactionEngine::builder().build();
+
+    let result = engine.process_sync(
+        "type,client,tx,client,amount\ndeposit,1,1,2,1.0\n".as_bytes(),
+        Vec::new(),
+    );
+    assert!(matches!(
+        result,
+        Err(TransactionError::DuplicateHeader { name }) if name == "client"
+    ));
+}
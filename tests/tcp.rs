@@ -0,0 +1,93 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use transactions_demo::tx_engine::{TransactionEngine, TransactionError};
+
+/// An address on the loopback interface with a port that was free a moment ago
+fn free_addr() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+/// Connects once the server has bound the address
+async fn connect(addr: SocketAddr) -> TcpStream {
+    for _ in 0..100 {
+        if let Ok(stream) = TcpStream::connect(addr).await {
+            return stream;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    panic!("the server did not bind {}", addr);
+}
+
+async fn round_trip(addr: SocketAddr, input: &str) -> String {
+    let mut stream = connect(addr).await;
+    stream.write_all(input.as_bytes()).await.unwrap();
+    stream.shutdown().await.unwrap();
+
+    let mut output = String::new();
+    stream.read_to_string(&mut output).await.unwrap();
+    output
+}
+
+#[tokio::test]
+async fn serves_balances_over_tcp() {
+    let addr = free_addr();
+    let engine = TransactionEngine::builder().build();
+    let server = tokio::spawn(async move { engine.serve_tcp(addr).await });
+
+    let output = round_trip(
+        addr,
+        "type,client,tx,amount\ndeposit,1,1,3.0\ndeposit,2,2,1.5\ndispute,1,1,\n",
+    )
+    .await;
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,0,3,3,false\n2,1.5,0,1.5,false\n"
+    );
+
+    // every connection is processed on a fresh engine
+    let output = round_trip(addr, "type,client,tx,amount\ndeposit,3,3,2.0\n").await;
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n3,2,0,2,false\n"
+    );
+
+    server.abort();
+}
+
+#[tokio::test]
+async fn failed_connection_is_passed_to_the_handler() {
+    let addr = free_addr();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let engine = {
+        let errors = errors.clone();
+        TransactionEngine::builder()
+            .on_connection_error(move |err| errors.lock().unwrap().push(err))
+            .build()
+    };
+    let server = tokio::spawn(async move { engine.serve_tcp(addr).await });
+
+    round_trip(addr, "type,type,client,tx,amount\n").await;
+    // the handler runs once processing fails, while the connection is closed
+    for _ in 0..100 {
+        if !errors.lock().unwrap().is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let errors = errors.lock().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(
+        matches!(&errors[0], TransactionError::DuplicateHeader { .. }),
+        "{:?}",
+        errors[0]
+    );
+    server.abort();
+}
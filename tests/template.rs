@@ -0,0 +1,49 @@
+use transactions_demo::tx_engine::{Decimal, TransactionEngine};
+
+async fn template() -> Vec<u8> {
+    let mut template = Vec::new();
+    TransactionEngine::write_csv_template(&mut template)
+        .await
+        .unwrap();
+    template
+}
+
+#[tokio::test]
+async fn template_processes_with_comment_lines_skipped() {
+    let mut engine = TransactionEngine::builder()
+        .with_skip_comment_lines(true)
+        .build();
+    let mut output = Vec::new();
+    engine
+        .process(template().await.as_slice(), &mut output)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "client,available,held,total,locked\n1,7.5,0,7.5,false\n"
+    );
+}
+
+#[tokio::test]
+async fn comment_lines_are_not_skipped_by_default() {
+    let mut engine = TransactionEngine::builder().build();
+    let result = engine
+        .process(template().await.as_slice(), tokio::io::sink())
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn indented_comment_lines_are_skipped() {
+    let mut engine = TransactionEngine::builder()
+        .with_skip_comment_lines(true)
+        .build();
+    let input = "type,client,tx,amount\n  # a comment\ndeposit,1,1,2.0\n\t#deposit,1,2,3.0\n";
+    engine
+        .process(input.as_bytes(), tokio::io::sink())
+        .await
+        .unwrap();
+
+    assert_eq!(engine.full_report().accounts[0].total(), Decimal::new(2, 0));
+}
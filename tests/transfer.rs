@@ -0,0 +1,124 @@
+use transactions_demo::tx_engine::{
+    Decimal, InputRow, RejectionReason, TransactionEngine, TransactionError,
+};
+
+mod common;
+use common::process;
+
+#[tokio::test]
+async fn transfer_moves_available_funds_to_target_client() {
+    let mut engine = TransactionEngine::builder().build();
+    let output = process(
+        &mut engine,
+        "type,client,tx,amount,target_client\ndeposit,1,1,10.0,\ntransfer,1,2,4.0,2\n",
+    )
+    .await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,6,0,6,false\n2,4,0,4,false\n"
+    );
+}
+
+#[tokio::test]
+async fn rejected_transfer_credits_nothing() {
+    let mut engine = TransactionEngine::builder().build();
+    let input = "\
+type,client,tx,amount,target_client
+deposit,1,1,10.0,
+transfer,1,2,11.0,2
+transfer,1,3,1.0,1
+";
+    let output = process(&mut engine, input).await;
+
+    // the target of the rejected transfer never got an account
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,10,0,10,false\n"
+    );
+    let reasons = engine
+        .rejections()
+        .iter()
+        .map(|rejection| rejection.reason)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        [
+            RejectionReason::InsufficientFunds,
+            RejectionReason::TransferToSelf
+        ]
+    );
+}
+
+#[tokio::test]
+async fn transfer_cannot_be_disputed() {
+    let mut engine = TransactionEngine::builder().build();
+    let input = "\
+type,client,tx,amount,target_client
+deposit,1,1,10.0,
+transfer,1,2,4.0,2
+dispute,1,2,,
+dispute,2,2,,
+";
+    let output = process(&mut engine, input).await;
+
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,6,0,6,false\n2,4,0,4,false\n"
+    );
+    assert_eq!(engine.rejections().len(), 2);
+    assert!(engine
+        .rejections()
+        .iter()
+        .all(|rejection| rejection.reason == RejectionReason::TransactionNotFound));
+}
+
+#[tokio::test]
+async fn transfer_without_target_client_is_an_error() {
+    let mut engine = TransactionEngine::builder().build();
+    let result = engine
+        .process(
+            "type,client,tx,amount\ndeposit,1,1,10.0\ntransfer,1,2,4.0\n".as_bytes(),
+            tokio::io::sink(),
+        )
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::MissingTransferTarget { tx: 2 })
+    ));
+}
+
+#[tokio::test]
+async fn transfer_between_ledgers_of_a_client() {
+    let mut engine = TransactionEngine::builder().with_multi_ledger(true).build();
+    let input = "\
+type,client,tx,amount,ledger,target_client,target_ledger
+deposit,1,1,10.0,checking,,
+transfer,1,2,4.0,checking,1,savings
+";
+    let output = process(&mut engine, input).await;
+
+    assert_eq!(
+        output,
+        "client,ledger,available,held,total,locked\n1,checking,6,0,6,false\n1,savings,4,0,4,false\n"
+    );
+}
+
+#[test]
+fn sharded_transfer_to_another_shard_is_an_error() {
+    let mut engine = TransactionEngine::builder().build_sharded(2);
+    let result = engine.process_rows(vec![
+        InputRow::new("deposit", 1, 1, Some(Decimal::TEN)),
+        InputRow::transfer(1, 2, Decimal::ONE, 2),
+    ]);
+
+    assert!(matches!(
+        result,
+        Err(TransactionError::CrossShardTransfer { tx: 2 })
+    ));
+    assert!(engine
+        .shards()
+        .iter()
+        .all(|shard| shard.client_ids().is_empty()));
+}
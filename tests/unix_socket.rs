@@ -0,0 +1,60 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use transactions_demo::tx_engine::TransactionEngine;
+
+/// Connects once the server has bound the socket
+async fn connect(path: &std::path::Path) -> UnixStream {
+    for _ in 0..100 {
+        if let Ok(stream) = UnixStream::connect(path).await {
+            return stream;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    panic!("the server did not bind {}", path.display());
+}
+
+async fn round_trip(path: &std::path::Path, input: &str) -> String {
+    let mut stream = connect(path).await;
+    stream.write_all(input.as_bytes()).await.unwrap();
+    stream.shutdown().await.unwrap();
+
+    let mut output = String::new();
+    stream.read_to_string(&mut output).await.unwrap();
+    output
+}
+
+#[tokio::test]
+async fn serves_balances_over_unix_socket() {
+    let path = std::env::temp_dir().join(format!("tx-engine-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let engine = TransactionEngine::builder().build();
+    let server = {
+        let path = path.clone();
+        tokio::spawn(async move { engine.serve_unix(path).await })
+    };
+
+    let output = round_trip(
+        &path,
+        "type,client,tx,amount\ndeposit,1,1,3.0\ndeposit,2,2,1.5\ndispute,1,1,\n",
+    )
+    .await;
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n1,0,3,3,false\n2,1.5,0,1.5,false\n"
+    );
+
+    // every connection is processed on a fresh engine
+    let output = round_trip(&path, "type,client,tx,amount\ndeposit,3,3,2.0\n").await;
+    assert_eq!(
+        output,
+        "client,available,held,total,locked\n3,2,0,2,false\n"
+    );
+
+    server.abort();
+    let _ = std::fs::remove_file(&path);
+}